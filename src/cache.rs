@@ -7,6 +7,9 @@ use std::path::PathBuf;
 const CACHE_DIR: &str = "rando-qc";
 const TRAIL_DATA_FILE: &str = "sentieretel.json";
 const ELEVATION_CACHE_FILE: &str = "elevation_cache.json";
+const ALERTS_CACHE_DIR: &str = "alerts";
+const CLIMATE_CACHE_DIR: &str = "climate";
+const RECENT_CACHE_DIR: &str = "recent";
 
 pub fn get_cache_dir() -> Result<PathBuf> {
     let cache_dir = dirs::cache_dir()
@@ -57,27 +60,140 @@ fn get_elevation_cache_path() -> Result<PathBuf> {
     Ok(get_cache_dir()?.join(ELEVATION_CACHE_FILE))
 }
 
+pub fn elevation_key(trail_name: &str, park_name: &str) -> String {
+    format!("{}|{}", trail_name, park_name)
+}
+
+fn read_elevation_cache() -> ElevationCache {
+    get_elevation_cache_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
 pub fn get_cached_elevation(trail_name: &str, park_name: &str) -> Option<Vec<f64>> {
-    let path = get_elevation_cache_path().ok()?;
-    let data = fs::read_to_string(&path).ok()?;
-    let cache: ElevationCache = serde_json::from_str(&data).ok()?;
+    read_elevation_cache()
+        .trails
+        .remove(&elevation_key(trail_name, park_name))
+}
 
-    let key = format!("{}|{}", trail_name, park_name);
-    cache.trails.get(&key).cloned()
+/// Every cached profile, keyed by `elevation_key`
+pub fn get_all_cached_elevations() -> HashMap<String, Vec<f64>> {
+    read_elevation_cache().trails
 }
 
 pub fn cache_elevation(trail_name: &str, park_name: &str, elevations: &[f64]) -> Result<()> {
+    cache_elevations(vec![(
+        elevation_key(trail_name, park_name),
+        elevations.to_vec(),
+    )])
+}
+
+/// Add several profiles with a single rewrite of the cache file
+pub fn cache_elevations(entries: Vec<(String, Vec<f64>)>) -> Result<()> {
     let path = get_elevation_cache_path()?;
+    let mut cache = read_elevation_cache();
+    cache.trails.extend(entries);
 
-    let mut cache: ElevationCache = fs::read_to_string(&path)
-        .ok()
-        .and_then(|data| serde_json::from_str(&data).ok())
-        .unwrap_or_default();
+    // Write to a temporary file first so an interrupted write can't corrupt the cache
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string(&cache)?).context("Failed to write elevation cache")?;
+    fs::rename(&tmp, &path).context("Failed to write elevation cache")?;
 
-    let key = format!("{}|{}", trail_name, park_name);
-    cache.trails.insert(key, elevations.to_vec());
+    Ok(())
+}
 
-    fs::write(&path, serde_json::to_string_pretty(&cache)?)?;
+fn get_alerts_cache_dir() -> Result<PathBuf> {
+    let dir = get_cache_dir()?.join(ALERTS_CACHE_DIR);
+    fs::create_dir_all(&dir).context("Failed to create alerts cache directory")?;
+    Ok(dir)
+}
 
-    Ok(())
+/// CAP files never change once published, so they are cached by file name
+pub fn get_cached_alert(file_name: &str) -> Option<String> {
+    let path = get_alerts_cache_dir().ok()?.join(file_name);
+    fs::read_to_string(path).ok()
+}
+
+pub fn cache_alert(file_name: &str, xml: &str) -> Result<()> {
+    let path = get_alerts_cache_dir()?.join(file_name);
+    fs::write(path, xml).context("Failed to write cached alert")
+}
+
+/// Files of a Datamart hour directory, cached once the hour is over
+pub fn get_cached_alert_listing(key: &str) -> Option<Vec<String>> {
+    let path = get_alerts_cache_dir()
+        .ok()?
+        .join(format!("{}.listing", key));
+    let text = fs::read_to_string(path).ok()?;
+    Some(text.lines().map(str::to_string).collect())
+}
+
+pub fn cache_alert_listing(key: &str, files: &[String]) -> Result<()> {
+    let path = get_alerts_cache_dir()?.join(format!("{}.listing", key));
+    fs::write(path, files.join("\n")).context("Failed to write cached alert listing")
+}
+
+/// Remove cached alerts and listings last written more than `max_age` ago
+pub fn prune_alerts(max_age: std::time::Duration) {
+    let Ok(entries) = get_alerts_cache_dir().and_then(|dir| Ok(fs::read_dir(dir)?)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > max_age);
+        if expired {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn get_climate_cache_dir() -> Result<PathBuf> {
+    let dir = get_cache_dir()?.join(CLIMATE_CACHE_DIR);
+    fs::create_dir_all(&dir).context("Failed to create climate cache directory")?;
+    Ok(dir)
+}
+
+/// Archive responses cover closed date ranges, so they never go stale
+pub fn get_cached_climate(key: &str) -> Option<String> {
+    let path = get_climate_cache_dir().ok()?.join(format!("{}.json", key));
+    fs::read_to_string(path).ok()
+}
+
+pub fn cache_climate(key: &str, json: &str) -> Result<()> {
+    let path = get_climate_cache_dir()?.join(format!("{}.json", key));
+    fs::write(path, json).context("Failed to write cached climate data")
+}
+
+fn get_recent_cache_dir() -> Result<PathBuf> {
+    let dir = get_cache_dir()?.join(RECENT_CACHE_DIR);
+    fs::create_dir_all(&dir).context("Failed to create recent weather cache directory")?;
+    Ok(dir)
+}
+
+/// Recent weather only changes once a day, so it's cached per day (YYYY-MM-DD)
+pub fn get_cached_recent(day: &str, key: &str) -> Option<String> {
+    let path = get_recent_cache_dir()
+        .ok()?
+        .join(format!("{}_{}.json", day, key));
+    fs::read_to_string(path).ok()
+}
+
+/// Cache a response for `day`, dropping those of earlier days
+pub fn cache_recent(day: &str, key: &str, json: &str) -> Result<()> {
+    let dir = get_recent_cache_dir()?;
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            if !entry.file_name().to_string_lossy().starts_with(day) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+    fs::write(dir.join(format!("{}_{}.json", day, key)), json)
+        .context("Failed to write cached recent weather")
 }
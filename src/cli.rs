@@ -1,5 +1,6 @@
-use crate::trails::Difficulty;
-use clap::{Parser, Subcommand};
+use crate::conditions::RiskLevel;
+use crate::trails::{Difficulty, TrailSort};
+use clap::{Args, Parser, Subcommand};
 use std::str::FromStr;
 
 fn parse_f64(s: &str) -> Result<f64, String> {
@@ -13,6 +14,18 @@ fn parse_f64(s: &str) -> Result<f64, String> {
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Unit system for output (metric, imperial)
+    #[arg(long, global = true)]
+    pub units: Option<String>,
+
+    /// Clock format for times of day (24h, 12h)
+    #[arg(long, global = true)]
+    pub clock: Option<String>,
+
+    /// Decimal separator for numbers (e.g. ",")
+    #[arg(long, global = true)]
+    pub decimal_separator: Option<char>,
 }
 
 #[derive(Subcommand)]
@@ -38,6 +51,26 @@ pub enum Commands {
         /// Filter by park name (partial match)
         #[arg(short, long)]
         park: Option<String>,
+
+        /// Maximum mud/ice/snow risk from recent weather (low, moderate, high)
+        #[arg(long)]
+        max_risk: Option<String>,
+
+        /// Only trails with enough snow on the ground for snowshoeing
+        #[arg(long)]
+        snowshoe_ready: bool,
+
+        /// Minimum elevation gain (m), needs `rando update --elevation`
+        #[arg(long)]
+        min_gain: Option<f64>,
+
+        /// Maximum elevation gain (m), needs `rando update --elevation`
+        #[arg(long)]
+        max_gain: Option<f64>,
+
+        /// Sort by climb, length or distance instead of by park
+        #[arg(long)]
+        sort: Option<String>,
     },
 
     /// Show trails in a specific park
@@ -53,7 +86,11 @@ pub enum Commands {
     },
 
     /// Update cached trail data
-    Update,
+    Update {
+        /// Prefetch elevation profiles for every trail instead (resumable)
+        #[arg(long)]
+        elevation: bool,
+    },
 
     /// Display trail info card
     Card {
@@ -77,6 +114,9 @@ pub enum Commands {
         /// Show 7-day forecast with best day recommendation
         #[arg(long)]
         week: bool,
+        /// Scoring profile for rating days (summer, winter-snowshoe, photography, or one from config)
+        #[arg(long)]
+        profile: Option<String>,
     },
 
     /// Find trails near a location
@@ -113,10 +153,14 @@ pub enum Commands {
         max_distance: Option<f64>,
     },
 
-    /// Log a completed hike
+    /// Log a completed hike, or manage logged hikes
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Log {
+        #[command(subcommand)]
+        command: Option<LogCommands>,
         /// Trail name (partial match)
-        trail: String,
+        #[arg(required = true)]
+        trail: Option<String>,
         /// Duration (e.g., "4h30m", "2h15m")
         #[arg(long)]
         time: Option<String>,
@@ -126,13 +170,51 @@ pub enum Commands {
         /// Notes about the hike
         #[arg(long)]
         notes: Option<String>,
+        #[command(flatten)]
+        details: Box<HikeDetailArgs>,
+        /// Ask for the details not given as flags
+        #[arg(short, long)]
+        interactive: bool,
     },
 
     /// Show personal hiking statistics
-    Stats,
+    Stats {
+        /// Only hikes of this year
+        #[arg(long, conflicts_with_all = ["from", "to"])]
+        year: Option<i32>,
+        /// Earliest date (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<String>,
+        /// Latest date (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<String>,
+        /// Only hikes in this park (name or code)
+        #[arg(long)]
+        park: Option<String>,
+        /// Compare totals year by year
+        #[arg(long, conflicts_with = "year")]
+        compare_years: bool,
+        /// Open an interactive dashboard
+        #[arg(long, conflicts_with = "compare_years")]
+        tui: bool,
+    },
 
-    /// Show current hiking streak
-    Streak,
+    /// Show your hiking pace calibrated from logged hikes
+    Pace,
+
+    /// Show how much of each park you've hiked, and your achievements
+    Progress {
+        /// Show one park's remaining trails (name or code)
+        #[arg(long)]
+        park: Option<String>,
+    },
+
+    /// Show hiking streaks and progress toward your goals
+    Streak {
+        /// Streak period: daily, weekly or monthly
+        #[arg(long, default_value = "weekly")]
+        cadence: String,
+    },
 
     /// Check if you can finish trail before dark
     Daylight {
@@ -149,14 +231,180 @@ pub enum Commands {
     /// Show active hunting seasons
     Hunt,
 
-    /// Check for park alerts
-    Alerts,
+    /// Check for park and weather alerts
+    Alerts {
+        /// Only show weather alerts covering this trail
+        #[arg(long)]
+        trail: Option<String>,
+    },
 
     /// Generate shareable trail info
     Share {
         /// Trail name (partial match)
         trail: String,
     },
+
+    /// Check thunderstorm risk and lightning exposure on summits
+    Storm {
+        /// Trail name (partial match)
+        trail: String,
+        /// Planned start time (HH:MM, default: next hour)
+        #[arg(long)]
+        start: Option<String>,
+    },
+
+    /// Show climate normals and the best months to hike a trail
+    Climate {
+        /// Trail name (partial match)
+        trail: String,
+        /// Years of history to average
+        #[arg(long, default_value = "10")]
+        years: u32,
+        /// Scoring profile for ranking months (summer, winter-snowshoe, photography, or one from config)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+
+    /// Show a trail's elevation profile with grades and climbs
+    Profile {
+        /// Trail name (partial match)
+        trail: String,
+        /// Grade (%) to report distance and time above
+        #[arg(long, default_value = "15")]
+        grade: f64,
+    },
+
+    /// Manage local elevation (DEM) tiles
+    Dem {
+        #[command(subcommand)]
+        command: DemCommands,
+    },
+}
+
+/// Optional details of a logged hike
+#[derive(Args)]
+pub struct HikeDetailArgs {
+    /// Start time (HH:MM)
+    #[arg(long)]
+    pub start: Option<String>,
+    /// Who came along (comma-separated)
+    #[arg(long = "with", value_delimiter = ',')]
+    pub companions: Vec<String>,
+    /// Rating from 1 to 5
+    #[arg(long)]
+    pub rating: Option<u8>,
+    /// How hard it felt: facile, intermediaire or difficile
+    #[arg(long)]
+    pub felt: Option<String>,
+    /// Conditions met: mud, ice, snow, water, bugs, deadfall, wind (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    pub conditions: Vec<String>,
+    /// Wildlife seen (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    pub wildlife: Vec<String>,
+    /// Photo file (repeatable)
+    #[arg(long = "photo")]
+    pub photos: Vec<String>,
+    /// Distance actually hiked in km, when different from the trail
+    #[arg(long)]
+    pub distance: Option<f64>,
+}
+
+#[derive(Subcommand)]
+pub enum LogCommands {
+    /// List logged hikes with their IDs
+    List {
+        /// Earliest date (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<String>,
+        /// Latest date (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<String>,
+        /// Filter by park name (partial match)
+        #[arg(long)]
+        park: Option<String>,
+        /// Filter by trail name (partial match)
+        #[arg(long)]
+        trail: Option<String>,
+    },
+
+    /// Show every field of a logged hike
+    Show {
+        /// Hike ID (see `rando log list`)
+        id: u32,
+    },
+
+    /// Change the date, duration, notes or details of a logged hike
+    Edit {
+        /// Hike ID (see `rando log list`)
+        id: u32,
+        /// Duration (e.g., "4h30m", "2h15m")
+        #[arg(long)]
+        time: Option<String>,
+        /// Date (YYYY-MM-DD format)
+        #[arg(long)]
+        date: Option<String>,
+        /// Notes about the hike ("" to clear)
+        #[arg(long)]
+        notes: Option<String>,
+        #[command(flatten)]
+        details: Box<HikeDetailArgs>,
+        /// Details to remove: start, with, rating, felt, conditions, wildlife, photo, distance (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        clear: Vec<String>,
+    },
+
+    /// Log a hike from a recorded GPX or FIT track, or restore a CSV export
+    Import {
+        /// Track file (.gpx or .fit) or CSV export
+        file: String,
+        /// gpx, fit or csv (default: from the file extension)
+        #[arg(long)]
+        format: Option<String>,
+        /// Trail name (partial match), instead of matching the track
+        #[arg(long)]
+        trail: Option<String>,
+        /// Notes about the hike
+        #[arg(long)]
+        notes: Option<String>,
+    },
+
+    /// Export logged hikes for spreadsheets, maps or calendars
+    Export {
+        /// csv, json, gpx or ics
+        #[arg(long, default_value = "csv")]
+        format: String,
+        /// Earliest date (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<String>,
+        /// Latest date (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<String>,
+        /// Output file path
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Fetch the weather of logged hikes that don't have it yet
+    Weather,
+
+    /// Delete a logged hike
+    Delete {
+        /// Hike ID (see `rando log list`)
+        id: u32,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DemCommands {
+    /// List the SRTM tiles covering a park, with download links
+    FetchList {
+        /// Park name or code (e.g., jacques-cartier, jac, mot)
+        park: String,
+    },
 }
 
 impl Commands {
@@ -194,4 +442,39 @@ impl Commands {
             _ => None,
         }
     }
+
+    pub fn get_max_risk(&self) -> Option<RiskLevel> {
+        match self {
+            Commands::List { max_risk, .. } => max_risk.as_ref().and_then(|r| r.parse().ok()),
+            _ => None,
+        }
+    }
+
+    pub fn get_snowshoe_ready(&self) -> bool {
+        match self {
+            Commands::List { snowshoe_ready, .. } => *snowshoe_ready,
+            _ => false,
+        }
+    }
+
+    pub fn get_min_gain(&self) -> Option<f64> {
+        match self {
+            Commands::List { min_gain, .. } => *min_gain,
+            _ => None,
+        }
+    }
+
+    pub fn get_max_gain(&self) -> Option<f64> {
+        match self {
+            Commands::List { max_gain, .. } => *max_gain,
+            _ => None,
+        }
+    }
+
+    pub fn get_sort(&self) -> Option<TrailSort> {
+        match self {
+            Commands::List { sort, .. } => sort.as_ref().and_then(|s| s.parse().ok()),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,114 @@
+use crate::scoring::{DayScore, ScoringProfile, score_day};
+use crate::services::weather::{ClimateDay, DailyWeather};
+use chrono::{Datelike, NaiveDate};
+
+/// Precipitation (mm) from which a day counts as wet
+const WET_DAY_MM: f64 = 1.0;
+
+/// Snow depth (cm) from which the ground counts as snow covered
+const SNOW_COVER_CM: f64 = 2.0;
+
+/// Average conditions of one calendar month over the history
+#[derive(Debug, Clone)]
+pub struct MonthlyNormals {
+    /// 1-12
+    pub month: u32,
+    pub max_temp: f64,
+    pub min_temp: f64,
+    /// Mean daily precipitation in mm
+    pub precipitation: f64,
+    /// Days per month with at least 1mm of precipitation
+    pub wet_days: f64,
+    /// Days per month with snow on the ground
+    pub snow_days: f64,
+    pub wind_speed: f64,
+    pub daylight_hours: f64,
+    /// Years that contributed to the averages
+    pub years: usize,
+}
+
+impl MonthlyNormals {
+    fn days_in_month(&self) -> f64 {
+        let (year, month) = if self.month == 12 {
+            (2001, 1)
+        } else {
+            (2000, self.month + 1)
+        };
+        NaiveDate::from_ymd_opt(year, month, 1)
+            .and_then(|d| d.pred_opt())
+            .map(|d| d.day() as f64)
+            .unwrap_or(30.0)
+    }
+
+    /// A typical day of the month, for rating with a scoring profile
+    pub fn typical_day(&self) -> DailyWeather {
+        DailyWeather {
+            date: NaiveDate::from_ymd_opt(2000, self.month, 15).unwrap_or_default(),
+            max_temp: self.max_temp,
+            min_temp: self.min_temp,
+            precipitation: self.precipitation,
+            precipitation_probability: Some(self.wet_days / self.days_in_month() * 100.0),
+            wind_speed: self.wind_speed,
+            weather_code: 0,
+            uv_index: None,
+            cloud_cover: None,
+            daylight_hours: self.daylight_hours,
+            ensemble: None,
+        }
+    }
+
+    pub fn score(&self, profile: &ScoringProfile) -> DayScore {
+        score_day(&self.typical_day(), profile)
+    }
+}
+
+/// Per-month averages of a daily history; months with no data are left out
+pub fn monthly_normals(history: &[ClimateDay]) -> Vec<MonthlyNormals> {
+    (1..=12)
+        .filter_map(|month| {
+            let days: Vec<&ClimateDay> =
+                history.iter().filter(|d| d.date.month() == month).collect();
+            if days.is_empty() {
+                return None;
+            }
+
+            let mut years: Vec<i32> = days.iter().map(|d| d.date.year()).collect();
+            years.dedup();
+            let year_count = years.len();
+            let mean = |value: fn(&ClimateDay) -> f64| {
+                days.iter().map(|d| value(d)).sum::<f64>() / days.len() as f64
+            };
+            let per_year = |count: usize| count as f64 / year_count as f64;
+
+            Some(MonthlyNormals {
+                month,
+                max_temp: mean(|d| d.max_temp),
+                min_temp: mean(|d| d.min_temp),
+                precipitation: mean(|d| d.precipitation),
+                wet_days: per_year(
+                    days.iter()
+                        .filter(|d| d.precipitation >= WET_DAY_MM)
+                        .count(),
+                ),
+                snow_days: per_year(
+                    days.iter()
+                        .filter(|d| d.snow_depth.is_some_and(|depth| depth >= SNOW_COVER_CM))
+                        .count(),
+                ),
+                wind_speed: mean(|d| d.wind_speed),
+                daylight_hours: mean(|d| d.daylight_hours),
+                years: year_count,
+            })
+        })
+        .collect()
+}
+
+/// Months ranked best first by the profile's score of their typical day
+pub fn best_months(normals: &[MonthlyNormals], profile: &ScoringProfile) -> Vec<(u32, DayScore)> {
+    let mut ranked: Vec<(u32, DayScore)> = normals
+        .iter()
+        .map(|n| (n.month, n.score(profile)))
+        .collect();
+    ranked.sort_by_key(|(_, score)| std::cmp::Reverse(score.score));
+    ranked
+}
@@ -1,9 +1,75 @@
 use crate::icons::Icons;
+use crate::services::alerts::{get_active_alerts, get_alerts_for_location};
+use crate::trails::{find_trail_by_name, load_trails};
+use crate::units;
 use anyhow::Result;
+use colored::Colorize;
+
+pub fn handle_alerts(trail_name: Option<&str>) -> Result<()> {
+    if let Some(name) = trail_name {
+        let trails = load_trails()?;
+        let trail = find_trail_by_name(&trails, name)
+            .ok_or_else(|| anyhow::anyhow!("Trail not found: {}", name))?;
+
+        println!(
+            "\n{}  Weather Alerts: {}\n",
+            Icons::ALERT,
+            trail.name.bold()
+        );
+        let alerts = get_alerts_for_location(trail.lat, trail.lng, &trail.park)?;
+        if alerts.is_empty() {
+            println!(
+                "  {} No weather alerts in effect for {}",
+                Icons::CHECK.green(),
+                trail.park
+            );
+        } else {
+            super::print_weather_alerts(&alerts);
+            for alert in &alerts {
+                let from = alert
+                    .effective
+                    .map(|e| units::get().day_and_time(&e))
+                    .unwrap_or_else(|| "now".to_string());
+                println!(
+                    "\n  {} (severity: {}, from {})",
+                    alert.event.bold(),
+                    alert.severity.to_lowercase(),
+                    from
+                );
+                if !alert.description.is_empty() {
+                    println!("  {}", alert.description.replace('\n', "\n  "));
+                }
+            }
+        }
+        return Ok(());
+    }
 
-pub fn handle_alerts() -> Result<()> {
     println!("\n{}  Park Alerts\n", Icons::ALERT);
 
+    match get_active_alerts() {
+        Ok(alerts) if alerts.is_empty() => {
+            println!(
+                "  {} No Environment Canada alerts in effect for Quebec\n",
+                Icons::CHECK.green()
+            );
+        }
+        Ok(alerts) => {
+            println!("  Environment Canada alerts in effect:\n");
+            for alert in &alerts {
+                super::print_weather_alerts(std::slice::from_ref(alert));
+                println!("     {}", alert.area_names());
+            }
+            println!();
+        }
+        Err(e) => {
+            println!(
+                "  {} Weather alerts unavailable: {}\n",
+                Icons::WARNING.yellow(),
+                e
+            );
+        }
+    }
+
     let parks = vec![
         ("Jacques-Cartier", "jac"),
         ("Mont-Tremblant", "mot"),
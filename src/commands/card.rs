@@ -1,9 +1,13 @@
-use crate::cache;
+use crate::conditions::{RiskLevel, Traction, TrailRisk, WinterConditions, get_recent_conditions};
+use crate::config::load_config;
+use crate::estimate::{Estimator, TimeEstimate};
 use crate::icons::Icons;
-use crate::services::elevation::{
-    ElevationStats, calculate_elevation_stats, fetch_elevation, sample_coordinates,
-};
+use crate::profile::{ElevationProfile, get_trail_profile};
+use crate::services::air_quality::{AirQuality, AqiLevel, get_air_quality};
+use crate::services::alerts::{AlertKind, WeatherAlert, get_alerts_for_location};
+use crate::services::elevation::{ElevationStats, calculate_elevation_stats};
 use crate::trails::Trail;
+use crate::units;
 use anyhow::Result;
 use chrono::{Datelike, Local};
 use crossterm::{
@@ -22,10 +26,35 @@ use ratatui::{
 use std::f64::consts::PI;
 use std::io::{self, stdout};
 
+/// Everything fetched over the network before the card is drawn
+struct CardData {
+    profile: ElevationProfile,
+    elevation_stats: ElevationStats,
+    risk: Option<TrailRisk>,
+    winter: Option<WinterConditions>,
+    alerts: Option<Vec<WeatherAlert>>,
+    air: Option<AirQuality>,
+    estimate: TimeEstimate,
+}
+
 pub fn print_card(trail: &Trail) -> Result<()> {
     // Fetch elevation data before entering TUI
     println!("Fetching elevation data...");
-    let elevation_stats = get_trail_elevation(trail);
+    let (profile, elevation_stats) = get_elevation_stats(trail);
+    let estimate = Estimator::for_user(&load_config()?).estimate(trail.length_km, Some(&profile));
+    println!("Checking recent conditions...");
+    let (risk, winter) = get_recent_conditions(trail).ok().unzip();
+    let alerts = get_alerts_for_location(trail.lat, trail.lng, &trail.park).ok();
+    let air = get_air_quality(trail.lat, trail.lng).ok();
+    let data = CardData {
+        profile,
+        elevation_stats,
+        risk,
+        winter,
+        alerts,
+        air,
+        estimate,
+    };
 
     enable_raw_mode()?;
     let mut stdout = stdout();
@@ -33,7 +62,7 @@ pub fn print_card(trail: &Trail) -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let result = run_card_ui(&mut terminal, trail, &elevation_stats);
+    let result = run_card_ui(&mut terminal, trail, &data);
 
     disable_raw_mode()?;
     execute!(
@@ -49,10 +78,10 @@ pub fn print_card(trail: &Trail) -> Result<()> {
 fn run_card_ui(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     trail: &Trail,
-    elevation_stats: &ElevationStats,
+    data: &CardData,
 ) -> Result<()> {
     loop {
-        terminal.draw(|f| ui(f, trail, elevation_stats))?;
+        terminal.draw(|f| ui(f, trail, data))?;
 
         if let Event::Key(key) = event::read()? {
             match key.code {
@@ -64,7 +93,10 @@ fn run_card_ui(
     Ok(())
 }
 
-fn ui(f: &mut Frame, trail: &Trail, elevation_stats: &ElevationStats) {
+fn ui(f: &mut Frame, trail: &Trail, data: &CardData) {
+    let elevation_stats = &data.elevation_stats;
+    let units = units::get();
+
     let size = f.size();
     let vertical = Layout::default()
         .direction(ratatui::layout::Direction::Vertical)
@@ -90,7 +122,7 @@ fn ui(f: &mut Frame, trail: &Trail, elevation_stats: &ElevationStats) {
         .direction(ratatui::layout::Direction::Vertical)
         .constraints([
             Constraint::Length(3),
-            Constraint::Length(5),
+            Constraint::Length(10),
             Constraint::Length(8),
             Constraint::Length(4),
             Constraint::Length(6),
@@ -105,12 +137,7 @@ fn ui(f: &mut Frame, trail: &Trail, elevation_stats: &ElevationStats) {
         .map(|d| d.to_string())
         .unwrap_or_else(|| "Non spécifié".to_string());
 
-    let estimated_hours = (trail.length_km / 3.0).ceil() as u32;
-    let estimated_time = if estimated_hours >= 2 {
-        format!("{}-{}h", estimated_hours - 1, estimated_hours + 1)
-    } else {
-        "1-2h".to_string()
-    };
+    let estimated_time = format!("{} ({})", data.estimate.range(), data.estimate.describe());
 
     let (sunrise, sunset, daylight) = calculate_sun_times(trail.lat, trail.lng);
 
@@ -142,7 +169,7 @@ fn ui(f: &mut Frame, trail: &Trail, elevation_stats: &ElevationStats) {
         Line::from(vec![
             Span::styled("Distance: ", Style::default().fg(Color::Gray)),
             Span::styled(
-                format!("{:.1}km", trail.length_km),
+                units.distance(trail.length_km, 1),
                 Style::default().fg(Color::White),
             ),
         ]),
@@ -154,12 +181,16 @@ fn ui(f: &mut Frame, trail: &Trail, elevation_stats: &ElevationStats) {
             Span::styled("Estimated time: ", Style::default().fg(Color::Gray)),
             Span::styled(&estimated_time, Style::default().fg(Color::White)),
         ]),
+        risk_line(data.risk.as_ref()),
+        snow_line(data.winter.as_ref()),
+        alerts_line(data.alerts.as_deref()),
+        air_line(data.air.as_ref()),
     ])
     .block(info_block)
     .alignment(Alignment::Left);
     f.render_widget(info, card_layout[1]);
 
-    let elevation_data = normalize_elevation_for_sparkline(elevation_stats);
+    let elevation_data = normalize_elevation_for_sparkline(&data.profile, elevation_stats);
 
     let elevation_sparkline = Sparkline::default()
         .block(
@@ -175,15 +206,33 @@ fn ui(f: &mut Frame, trail: &Trail, elevation_stats: &ElevationStats) {
 
     let stats_text = Line::from(vec![
         Span::styled(
-            format!("↑ Total gain: {:.0}m  ", elevation_stats.total_gain),
+            format!(
+                "↑ Total gain: {}  ",
+                units.elevation(elevation_stats.total_gain)
+            ),
             Style::default().fg(Color::Green),
         ),
         Span::styled(
-            format!("↓ Total loss: {:.0}m", elevation_stats.total_loss),
+            format!(
+                "↓ Total loss: {}  ",
+                units.elevation(elevation_stats.total_loss)
+            ),
             Style::default().fg(Color::Red),
         ),
+        Span::styled(
+            format!(
+                "(raw ↑{} ↓{})",
+                units.elevation(elevation_stats.raw_gain),
+                units.elevation(elevation_stats.raw_loss)
+            ),
+            Style::default().fg(Color::Gray),
+        ),
     ]);
-    let stats = Paragraph::new(stats_text)
+    let grade_text = Line::from(Span::styled(
+        grade_summary(&data.profile),
+        Style::default().fg(Color::Yellow),
+    ));
+    let stats = Paragraph::new(vec![stats_text, grade_text])
         .block(
             Block::default()
                 .borders(Borders::ALL)
@@ -239,7 +288,10 @@ fn ui(f: &mut Frame, trail: &Trail, elevation_stats: &ElevationStats) {
         ]),
         Line::from(vec![
             Span::styled("   Hospital: ", Style::default().fg(Color::Gray)),
-            Span::styled("CHU de Québec (47km)", Style::default().fg(Color::White)),
+            Span::styled(
+                format!("CHU de Québec ({})", units.distance(47.0, 0)),
+                Style::default().fg(Color::White),
+            ),
         ]),
     ];
     let emergency = Paragraph::new(emergency_text)
@@ -277,7 +329,9 @@ fn ui(f: &mut Frame, trail: &Trail, elevation_stats: &ElevationStats) {
     let footer_text = Line::from(vec![
         Span::styled("Generated: ", Style::default().fg(Color::Gray)),
         Span::styled(
-            Local::now().format("%Y-%m-%d %H:%M").to_string(),
+            Local::now()
+                .format(&format!("%Y-%m-%d {}", units.time_format()))
+                .to_string(),
             Style::default().fg(Color::White),
         ),
     ]);
@@ -318,58 +372,191 @@ fn ui(f: &mut Frame, trail: &Trail, elevation_stats: &ElevationStats) {
     f.render_widget(help, vertical[2]);
 }
 
-/// Get elevation data for a trail (cached or fetched)
-fn get_trail_elevation(trail: &Trail) -> ElevationStats {
-    if let Some(cached) = cache::get_cached_elevation(&trail.name, &trail.park) {
-        return calculate_elevation_stats(&cached);
+fn risk_line(risk: Option<&TrailRisk>) -> Line<'static> {
+    let label = Span::styled("Trail risk: ", Style::default().fg(Color::Gray));
+    match risk {
+        Some(risk) => {
+            let color = match risk.level() {
+                RiskLevel::Low => Color::Green,
+                RiskLevel::Moderate => Color::Yellow,
+                RiskLevel::High => Color::Red,
+            };
+            Line::from(vec![
+                label,
+                Span::styled(risk.level().to_string(), Style::default().fg(color)),
+                Span::styled(
+                    format!(" ({})", risk.summary()),
+                    Style::default().fg(Color::White),
+                ),
+            ])
+        }
+        None => Line::from(vec![
+            label,
+            Span::styled("unavailable", Style::default().fg(Color::Gray)),
+        ]),
+    }
+}
+
+fn snow_line(winter: Option<&WinterConditions>) -> Line<'static> {
+    let label = Span::styled("Snow: ", Style::default().fg(Color::Gray));
+    match winter {
+        Some(winter) => {
+            let color = match winter.traction {
+                Traction::None => Color::Green,
+                Traction::Microspikes => Color::Yellow,
+                Traction::Snowshoes => Color::Cyan,
+            };
+            Line::from(vec![
+                label,
+                Span::styled(winter.summary(), Style::default().fg(color)),
+            ])
+        }
+        None => Line::from(vec![
+            label,
+            Span::styled("unavailable", Style::default().fg(Color::Gray)),
+        ]),
     }
+}
 
-    let sampled = sample_coordinates(&trail.coordinates_wgs84, 50);
+fn alerts_line(alerts: Option<&[WeatherAlert]>) -> Line<'_> {
+    let label = Span::styled("Alerts: ", Style::default().fg(Color::Gray));
+    let Some(alerts) = alerts else {
+        return Line::from(vec![
+            label,
+            Span::styled("unavailable", Style::default().fg(Color::Gray)),
+        ]);
+    };
+    match alerts.first() {
+        Some(alert) => {
+            let color = if alert.kind == AlertKind::Warning {
+                Color::Red
+            } else {
+                Color::Yellow
+            };
+            let more = if alerts.len() > 1 {
+                format!(" (+{} more)", alerts.len() - 1)
+            } else {
+                String::new()
+            };
+            Line::from(vec![
+                label,
+                Span::styled(
+                    format!("{} {}", Icons::ALERT, alert.kind.label()),
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!(" {}{}", alert.event, more),
+                    Style::default().fg(Color::White),
+                ),
+            ])
+        }
+        None => Line::from(vec![
+            label,
+            Span::styled("None in effect", Style::default().fg(Color::Green)),
+        ]),
+    }
+}
 
-    match fetch_elevation(&sampled) {
-        Ok(elevations) => {
-            // Ignore cache errors - non-critical
-            let _ = cache::cache_elevation(&trail.name, &trail.park, &elevations);
-            calculate_elevation_stats(&elevations)
+fn air_line(air: Option<&AirQuality>) -> Line<'static> {
+    let label = Span::styled("Air quality: ", Style::default().fg(Color::Gray));
+    match air {
+        Some(air) => {
+            let color = match air.level() {
+                Some(AqiLevel::Good) => Color::Green,
+                Some(AqiLevel::Moderate) => Color::Yellow,
+                Some(_) => Color::Red,
+                None => Color::Gray,
+            };
+            let mut spans = vec![
+                label,
+                Span::styled(air.summary(), Style::default().fg(color)),
+            ];
+            if air.needs_warning() {
+                spans.push(Span::styled(
+                    format!(" {} mask or reschedule", Icons::WARNING),
+                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ));
+            }
+            Line::from(spans)
+        }
+        None => Line::from(vec![
+            label,
+            Span::styled("unavailable", Style::default().fg(Color::Gray)),
+        ]),
+    }
+}
+
+/// Steepest 500m and hardest climb, e.g. "Steepest 500m: 18% • 2 climbs (Cat 3)"
+fn grade_summary(profile: &ElevationProfile) -> String {
+    let units = units::get();
+    let steepest = match profile.steepest(0.5, true) {
+        Some(s) => format!("Steepest 500m: {}%", units.number(s.grade(), 0)),
+        None => "Steepest 500m: -".to_string(),
+    };
+    let climbs = profile.climbs();
+    match climbs.iter().filter_map(|c| c.category).max() {
+        Some(category) => format!(
+            "{} • {} climbs (hardest {})",
+            steepest,
+            climbs.len(),
+            category.label()
+        ),
+        None => format!("{} • {} climbs", steepest, climbs.len()),
+    }
+}
+
+/// Get elevation data for a trail (cached or fetched)
+fn get_elevation_stats(trail: &Trail) -> (ElevationProfile, ElevationStats) {
+    match get_trail_profile(trail) {
+        Ok(profile) => {
+            let stats = calculate_elevation_stats(&profile.elevations(), profile.length_km());
+            (profile, stats)
         }
         Err(e) => {
             println!("Warning: Could not fetch elevation data: {}", e);
-            ElevationStats {
-                min: 0.0,
-                max: 0.0,
-                total_gain: 0.0,
-                total_loss: 0.0,
-                elevations: Vec::new(),
-            }
+            (ElevationProfile::default(), ElevationStats::default())
         }
     }
 }
 
 /// Normalize elevation data to 0-100 range for sparkline display
-fn normalize_elevation_for_sparkline(stats: &ElevationStats) -> Vec<u64> {
+fn normalize_elevation_for_sparkline(
+    profile: &ElevationProfile,
+    stats: &ElevationStats,
+) -> Vec<u64> {
     const DISPLAY_POINTS: usize = 30;
     const DEFAULT_VALUE: u64 = 50;
 
-    if stats.elevations.is_empty() {
+    if profile.is_empty() {
         return vec![DEFAULT_VALUE; DISPLAY_POINTS];
     }
 
     let range = (stats.max - stats.min).max(1.0);
-    let step = stats.elevations.len() as f64 / DISPLAY_POINTS as f64;
 
-    (0..DISPLAY_POINTS)
-        .map(|i| {
-            let idx = (i as f64 * step) as usize;
-            if idx < stats.elevations.len() {
-                ((stats.elevations[idx] - stats.min) / range * 100.0).clamp(5.0, 95.0) as u64
-            } else {
-                DEFAULT_VALUE
-            }
-        })
+    // Evenly spaced by distance, not by sample
+    profile
+        .resample(DISPLAY_POINTS)
+        .iter()
+        .map(|elevation| ((elevation - stats.min) / range * 100.0).clamp(5.0, 95.0) as u64)
         .collect()
 }
 
 pub fn calculate_sun_times(lat: f64, lng: f64) -> (String, String, String) {
+    let (sunrise_hour, sunset_hour) = sun_hours(lat, lng);
+    let units = units::get();
+
+    let sunrise = units.time_of_day(sunrise_hour);
+    let sunset = units.time_of_day(sunset_hour);
+
+    let daylight_hours = (sunset_hour - sunrise_hour) as u32;
+    let daylight_mins = ((sunset_hour - sunrise_hour) * 60.0) as u32 % 60;
+    let daylight = format!("{}h {}min", daylight_hours, daylight_mins);
+
+    (sunrise, sunset, daylight)
+}
+
+/// Today's sunrise and sunset as fractional local hours
+pub fn sun_hours(lat: f64, lng: f64) -> (f64, f64) {
     let now = Local::now();
     let day_of_year = now.ordinal() as f64;
     let lat_rad = lat * PI / 180.0;
@@ -381,18 +568,5 @@ pub fn calculate_sun_times(lat: f64, lng: f64) -> (String, String, String) {
     let sunrise_hour = solar_noon - hour_angle * 12.0 / PI;
     let sunset_hour = solar_noon + hour_angle * 12.0 / PI;
 
-    let sunrise = format_time(sunrise_hour);
-    let sunset = format_time(sunset_hour);
-
-    let daylight_hours = (sunset_hour - sunrise_hour) as u32;
-    let daylight_mins = ((sunset_hour - sunrise_hour) * 60.0) as u32 % 60;
-    let daylight = format!("{}h {}min", daylight_hours, daylight_mins);
-
-    (sunrise, sunset, daylight)
-}
-
-fn format_time(hour: f64) -> String {
-    let h = hour.floor() as u32;
-    let m = ((hour - hour.floor()) * 60.0).round() as u32;
-    format!("{:02}:{:02}", h, m)
+    (sunrise_hour, sunset_hour)
 }
@@ -1,9 +1,15 @@
-use super::card::calculate_sun_times;
+use super::card::sun_hours;
+use crate::conditions::{RiskLevel, Traction, get_recent_conditions};
+use crate::estimate::estimate_trail;
 use crate::icons::Icons;
-use crate::services::weather::get_weather;
+use crate::services::air_quality::get_air_quality;
+use crate::services::alerts::{AlertKind, get_alerts_for_location};
+use crate::services::weather::{get_hourly_convection, get_weather};
+use crate::storm::storm_windows;
 use crate::trails::{find_trail_by_name, load_trails};
+use crate::units;
 use anyhow::Result;
-use chrono::{Datelike, Local};
+use chrono::{Datelike, Local, Timelike};
 use colored::Colorize;
 
 pub fn handle_checklist(trail_name: &str) -> Result<()> {
@@ -12,11 +18,21 @@ pub fn handle_checklist(trail_name: &str) -> Result<()> {
         .ok_or_else(|| anyhow::anyhow!("Trail not found: {}", trail_name))?;
 
     let weather = get_weather(trail.lat, trail.lng).ok();
-    let (_, _, daylight) = calculate_sun_times(trail.lat, trail.lng);
-    let estimated_hours = (trail.length_km / 3.0).ceil();
+    let (risk, winter) = get_recent_conditions(trail).ok().unzip();
+    let alerts = get_alerts_for_location(trail.lat, trail.lng, &trail.park).unwrap_or_default();
+    let air = get_air_quality(trail.lat, trail.lng).ok();
+    let (sunrise_hour, sunset_hour) = sun_hours(trail.lat, trail.lng);
+    let units = units::get();
+    let estimate = estimate_trail(trail)?;
+    // Pack for the slow end of the range
+    let estimated_hours = estimate.high;
 
     let month = Local::now().month();
-    let is_winter = month >= 11 || month <= 3;
+    // Snow on the ground decides; the calendar is only a fallback without history
+    let is_winter = match winter {
+        Some(ref winter) => winter.has_snow() || (month == 12 || month <= 2),
+        None => month >= 11 || month <= 3,
+    };
     let is_summer = (6..=8).contains(&month);
 
     println!(
@@ -29,34 +45,44 @@ pub fn handle_checklist(trail_name: &str) -> Result<()> {
         .map(|d| d.to_string())
         .unwrap_or_else(|| "Unknown".to_string());
     println!(
-        "Based on: {}, {:.1}km, ~{:.0}h, {}\n",
+        "Based on: {}, {}, ~{} ({}), {}\n",
         difficulty_display,
-        trail.length_km,
-        estimated_hours,
+        units.distance(trail.length_km, 1),
+        estimate.range(),
+        estimate.describe(),
         month_name(month)
     );
 
+    if !alerts.is_empty() {
+        println!("Weather alerts:");
+        super::print_weather_alerts(&alerts);
+        if alerts.iter().any(|a| a.kind == AlertKind::Warning) {
+            println!(
+                "  {} A warning is in effect - consider postponing\n",
+                Icons::WARNING.red()
+            );
+        } else {
+            println!();
+        }
+    }
+
     println!("Essentials:");
-    let water_liters = (estimated_hours / 2.0).ceil().max(1.0) as u32;
-    println!("  ☐ Water ({}L for this length)", water_liters);
+    let water_liters = (estimated_hours / 2.0).ceil().max(1.0);
+    println!("  ☐ Water ({} for this length)", units.volume(water_liters));
     println!("  ☐ Snacks/lunch");
     println!("  ☐ Map/GPS (phone + offline map)");
     println!("  ☐ First aid kit");
 
     // Check if need headlamp
-    let daylight_hours: f64 = daylight
-        .split('h')
-        .next()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(8.0);
+    let daylight_hours = sunset_hour - sunrise_hour;
     if daylight_hours < estimated_hours + 1.0 {
         println!("  ☐ Headlamp (short daylight!)");
     }
 
     if let Some(ref w) = weather {
         println!(
-            "\nClothing (current: {:.0}°C, {}):",
-            w.temperature,
+            "\nClothing (current: {}, {}):",
+            units.temperature(w.temperature),
             w.description()
         );
     } else {
@@ -71,7 +97,12 @@ pub fn handle_checklist(trail_name: &str) -> Result<()> {
         println!("  ☐ Warm hat + gloves");
         println!("  ☐ Extra socks");
         println!("\nWinter gear:");
-        println!("  ☐ Microspikes or crampons");
+        match winter.as_ref().map(|w| w.traction) {
+            Some(Traction::Snowshoes) => println!("  ☐ Snowshoes"),
+            Some(Traction::Microspikes) => println!("  ☐ Microspikes"),
+            Some(Traction::None) => {}
+            None => println!("  ☐ Microspikes or crampons"),
+        }
         println!("  ☐ Trekking poles");
         println!("  ☐ Hand warmers");
     } else if is_summer {
@@ -92,6 +123,54 @@ pub fn handle_checklist(trail_name: &str) -> Result<()> {
         println!("  ☐ Rain gear (rain expected)");
     }
 
+    let storms = get_hourly_convection(trail.lat, trail.lng)
+        .map(|hours| storm_windows(&hours, Local::now().date_naive()))
+        .unwrap_or_default();
+    if let Some(first) = storms.first() {
+        println!(
+            "  ☐ Early start - thunderstorms possible from {} (check rando storm)",
+            units.clock_time(first.start.hour(), 0)
+        );
+    }
+
+    if let Some(ref risk) = risk {
+        println!(
+            "\nTrail conditions ({} risk, {}):",
+            risk.level(),
+            risk.details()
+        );
+        if risk.mud >= 4 {
+            println!("  ☐ Waterproof boots + gaiters (muddy trails)");
+        }
+        if risk.ice >= 4 && !is_winter {
+            println!("  ☐ Microspikes (icy patches likely)");
+        }
+        if risk.level() == RiskLevel::Low {
+            println!("  Trails should be in good shape");
+        }
+    }
+
+    if let Some(ref winter) = winter
+        && (winter.has_snow() || winter.snowfall_7d > 0.0)
+    {
+        println!("\nSnow ({}):", winter.summary());
+        println!("  {}", winter.details());
+        if winter.crust_risk() {
+            println!("  ☐ Crampons or aggressive microspikes (icy crust)");
+        }
+        if winter.surface == crate::conditions::SnowSurface::FreshPowder {
+            println!("  ☐ Gaiters + allow extra time breaking trail");
+        }
+    }
+
+    if let Some(ref air) = air
+        && air.needs_warning()
+    {
+        println!("\nAir quality ({}):", air.summary());
+        println!("  ☐ N95/KN95 mask (smoke or pollution)");
+        println!("  ☐ Consider rescheduling, especially for long climbs");
+    }
+
     println!("\nEmergency:");
     println!("  ☐ Emergency blanket");
     println!("  ☐ Whistle");
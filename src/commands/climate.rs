@@ -0,0 +1,153 @@
+use crate::climate::{best_months, monthly_normals};
+use crate::config::load_config;
+use crate::icons::Icons;
+use crate::scoring::resolve_profile;
+use crate::services::weather::get_climate_history;
+use crate::trails::{find_trail_by_name, load_trails};
+use crate::units;
+use anyhow::Result;
+use colored::Colorize;
+
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Width of the temperature range chart in characters
+const CHART_WIDTH: usize = 40;
+
+pub fn handle_climate(trail_name: &str, years: u32, profile: Option<&str>) -> Result<()> {
+    if !(1..=30).contains(&years) {
+        anyhow::bail!("Invalid number of years: {}. Use 1 to 30", years);
+    }
+
+    let trails = load_trails()?;
+    let trail = find_trail_by_name(&trails, trail_name)
+        .ok_or_else(|| anyhow::anyhow!("Trail not found: {}", trail_name))?;
+    let config = load_config()?;
+    let (profile_name, profile) = resolve_profile(profile, &config)?;
+
+    println!("Fetching {} years of weather history...", years);
+    let history = get_climate_history(trail.lat, trail.lng, years)?;
+    let normals = monthly_normals(&history);
+    if normals.is_empty() {
+        anyhow::bail!("No weather history available for this location");
+    }
+
+    let units = units::get();
+    println!(
+        "\n{}  Climate Normals for {} ({}, {} years)",
+        Icons::WEATHER,
+        trail.name.bold(),
+        trail.park,
+        normals.iter().map(|n| n.years).max().unwrap_or(0)
+    );
+    println!("  Scoring profile: {}\n", profile_name);
+
+    println!(
+        "  {:<5} {:>13}  {:>4}  {:>5}  {:>8}  Score",
+        "Month", "High/Low", "Wet", "Snow", "Daylight"
+    );
+    for normal in &normals {
+        let score = normal.score(&profile);
+        let filled = (score.score as usize).div_ceil(10);
+        let bar = format!("{}{}", "█".repeat(filled), "░".repeat(10 - filled));
+        let bar = if score.is_good() {
+            bar.green()
+        } else if score.score >= 40 {
+            bar.yellow()
+        } else {
+            bar.red()
+        };
+        println!(
+            "  {:<5} {:>13}  {:>3}d  {:>4}d  {:>7}h  {} {:>3} {}",
+            MONTH_NAMES[normal.month as usize - 1],
+            format!(
+                "{}/{}",
+                units.temperature(normal.max_temp),
+                units.temperature(normal.min_temp)
+            ),
+            normal.wet_days.round(),
+            normal.snow_days.round(),
+            units.number(normal.daylight_hours, 1),
+            bar,
+            score.score,
+            score.label()
+        );
+    }
+
+    print_temperature_chart(&normals);
+
+    let ranked = best_months(&normals, &profile);
+    let best: Vec<&str> = ranked
+        .iter()
+        .filter(|(_, score)| score.is_good())
+        .take(3)
+        .map(|(month, _)| MONTH_NAMES[*month as usize - 1])
+        .collect();
+
+    if best.is_empty() {
+        let (month, score) = &ranked[0];
+        println!(
+            "\n  {} No month is typically good for this profile; best is {} ({})",
+            Icons::INFO,
+            MONTH_NAMES[*month as usize - 1],
+            score.explanation()
+        );
+    } else {
+        println!("\n  {} Best months: {}", Icons::SUCCESS, best.join(", "));
+    }
+
+    Ok(())
+}
+
+/// Daily low-to-high range of each month on a shared temperature axis
+fn print_temperature_chart(normals: &[crate::climate::MonthlyNormals]) {
+    let units = units::get();
+    let coldest = normals
+        .iter()
+        .map(|n| n.min_temp)
+        .fold(f64::INFINITY, f64::min)
+        .floor();
+    let warmest = normals
+        .iter()
+        .map(|n| n.max_temp)
+        .fold(f64::NEG_INFINITY, f64::max)
+        .ceil();
+    let span = (warmest - coldest).max(1.0);
+    let column =
+        |temp: f64| (((temp - coldest) / span) * (CHART_WIDTH - 1) as f64).round() as usize;
+
+    println!(
+        "\n  Temperature range ({} to {}):",
+        units.temperature(coldest),
+        units.temperature(warmest)
+    );
+    for normal in normals {
+        let low = column(normal.min_temp);
+        let high = column(normal.max_temp).max(low);
+        let line: String = (0..CHART_WIDTH)
+            .map(|i| {
+                if (low..=high).contains(&i) {
+                    '▓'
+                } else {
+                    '·'
+                }
+            })
+            .collect();
+        println!(
+            "  {:<5} {}",
+            MONTH_NAMES[normal.month as usize - 1],
+            line.cyan()
+        );
+    }
+
+    if coldest < 0.0 && warmest > 0.0 {
+        let freezing = column(0.0);
+        println!(
+            "  {:<5} {}^ {}",
+            "",
+            " ".repeat(freezing),
+            units.temperature(0.0)
+        );
+    }
+}
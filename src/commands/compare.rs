@@ -1,5 +1,4 @@
-use crate::cache;
-use crate::services::elevation::{DEFAULT_SAMPLE_POINTS, fetch_elevation, sample_coordinates};
+use crate::profile::get_trail_profile;
 use crate::trails::{find_trail_by_name, load_trails};
 use crate::tui;
 use anyhow::Result;
@@ -14,23 +13,10 @@ pub fn handle_compare(trail1_name: &str, trail2_name: &str) -> Result<()> {
 
     println!("Fetching elevation data for both trails...");
 
-    let elev1 = get_trail_elevation(trail1)?;
-    let elev2 = get_trail_elevation(trail2)?;
+    let profile1 = get_trail_profile(trail1)?;
+    let profile2 = get_trail_profile(trail2)?;
 
-    tui::run_compare_tui(trail1, trail2, &elev1, &elev2)?;
+    tui::run_compare_tui(trail1, trail2, &profile1, &profile2)?;
 
     Ok(())
 }
-
-fn get_trail_elevation(trail: &crate::trails::Trail) -> Result<Vec<f64>> {
-    if let Some(cached) = cache::get_cached_elevation(&trail.name, &trail.park) {
-        return Ok(cached);
-    }
-
-    let sampled = sample_coordinates(&trail.coordinates_wgs84, DEFAULT_SAMPLE_POINTS);
-    let elevations = fetch_elevation(&sampled)?;
-
-    let _ = cache::cache_elevation(&trail.name, &trail.park, &elevations);
-
-    Ok(elevations)
-}
@@ -0,0 +1,55 @@
+use crate::config::load_config;
+use crate::icons::Icons;
+use crate::services::dem::{has_tile, tile_name, tile_url};
+use crate::trails::{find_trails_by_park, load_trails};
+use anyhow::Result;
+use colored::Colorize;
+use std::collections::BTreeSet;
+
+pub fn handle_dem_fetch_list(park_name: &str) -> Result<()> {
+    let trails = load_trails()?;
+    let park_trails = find_trails_by_park(&trails, park_name);
+
+    if park_trails.is_empty() {
+        anyhow::bail!("No trails found for park: {}", park_name);
+    }
+
+    let tiles: BTreeSet<String> = park_trails
+        .iter()
+        .flat_map(|t| t.coordinates_wgs84.iter())
+        .map(|&(lat, lng)| tile_name(lat, lng))
+        .collect();
+
+    let dem_dir = load_config()?.dem_dir;
+    let mut parks: Vec<&str> = park_trails.iter().map(|t| t.park.as_str()).collect();
+    parks.dedup();
+
+    println!(
+        "\n{} DEM tiles for {} ({} trails)\n",
+        Icons::ELEVATION,
+        parks.join(", ").bold(),
+        park_trails.len()
+    );
+
+    for tile in &tiles {
+        let present = dem_dir.as_ref().is_some_and(|dir| has_tile(dir, tile));
+        let status = if present {
+            Icons::SUCCESS.green()
+        } else {
+            "[ ]".normal()
+        };
+        println!("  {} {}  {}", status, tile, tile_url(tile));
+    }
+
+    match dem_dir {
+        Some(dir) => println!(
+            "\nDownload the missing tiles, gunzip them into {}",
+            dir.display()
+        ),
+        None => println!(
+            "\nDownload and gunzip the tiles into a directory, then set \"dem_dir\" in the config file"
+        ),
+    }
+
+    Ok(())
+}
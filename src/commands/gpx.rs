@@ -1,5 +1,7 @@
-use crate::services::elevation::fetch_elevation;
+use crate::services::elevation::elevation_provider;
 use crate::trails::Trail;
+use crate::units;
+use crate::xml::escape_xml;
 use anyhow::Result;
 use std::fmt::Write;
 use std::fs;
@@ -10,10 +12,13 @@ pub fn export_gpx(trail: &Trail, output_path: &str) -> Result<()> {
         trail.coordinates_wgs84.len()
     );
 
-    let elevations = fetch_elevation(&trail.coordinates_wgs84).unwrap_or_else(|e| {
-        eprintln!("Warning: Could not fetch elevation data: {}", e);
-        vec![0.0; trail.coordinates_wgs84.len()]
-    });
+    // Without elevations the points are written with no <ele> rather than as sea level
+    let elevations = elevation_provider()
+        .and_then(|provider| provider.elevations(&trail.coordinates_wgs84))
+        .unwrap_or_else(|e| {
+            eprintln!("Warning: Could not fetch elevation data: {}", e);
+            Vec::new()
+        });
 
     let difficulty_str = trail
         .difficulty
@@ -27,10 +32,10 @@ pub fn export_gpx(trail: &Trail, output_path: &str) -> Result<()> {
     writeln!(gpx, "    <name>{}</name>", escape_xml(&trail.name))?;
     writeln!(
         gpx,
-        "    <desc>{} - {} - {:.1}km</desc>",
+        "    <desc>{} - {} - {}</desc>",
         escape_xml(&trail.park),
         escape_xml(&difficulty_str),
-        trail.length_km
+        units::get().distance(trail.length_km, 1)
     )?;
     writeln!(gpx, "  </metadata>")?;
     writeln!(gpx, "  <trk>")?;
@@ -38,12 +43,18 @@ pub fn export_gpx(trail: &Trail, output_path: &str) -> Result<()> {
     writeln!(gpx, "    <trkseg>")?;
 
     for (i, (lat, lng)) in trail.coordinates_wgs84.iter().enumerate() {
-        let ele = elevations.get(i).unwrap_or(&0.0);
-        writeln!(
-            gpx,
-            "      <trkpt lat=\"{:.6}\" lon=\"{:.6}\"><ele>{:.1}</ele></trkpt>",
-            lat, lng, ele
-        )?;
+        match elevations.get(i) {
+            Some(ele) => writeln!(
+                gpx,
+                "      <trkpt lat=\"{:.6}\" lon=\"{:.6}\"><ele>{:.1}</ele></trkpt>",
+                lat, lng, ele
+            )?,
+            None => writeln!(
+                gpx,
+                "      <trkpt lat=\"{:.6}\" lon=\"{:.6}\"></trkpt>",
+                lat, lng
+            )?,
+        }
     }
 
     writeln!(gpx, "    </trkseg>")?;
@@ -53,11 +64,3 @@ pub fn export_gpx(trail: &Trail, output_path: &str) -> Result<()> {
     fs::write(output_path, gpx)?;
     Ok(())
 }
-
-fn escape_xml(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&apos;")
-}
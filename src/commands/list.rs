@@ -1,6 +1,7 @@
 use crate::cli::Cli;
+use crate::conditions::{RiskLevel, get_trail_risk, get_winter_conditions};
 use crate::icons::Icons;
-use crate::trails::{Difficulty, filter_trails, load_trails};
+use crate::trails::{Difficulty, Trail, TrailSort, filter_trails, load_trails, sort_trails};
 use anyhow::Result;
 use colored::Colorize;
 
@@ -17,8 +18,32 @@ pub fn handle_list(cli: &Cli) -> Result<()> {
         );
     }
 
+    if let crate::cli::Commands::List {
+        max_risk: Some(ref risk_str),
+        ..
+    } = cli.command
+        && risk_str.parse::<RiskLevel>().is_err()
+    {
+        anyhow::bail!(
+            "Invalid risk level: '{}'. Valid options are: low, moderate, high",
+            risk_str
+        );
+    }
+
+    if let crate::cli::Commands::List {
+        sort: Some(ref sort_str),
+        ..
+    } = cli.command
+        && sort_str.parse::<TrailSort>().is_err()
+    {
+        anyhow::bail!(
+            "Invalid sort: '{}'. Valid options are: climb, length, distance",
+            sort_str
+        );
+    }
+
     let trails = load_trails()?;
-    let filtered = filter_trails(
+    let mut filtered = filter_trails(
         &trails,
         cli.command.get_difficulty(),
         cli.command.get_max_distance(),
@@ -27,11 +52,55 @@ pub fn handle_list(cli: &Cli) -> Result<()> {
         cli.command.get_park_name(),
     );
 
+    let (min_gain, max_gain) = (cli.command.get_min_gain(), cli.command.get_max_gain());
+    let sort = cli.command.get_sort();
+    let needs_elevation =
+        min_gain.is_some() || max_gain.is_some() || sort == Some(TrailSort::Climb);
+    if needs_elevation && trails.iter().all(|t| t.elevation.is_none()) {
+        println!(
+            "{} No elevation data cached yet. Fetch it with: rando update --elevation",
+            Icons::WARNING.yellow()
+        );
+    }
+    if min_gain.is_some() || max_gain.is_some() {
+        filtered.retain(|trail| {
+            trail.elevation.is_some_and(|e| {
+                min_gain.is_none_or(|min| e.gain >= min) && max_gain.is_none_or(|max| e.gain <= max)
+            })
+        });
+    }
+
+    if let Some(max_risk) = cli.command.get_max_risk() {
+        println!(
+            "Checking recent conditions for {} trails...",
+            filtered.len()
+        );
+        retain_checked(&mut filtered, "recent conditions", |trail| {
+            get_trail_risk(trail).map(|risk| risk.level() <= max_risk)
+        })?;
+    }
+
+    if cli.command.get_snowshoe_ready() {
+        println!("Checking snow cover for {} trails...", filtered.len());
+        retain_checked(&mut filtered, "snow cover", |trail| {
+            get_winter_conditions(trail).map(|winter| winter.snowshoe_ready())
+        })?;
+    }
+
     if filtered.is_empty() {
         println!("{}", "No trails found matching your criteria.".yellow());
         return Ok(());
     }
 
+    if let Some(sort) = sort {
+        sort_trails(&mut filtered, sort);
+        for trail in &filtered {
+            super::print_trail_info(trail, true)?;
+            println!("  {}", trail.park.dimmed());
+        }
+        return Ok(());
+    }
+
     let mut current_park = String::new();
     for trail in &filtered {
         if trail.park != current_park {
@@ -43,3 +112,45 @@ pub fn handle_list(cli: &Cli) -> Result<()> {
 
     Ok(())
 }
+
+/// Keep the trails passing a check that needs the network. Trails that
+/// couldn't be checked are named rather than dropped silently
+fn retain_checked(
+    trails: &mut Vec<Trail>,
+    what: &str,
+    check: impl Fn(&Trail) -> Result<bool>,
+) -> Result<()> {
+    const LISTED: usize = 5;
+    let total = trails.len();
+    let mut failed = Vec::new();
+    let mut last_error = None;
+    trails.retain(|trail| match check(trail) {
+        Ok(keep) => keep,
+        Err(e) => {
+            failed.push(trail.name.clone());
+            last_error = Some(e);
+            false
+        }
+    });
+
+    if let Some(error) = last_error {
+        if failed.len() == total {
+            anyhow::bail!("Could not check {} for any trail: {}", what, error);
+        }
+        let mut names = failed[..failed.len().min(LISTED)].join(", ");
+        if failed.len() > LISTED {
+            names.push_str(&format!(" and {} more", failed.len() - LISTED));
+        }
+        println!(
+            "{} Could not check {} for {} trail{} ({}), left out: {}",
+            Icons::WARNING.yellow(),
+            what,
+            failed.len(),
+            if failed.len() == 1 { "" } else { "s" },
+            error,
+            names
+        );
+    }
+
+    Ok(())
+}
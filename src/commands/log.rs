@@ -1,33 +1,705 @@
-use crate::data::logs::{add_hike, parse_duration};
+use super::progress::announce_achievements;
+use crate::cli::HikeDetailArgs;
+use crate::data::export::{ExportFormat, from_csv, to_csv, to_gpx, to_ics, to_json};
+use crate::data::logs::{
+    DetailField, HikeDetails, HikeLog, HikeUpdate, TrailCondition, TrailRef, add_hike,
+    add_hike_log, delete_hike, fetch_weather, import_hikes, load_logs, parse_date, parse_duration,
+    set_weather, update_hike, validate_duration,
+};
 use crate::icons::Icons;
-use crate::trails::{find_trail_by_name, load_trails};
-use anyhow::Result;
+use crate::progress::achievements;
+use crate::tracks::{match_trails, read_track};
+use crate::trails::{Difficulty, find_trail_by_name, load_trails};
+use crate::units;
+use anyhow::{Context, Result};
+use chrono::NaiveTime;
 use colored::Colorize;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
 
 pub fn handle_log(
     trail_name: &str,
     time: Option<String>,
     date: Option<String>,
     notes: Option<String>,
+    details: &HikeDetailArgs,
+    interactive: bool,
 ) -> Result<()> {
     let trails = load_trails()?;
     let trail = find_trail_by_name(&trails, trail_name)
         .ok_or_else(|| anyhow::anyhow!("Trail not found: {}", trail_name))?;
 
-    let duration_minutes = time.as_ref().map(|t| parse_duration(t)).transpose()?;
+    let mut duration_minutes = time.as_ref().map(|t| parse_duration(t)).transpose()?;
+    let mut notes = notes;
+    let mut details = parse_details(details)?;
+    let before = achievements(&load_logs()?.hikes, &trails);
+    if interactive {
+        println!(
+            "Details for {} (press Enter to skip a question)",
+            trail.name.bold()
+        );
+        prompt_details(
+            &mut duration_minutes,
+            &mut notes,
+            &mut details,
+            trail.length_km,
+        )?;
+    }
+
+    let hike = add_hike(trail, date, duration_minutes, notes, details)?;
+
+    println!(
+        "\n{} Logged: {} (#{})",
+        Icons::SUCCESS.green(),
+        trail.name.bold(),
+        hike.id
+    );
+    print_hike(&hike);
+    announce_achievements(&before, &trails)?;
+
+    Ok(())
+}
+
+/// Check and convert the detail flags
+fn parse_details(args: &HikeDetailArgs) -> Result<HikeDetails> {
+    let details = HikeDetails {
+        start_time: args.start.as_deref().map(parse_start_time).transpose()?,
+        companions: clean_list(&args.companions),
+        rating: args.rating,
+        perceived_difficulty: args.felt.as_deref().map(parse_felt).transpose()?,
+        conditions: args
+            .conditions
+            .iter()
+            .filter(|c| !c.trim().is_empty())
+            .map(|c| parse_condition(c))
+            .collect::<Result<_>>()?,
+        wildlife: clean_list(&args.wildlife),
+        photos: args
+            .photos
+            .iter()
+            .map(|p| photo_path(p))
+            .collect::<Result<_>>()?,
+        actual_distance_km: args.distance,
+    };
+    details.validate()?;
+    Ok(details)
+}
+
+/// Ask for each field not already given on the command line
+fn prompt_details(
+    duration_minutes: &mut Option<u32>,
+    notes: &mut Option<String>,
+    details: &mut HikeDetails,
+    trail_km: f64,
+) -> Result<()> {
+    let list = |answer: &str| -> Result<Vec<String>> { Ok(split_list(answer)) };
+
+    if duration_minutes.is_none() {
+        *duration_minutes = ask("Time (e.g. 4h30m)", |a| {
+            let minutes = parse_duration(a)?;
+            validate_duration(minutes)?;
+            Ok(minutes)
+        })?;
+    }
+    if details.start_time.is_none() {
+        details.start_time = ask("Start time (HH:MM)", parse_start_time)?;
+    }
+    if details.companions.is_empty() {
+        details.companions = ask("With (comma-separated)", list)?.unwrap_or_default();
+    }
+    if details.rating.is_none() {
+        details.rating = ask("Rating (1-5)", |a| {
+            let rating: u8 = a
+                .parse()
+                .ok()
+                .filter(|r| (1..=5).contains(r))
+                .with_context(|| format!("Invalid rating: '{}'. Use 1 to 5", a))?;
+            Ok(rating)
+        })?;
+    }
+    if details.perceived_difficulty.is_none() {
+        details.perceived_difficulty = ask(
+            "How hard it felt (facile, intermediaire, difficile)",
+            parse_felt,
+        )?;
+    }
+    if details.conditions.is_empty() {
+        let options: Vec<String> = TrailCondition::ALL.iter().map(|c| c.to_string()).collect();
+        details.conditions = ask(&format!("Conditions ({})", options.join(", ")), |a| {
+            split_list(a).iter().map(|c| parse_condition(c)).collect()
+        })?
+        .unwrap_or_default();
+    }
+    if details.wildlife.is_empty() {
+        details.wildlife = ask("Wildlife seen (comma-separated)", list)?.unwrap_or_default();
+    }
+    if details.photos.is_empty() {
+        details.photos = ask("Photos (comma-separated paths)", |a| {
+            split_list(a).iter().map(|p| photo_path(p)).collect()
+        })?
+        .unwrap_or_default();
+    }
+    if details.actual_distance_km.is_none() {
+        let label = format!("Distance hiked in km (Enter for {:.1})", trail_km);
+        details.actual_distance_km = ask(&label, |a| {
+            let km: f64 = a
+                .replace(',', ".")
+                .parse()
+                .ok()
+                .filter(|km: &f64| *km > 0.0)
+                .with_context(|| format!("Invalid distance: '{}'", a))?;
+            Ok(km)
+        })?
+        .filter(|km| (km - trail_km).abs() >= 0.05);
+    }
+    if notes.is_none() {
+        *notes = ask("Notes", |a| Ok(a.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Prompt until the answer parses; an empty answer (or end of input) skips the field
+fn ask<T>(label: &str, parse: impl Fn(&str) -> Result<T>) -> Result<Option<T>> {
+    loop {
+        print!("  {}: ", label);
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer)? == 0 {
+            println!();
+            return Ok(None);
+        }
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Ok(None);
+        }
+        match parse(answer) {
+            Ok(value) => return Ok(Some(value)),
+            Err(e) => println!("  {}", e.to_string().red()),
+        }
+    }
+}
+
+fn split_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn clean_list(items: &[String]) -> Vec<String> {
+    items
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_start_time(text: &str) -> Result<String> {
+    let time = NaiveTime::parse_from_str(text.trim(), "%H:%M")
+        .with_context(|| format!("Invalid start time: '{}'. Use HH:MM", text))?;
+    Ok(time.format("%H:%M").to_string())
+}
+
+fn parse_felt(text: &str) -> Result<Difficulty> {
+    text.parse().map_err(|_| {
+        anyhow::anyhow!(
+            "Invalid difficulty: '{}'. Valid options are: facile, intermediaire, difficile",
+            text
+        )
+    })
+}
+
+fn parse_condition(text: &str) -> Result<TrailCondition> {
+    text.parse().map_err(|_| {
+        let options: Vec<String> = TrailCondition::ALL.iter().map(|c| c.to_string()).collect();
+        anyhow::anyhow!(
+            "Invalid condition: '{}'. Valid options are: {}",
+            text.trim(),
+            options.join(", ")
+        )
+    })
+}
+
+fn parse_detail_field(text: &str) -> Result<DetailField> {
+    text.parse().map_err(|_| {
+        let options: Vec<String> = DetailField::ALL.iter().map(|f| f.to_string()).collect();
+        anyhow::anyhow!(
+            "Invalid detail to clear: '{}'. Valid options are: {}",
+            text.trim(),
+            options.join(", ")
+        )
+    })
+}
+
+/// Photos are stored by absolute path, so they must exist when logged
+fn photo_path(path: &str) -> Result<String> {
+    let path = fs::canonicalize(path).with_context(|| format!("Photo not found: {}", path))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+pub fn handle_log_import(
+    file: &str,
+    format: Option<&str>,
+    trail_name: Option<&str>,
+    notes: Option<String>,
+) -> Result<()> {
+    let path = Path::new(file);
+    let format = format
+        .map(str::to_lowercase)
+        .or_else(|| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .map(str::to_lowercase)
+        })
+        .unwrap_or_default();
+    match format.as_str() {
+        "csv" => return import_csv(path),
+        "gpx" | "fit" => {}
+        other => anyhow::bail!(
+            "Invalid import format: '{}'. Valid options are: gpx, fit, csv",
+            other
+        ),
+    }
+
+    let track = read_track(path)?;
+    println!(
+        "Read {} ({} points)",
+        track.name.as_deref().unwrap_or(file),
+        track.points.len()
+    );
+    let date = track
+        .date()
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Track has no timestamps; log it with: rando log <trail> --date YYYY-MM-DD"
+            )
+        })?
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let trails = load_trails()?;
+    let matches = match_trails(&track, &trails);
+    let trail = match trail_name {
+        Some(name) => find_trail_by_name(&trails, name)
+            .ok_or_else(|| anyhow::anyhow!("Trail not found: {}", name))?,
+        None => matches.first().map(|m| m.trail).ok_or_else(|| {
+            anyhow::anyhow!("No catalog trail matches this track; pass --trail <name>")
+        })?,
+    };
+
+    let logs = load_logs()?;
+    if let Some(existing) = logs
+        .hikes
+        .iter()
+        .find(|h| h.date == date && h.trail_name == trail.name && h.park == trail.park)
+    {
+        anyhow::bail!(
+            "{} on {} is already logged as #{} (see rando log show {})",
+            trail.name,
+            date,
+            existing.id,
+            existing.id
+        );
+    }
+
+    let before = achievements(&logs.hikes, &trails);
+    let heart_rate = track.heart_rate();
+    let weather = fetch_weather(trail, &date);
+    let hike = HikeLog {
+        id: 0,
+        trail_name: trail.name.clone(),
+        park: trail.park.clone(),
+        date,
+        duration_minutes: track.moving_minutes(),
+        distance_km: track.distance_km(),
+        notes,
+        elevation_gain: track.elevation_gain(),
+        other_trails: matches
+            .iter()
+            .filter(|m| m.trail.name != trail.name || m.trail.park != trail.park)
+            .map(|m| TrailRef::of(m.trail))
+            .collect(),
+        avg_heart_rate: heart_rate.map(|(avg, _)| avg),
+        max_heart_rate: heart_rate.map(|(_, max)| max),
+        calories: track.calories(),
+        track_file: None,
+        weather,
+        details: HikeDetails::default(),
+    };
+    let hike = add_hike_log(hike, Some(path))?;
+
+    println!(
+        "\n{} Imported: {} (#{})",
+        Icons::SUCCESS.green(),
+        trail.name.bold(),
+        hike.id
+    );
+    print_hike(&hike);
+
+    if !matches.is_empty() {
+        println!("\n  Matched trails:");
+        for m in &matches {
+            println!(
+                "    {} ({:.0}% covered, within {})",
+                m.trail.name,
+                m.coverage * 100.0,
+                units::get().elevation(m.max_deviation_m)
+            );
+        }
+    }
+    announce_achievements(&before, &trails)?;
+
+    Ok(())
+}
+
+fn import_csv(path: &Path) -> Result<()> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let hikes = from_csv(&text)?;
+    let (added, skipped) = import_hikes(hikes)?;
+
+    println!(
+        "\n{} Imported {} hike{} from {}",
+        Icons::SUCCESS.green(),
+        added,
+        if added == 1 { "" } else { "s" },
+        path.display()
+    );
+    if skipped > 0 {
+        println!("   Skipped {} already logged", skipped);
+    }
+    Ok(())
+}
 
-    add_hike(trail, date.clone(), duration_minutes, notes.clone())?;
+pub fn handle_log_export(
+    format: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    output: Option<&str>,
+) -> Result<()> {
+    let format: ExportFormat = format.parse().map_err(|_| {
+        anyhow::anyhow!(
+            "Invalid export format: '{}'. Valid options are: csv, json, gpx, ics",
+            format
+        )
+    })?;
+    let from = from.map(parse_date).transpose()?;
+    let to = to.map(parse_date).transpose()?;
+
+    let logs = load_logs()?;
+    let hikes = logs.between(from.as_deref(), to.as_deref());
+    if hikes.is_empty() {
+        println!("{}", "No logged hikes to export.".yellow());
+        return Ok(());
+    }
+
+    let mut exported = hikes.len();
+    let content = match format {
+        ExportFormat::Csv => to_csv(&hikes),
+        ExportFormat::Json => to_json(&hikes)?,
+        ExportFormat::Ics => to_ics(&hikes),
+        ExportFormat::Gpx => {
+            let (gpx, written) = to_gpx(&hikes, &load_trails()?)?;
+            exported = written;
+            gpx
+        }
+    };
 
-    println!("\n{} Logged: {}", Icons::SUCCESS.green(), trail.name.bold());
-    if let Some(d) = date.as_ref() {
-        println!("   Date: {}", d);
+    let default_path = format!("rando-hikes.{}", format.extension());
+    let output_path = output.unwrap_or(&default_path);
+    fs::write(output_path, content)?;
+
+    println!(
+        "{} Exported {} hike{} to {}",
+        Icons::SUCCESS.green(),
+        exported,
+        if exported == 1 { "" } else { "s" },
+        output_path
+    );
+    if exported < hikes.len() {
+        println!(
+            "   {} skipped: no recorded track and trail not in the catalog",
+            hikes.len() - exported
+        );
     }
-    if let Some(t) = time.as_ref() {
-        println!("   Time: {}", t);
+    Ok(())
+}
+
+pub fn handle_log_list(
+    from: Option<&str>,
+    to: Option<&str>,
+    park: Option<&str>,
+    trail: Option<&str>,
+) -> Result<()> {
+    // Range bounds may be in the future, unlike hike dates
+    let from = from.map(parse_date).transpose()?;
+    let to = to.map(parse_date).transpose()?;
+
+    let logs = load_logs()?;
+    let contains = |text: &str, filter: Option<&str>| {
+        filter.is_none_or(|f| text.to_lowercase().contains(&f.to_lowercase()))
+    };
+    let mut hikes = logs.between(from.as_deref(), to.as_deref());
+    hikes.retain(|h| contains(&h.park, park) && contains(&h.trail_name, trail));
+
+    if hikes.is_empty() {
+        println!("{}", "No logged hikes match your criteria.".yellow());
+        return Ok(());
     }
-    if let Some(n) = notes.as_ref() {
-        println!("   Notes: {}", n);
+
+    let units = units::get();
+    println!(
+        "\n  {:>4}  {:<10}  {:<30}  {:>8}  {:>7}",
+        "ID", "Date", "Trail", "Distance", "Time"
+    );
+    for hike in &hikes {
+        println!(
+            "  {:>4}  {:<10}  {:<30}  {:>8}  {:>7}{}",
+            hike.id,
+            hike.date,
+            hike.trail_name.chars().take(30).collect::<String>(),
+            units.distance(hike.hiked_km(), 1),
+            format_duration(hike.duration_minutes),
+            if hike.notes.is_some() { "  *" } else { "" }
+        );
     }
+    println!(
+        "\n  {} hike{}{}",
+        hikes.len(),
+        if hikes.len() == 1 { "" } else { "s" },
+        if hikes.iter().any(|h| h.notes.is_some()) {
+            " (* has notes, see rando log show <id>)"
+        } else {
+            ""
+        }
+    );
 
     Ok(())
 }
+
+pub fn handle_log_show(id: u32) -> Result<()> {
+    let logs = load_logs()?;
+    let hike = logs
+        .find(id)
+        .ok_or_else(|| anyhow::anyhow!("No logged hike with ID {}", id))?;
+    print_hike(hike);
+    Ok(())
+}
+
+pub fn handle_log_edit(
+    id: u32,
+    time: Option<String>,
+    date: Option<String>,
+    notes: Option<String>,
+    details: &HikeDetailArgs,
+    clear: &[String],
+) -> Result<()> {
+    let details = parse_details(details)?;
+    let clear: Vec<DetailField> = clear
+        .iter()
+        .filter(|c| !c.trim().is_empty())
+        .map(|c| parse_detail_field(c))
+        .collect::<Result<_>>()?;
+    if time.is_none() && date.is_none() && notes.is_none() && details.is_empty() && clear.is_empty()
+    {
+        anyhow::bail!(
+            "Nothing to change. Use --time, --date, --notes, --clear or a detail such as --rating (see rando log edit --help)"
+        );
+    }
+
+    let update = HikeUpdate {
+        date,
+        duration_minutes: time.as_ref().map(|t| parse_duration(t)).transpose()?,
+        notes,
+        details,
+        clear,
+    };
+    let mut hike = update_hike(id, update)?;
+    if hike.weather.is_none()
+        && let Some(trail) = hike.trail(&load_trails()?)
+        && let Some(weather) = fetch_weather(trail, &hike.date)
+    {
+        set_weather(vec![(hike.id, weather)])?;
+        hike.weather = Some(weather);
+    }
+
+    println!("\n{} Updated hike #{}", Icons::SUCCESS.green(), hike.id);
+    print_hike(&hike);
+    Ok(())
+}
+
+/// Fetch the weather of logged hikes that don't have it yet
+pub fn handle_log_weather() -> Result<()> {
+    let logs = load_logs()?;
+    let missing: Vec<&HikeLog> = logs.hikes.iter().filter(|h| h.weather.is_none()).collect();
+    if missing.is_empty() {
+        println!(
+            "{} Every logged hike has its weather",
+            Icons::SUCCESS.green()
+        );
+        return Ok(());
+    }
+
+    let trails = load_trails()?;
+    let mut snapshots = Vec::new();
+    let mut failed = 0;
+    for (i, hike) in missing.iter().enumerate() {
+        print!(
+            "\r  Fetching weather {}/{}: {:<30}",
+            i + 1,
+            missing.len(),
+            hike.trail_name.chars().take(30).collect::<String>()
+        );
+        io::stdout().flush()?;
+        match hike
+            .trail(&trails)
+            .and_then(|t| fetch_weather(t, &hike.date))
+        {
+            Some(weather) => snapshots.push((hike.id, weather)),
+            None => failed += 1,
+        }
+    }
+    println!();
+
+    let fetched = snapshots.len();
+    set_weather(snapshots)?;
+    println!(
+        "{} Added weather to {} hike{}",
+        Icons::SUCCESS.green(),
+        fetched,
+        if fetched == 1 { "" } else { "s" }
+    );
+    if failed > 0 {
+        println!(
+            "{} {} failed (trail no longer in the catalog, or offline); run again to retry",
+            Icons::WARNING.yellow(),
+            failed
+        );
+    }
+    Ok(())
+}
+
+pub fn handle_log_delete(id: u32, yes: bool) -> Result<()> {
+    let logs = load_logs()?;
+    let hike = logs
+        .find(id)
+        .ok_or_else(|| anyhow::anyhow!("No logged hike with ID {}", id))?;
+
+    if !yes {
+        print!(
+            "Delete #{} {} on {}? [y/N] ",
+            hike.id,
+            hike.trail_name.bold(),
+            hike.date
+        );
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().lock().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("Cancelled");
+            return Ok(());
+        }
+    }
+
+    let removed = delete_hike(id)?;
+    println!(
+        "{} Deleted #{} {} ({})",
+        Icons::SUCCESS.green(),
+        removed.id,
+        removed.trail_name,
+        removed.date
+    );
+    Ok(())
+}
+
+fn print_hike(hike: &HikeLog) {
+    let units = units::get();
+    println!("\n  {} (#{})", hike.trail_name.bold(), hike.id);
+    println!("  Park:     {}", hike.park);
+    let details = &hike.details;
+    match &details.start_time {
+        Some(start) => println!("  Date:     {} at {}", hike.date, start),
+        None => println!("  Date:     {}", hike.date),
+    }
+    match details.actual_distance_km {
+        Some(km) => println!(
+            "  Distance: {} (trail {})",
+            units.distance(km, 1),
+            units.distance(hike.distance_km, 1)
+        ),
+        None => println!("  Distance: {}", units.distance(hike.distance_km, 1)),
+    }
+    println!("  Time:     {}", format_duration(hike.duration_minutes));
+    if let Some(weather) = &hike.weather {
+        println!(
+            "  Weather:  {} {} to {}, {} precipitation, wind {}",
+            Icons::weather(weather.weather_code),
+            units.temperature(weather.min_temp),
+            units.temperature(weather.max_temp),
+            units.precipitation(weather.precipitation),
+            units.speed(weather.wind_speed)
+        );
+    }
+    if let Some(rating) = details.rating {
+        println!(
+            "  Rating:   {}{}",
+            "★".repeat(rating as usize),
+            "☆".repeat(5usize.saturating_sub(rating as usize))
+        );
+    }
+    if let Some(felt) = details.perceived_difficulty {
+        println!("  Felt:     {}", felt);
+    }
+    if !details.companions.is_empty() {
+        println!("  With:     {}", details.companions.join(", "));
+    }
+    if !details.conditions.is_empty() {
+        let conditions: Vec<String> = details.conditions.iter().map(|c| c.to_string()).collect();
+        println!("  Trail:    {}", conditions.join(", "));
+    }
+    if !details.wildlife.is_empty() {
+        println!("  Wildlife: {}", details.wildlife.join(", "));
+    }
+    if let Some(gain) = hike.elevation_gain {
+        println!("  Gain:     {}", units.elevation(gain));
+    }
+    if let (Some(avg), Some(max)) = (hike.avg_heart_rate, hike.max_heart_rate) {
+        println!("  Heart:    {} bpm avg, {} max", avg, max);
+    }
+    if let Some(calories) = hike.calories {
+        println!("  Energy:   {} kcal", calories);
+    }
+    if !hike.other_trails.is_empty() {
+        let others: Vec<String> = hike
+            .other_trails
+            .iter()
+            .map(|t| {
+                if t.park == hike.park {
+                    t.name.clone()
+                } else {
+                    format!("{} ({})", t.name, t.park)
+                }
+            })
+            .collect();
+        println!("  Also:     {}", others.join(", "));
+    }
+    if let Some(track) = &hike.track_file {
+        println!("  Track:    {}", track);
+    }
+    for photo in &details.photos {
+        println!("  Photo:    {}", photo);
+    }
+    if let Some(notes) = &hike.notes {
+        println!("  Notes:    {}", notes);
+    }
+}
+
+fn format_duration(minutes: Option<u32>) -> String {
+    match minutes {
+        Some(m) => format!("{}h{:02}", m / 60, m % 60),
+        None => "-".into(),
+    }
+}
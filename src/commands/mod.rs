@@ -1,43 +1,63 @@
 mod alerts;
 mod card;
 mod checklist;
+mod climate;
 mod compare;
 mod daylight;
+mod dem;
 mod gpx;
 mod hunt;
 mod list;
 mod log;
 mod nearby;
+mod pace;
 mod park;
+mod profile;
+mod progress;
 mod random;
 mod share;
 mod stats;
+mod storm;
 mod streak;
 mod trail;
+mod update;
 mod weather;
 
 pub use alerts::handle_alerts;
 pub use card::print_card;
 pub use checklist::handle_checklist;
+pub use climate::handle_climate;
 pub use compare::handle_compare;
 pub use daylight::handle_daylight;
+pub use dem::handle_dem_fetch_list;
 pub use gpx::export_gpx;
 pub use hunt::handle_hunt;
 pub use list::handle_list;
-pub use log::handle_log;
+pub use log::{
+    handle_log, handle_log_delete, handle_log_edit, handle_log_export, handle_log_import,
+    handle_log_list, handle_log_show, handle_log_weather,
+};
 pub use nearby::handle_nearby;
+pub use pace::handle_pace;
 pub use park::handle_park;
+pub use profile::handle_profile;
+pub use progress::handle_progress;
 pub use random::handle_random;
 pub use share::handle_share;
 pub use stats::handle_stats;
+pub use storm::handle_storm;
 pub use streak::handle_streak;
 pub use trail::handle_trail;
+pub use update::handle_update_elevation;
 pub use weather::handle_weather;
 
-use crate::conditions::{format_condition_url, get_park_url};
+use crate::conditions::{RiskLevel, format_condition_url, get_park_url};
 use crate::icons::Icons;
+use crate::services::air_quality::{AirQuality, AqiLevel};
+use crate::services::alerts::{AlertKind, WeatherAlert};
 use crate::services::weather::get_weather;
 use crate::trails::{Difficulty, Trail};
+use crate::units;
 use anyhow::Result;
 use colored::{ColoredString, Colorize};
 
@@ -50,19 +70,27 @@ pub fn print_trail_info(trail: &Trail, show_weather: bool) -> Result<()> {
         None => "Non spécifié".normal(),
     };
 
+    let units = units::get();
+    let climb = trail
+        .elevation
+        .map(|e| format!(" • ↑{}", units.elevation(e.gain)))
+        .unwrap_or_default();
     println!(
-        "  {} • {:.1}km • {:.0}km from Montreal",
-        difficulty_display, trail.length_km, trail.distance_from_mtl
+        "  {} • {}{} • {} from Montreal",
+        difficulty_display,
+        units.distance(trail.length_km, 1),
+        climb,
+        units.distance(trail.distance_from_mtl, 0)
     );
 
     if show_weather {
         if let Ok(weather) = get_weather(trail.lat, trail.lng) {
             println!(
-                "  {} {:.0}°C, {}, wind {:.0}km/h",
+                "  {} {}, {}, wind {}",
                 Icons::weather(weather.weather_code),
-                weather.temperature,
+                units.temperature(weather.temperature),
                 weather.description(),
-                weather.wind_speed
+                units.speed(weather.wind_speed)
             );
         }
 
@@ -84,3 +112,71 @@ fn format_difficulty(difficulty: Difficulty) -> ColoredString {
         Difficulty::Difficile => "Difficile".red(),
     }
 }
+
+fn format_risk_level(level: RiskLevel) -> ColoredString {
+    match level {
+        RiskLevel::Low => "Low".green(),
+        RiskLevel::Moderate => "Moderate".yellow(),
+        RiskLevel::High => "High".red(),
+    }
+}
+
+/// Print one line per weather alert, most severe first
+fn print_weather_alerts(alerts: &[WeatherAlert]) {
+    for alert in alerts {
+        let label = format!("{} {}", Icons::ALERT, alert.kind.label());
+        let label = match alert.kind {
+            AlertKind::Warning => label.red().bold(),
+            AlertKind::Watch | AlertKind::Advisory => label.yellow(),
+            AlertKind::Statement | AlertKind::Other => label.normal(),
+        };
+        let until = alert
+            .expires
+            .map(|e| format!(" (until {})", units::get().day_and_time(&e)))
+            .unwrap_or_default();
+        println!("  {}: {}{}", label, alert_title(alert), until);
+    }
+}
+
+fn alert_title(alert: &WeatherAlert) -> &str {
+    if alert.headline.is_empty() {
+        &alert.event
+    } else {
+        &alert.headline
+    }
+}
+
+/// Print current air quality, smoky forecast days and the mask/reschedule advice
+fn print_air_quality(air: &AirQuality) {
+    let summary = air.summary();
+    let summary = match air.level() {
+        Some(AqiLevel::Good) => summary.green(),
+        Some(AqiLevel::Moderate) => summary.yellow(),
+        Some(_) => summary.red(),
+        None => summary.dimmed(),
+    };
+    println!("  Air quality: {}", summary);
+
+    let smoky_days = air.smoky_days();
+    if !smoky_days.is_empty() {
+        println!(
+            "  {} Smoke forecast: {}",
+            Icons::WARNING.red(),
+            smoky_days
+                .iter()
+                .filter_map(|d| {
+                    d.max_aqi
+                        .map(|aqi| format!("{} AQI {:.0}", d.date.format("%a %d"), aqi))
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    if air.needs_warning() {
+        println!(
+            "  {} Poor air quality - consider a mask or reschedule",
+            Icons::WARNING.red()
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::geo::distance_km;
 use crate::icons::Icons;
 use crate::trails::{Difficulty, load_trails};
+use crate::units;
 use anyhow::Result;
 use colored::Colorize;
 
@@ -43,11 +44,12 @@ pub fn handle_nearby(
 
     nearby_trails.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
 
+    let units = units::get();
     if nearby_trails.is_empty() {
         println!(
-            "{} No trails found within {}km of ({:.4}, {:.4})",
+            "{} No trails found within {} of ({:.4}, {:.4})",
             Icons::LOCATION.yellow(),
-            radius,
+            units.distance(radius, 0),
             search_lat,
             search_lng
         );
@@ -55,9 +57,9 @@ pub fn handle_nearby(
     }
 
     println!(
-        "\n{} Trails within {:.0}km of ({:.4}, {:.4})\n",
+        "\n{} Trails within {} of ({:.4}, {:.4})\n",
         Icons::LOCATION.green(),
-        radius,
+        units.distance(radius, 0),
         search_lat,
         search_lng
     );
@@ -65,12 +67,12 @@ pub fn handle_nearby(
     for (trail, dist) in nearby_trails {
         let difficulty_display = format_difficulty(trail.difficulty);
         println!(
-            "  {:.1}km   {} ({}) - {}, {:.1}km",
-            dist,
+            "  {}   {} ({}) - {}, {}",
+            units.distance(dist, 1),
             trail.name.bold(),
             trail.park,
             difficulty_display,
-            trail.length_km
+            units.distance(trail.length_km, 1)
         );
     }
 
@@ -0,0 +1,82 @@
+use crate::config::load_config;
+use crate::estimate::{NAISMITH_CLIMB_RATE, NAISMITH_SPEED, format_hours};
+use crate::icons::Icons;
+use crate::pace::{calibrate, load_pace_samples};
+use crate::units;
+use anyhow::Result;
+use colored::Colorize;
+
+pub fn handle_pace() -> Result<()> {
+    let config = load_config()?;
+    let model = config.time_model;
+    let samples = load_pace_samples(model, true)?;
+
+    let Some(pace) = calibrate(&samples) else {
+        println!(
+            "\n{} No hikes to calibrate from. Log hikes with a duration: rando log <trail> --time 3h15m",
+            Icons::STATS.yellow()
+        );
+        return Ok(());
+    };
+
+    let units = units::get();
+    let confidence = pace.confidence();
+    println!("\n{} Your Hiking Pace\n", Icons::STATS);
+    println!(
+        "  Based on:     {} hike{} ({} confidence)",
+        pace.hikes,
+        if pace.hikes == 1 { "" } else { "s" },
+        confidence.label()
+    );
+    println!(
+        "  Flat speed:   {}/h (standard {}/h)",
+        units.distance(pace.flat_speed, 1),
+        units.distance(NAISMITH_SPEED, 1)
+    );
+    println!(
+        "  Climbing:     {}/h (standard {}/h)",
+        units.elevation(pace.climb_rate),
+        units.elevation(NAISMITH_CLIMB_RATE)
+    );
+    let comparison = pace.comparison(model);
+    let comparison = if pace.ratio <= 1.0 {
+        comparison.green()
+    } else {
+        comparison.yellow()
+    };
+    println!("  Overall:      {}\n", comparison);
+
+    if !pace.fitted {
+        println!(
+            "  {} Too few or too similar hikes to separate flat speed from climbing; scaled Naismith instead",
+            Icons::INFO
+        );
+    }
+    if config.pace.is_some() {
+        println!(
+            "  {} \"pace\" is set in the config, so estimates use it instead of this calibration",
+            Icons::INFO
+        );
+    }
+
+    println!(
+        "\n  {:<10} {:<30} {:>7} {:>7} {:>7}",
+        "Date",
+        "Trail",
+        "Actual",
+        model.label(),
+        "Fit"
+    );
+    for sample in &samples {
+        println!(
+            "  {:<10} {:<30} {:>7} {:>7} {:>7}",
+            sample.date,
+            sample.trail_name.chars().take(30).collect::<String>(),
+            format_hours(sample.hours),
+            format_hours(sample.standard_hours),
+            format_hours(pace.hours(sample.distance_km, sample.gain))
+        );
+    }
+
+    Ok(())
+}
@@ -1,18 +1,11 @@
 use crate::icons::Icons;
-use crate::trails::{get_trails_by_park, load_trails};
+use crate::trails::{find_trails_by_park, load_trails};
 use anyhow::Result;
 use colored::Colorize;
 
 pub fn handle_park(park_name: &str) -> Result<()> {
     let trails = load_trails()?;
-    let park_trails: Vec<_> = if park_name.len() <= 4 {
-        get_trails_by_park(&trails, park_name)
-    } else {
-        trails
-            .iter()
-            .filter(|t| t.park.to_lowercase().contains(&park_name.to_lowercase()))
-            .collect()
-    };
+    let park_trails = find_trails_by_park(&trails, park_name);
 
     if park_trails.is_empty() {
         println!(
@@ -0,0 +1,146 @@
+use crate::icons::Icons;
+use crate::profile::{ElevationProfile, Segment, get_trail_profile};
+use crate::services::elevation::calculate_elevation_stats;
+use crate::trails::{find_trail_by_name, load_trails};
+use crate::units;
+use anyhow::Result;
+use colored::Colorize;
+
+const CHART_WIDTH: usize = 60;
+const CHART_HEIGHT: usize = 10;
+
+/// Upper bounds (%) of the grade bands in the distribution
+const GRADE_BANDS: [f64; 4] = [5.0, 10.0, 15.0, 20.0];
+
+pub fn handle_profile(trail_name: &str, grade: f64) -> Result<()> {
+    let trails = load_trails()?;
+    let trail = find_trail_by_name(&trails, trail_name)
+        .ok_or_else(|| anyhow::anyhow!("Trail not found: {}", trail_name))?;
+
+    println!("Fetching elevation data...");
+    let profile = get_trail_profile(trail)?;
+    if profile.points.len() < 2 {
+        anyhow::bail!("Not enough elevation data for {}", trail.name);
+    }
+
+    let units = units::get();
+    let stats = calculate_elevation_stats(&profile.elevations(), profile.length_km());
+
+    println!(
+        "\n{} Elevation Profile: {}\n",
+        Icons::ELEVATION,
+        trail.name.bold()
+    );
+    println!(
+        "  {} • ↑{} ↓{} • {} to {}\n",
+        units.distance(profile.length_km(), 1),
+        units.elevation(stats.total_gain),
+        units.elevation(stats.total_loss),
+        units.elevation(stats.min),
+        units.elevation(stats.max)
+    );
+
+    print_chart(&profile, stats.min, stats.max);
+
+    println!("\n  Steepest sections:");
+    for (window, label) in [(0.5, "500m"), (1.0, "1km")] {
+        print_steepest(&format!("{} up", label), profile.steepest(window, true));
+        print_steepest(&format!("{} down", label), profile.steepest(window, false));
+    }
+
+    let climbs = profile.climbs();
+    println!("\n  Climbs:");
+    if climbs.is_empty() {
+        println!("    None");
+    }
+    for climb in &climbs {
+        println!(
+            "    {} → {}  {} at {}%  {}",
+            units.distance(climb.start_km, 1),
+            units.distance(climb.end_km, 1),
+            format!("+{}", units.elevation(climb.gain)).green(),
+            units.number(climb.grade(), 1),
+            climb.category.map(|c| c.label()).unwrap_or("uncategorized")
+        );
+    }
+
+    println!("\n  Grade distribution:");
+    let segments = profile.smoothed().segments();
+    let total = profile.length_km().max(f64::EPSILON);
+    let mut lower = 0.0;
+    for upper in GRADE_BANDS.iter().copied().chain([f64::INFINITY]) {
+        let distance: f64 = segments
+            .iter()
+            .filter(|s| (lower..upper).contains(&s.grade().abs()))
+            .map(Segment::length_km)
+            .sum();
+        let band = if upper.is_finite() {
+            format!("{:.0}-{:.0}%", lower, upper)
+        } else {
+            format!("{:.0}%+", lower)
+        };
+        let share = distance / total;
+        println!(
+            "    {:>7}  {:<20} {}",
+            band,
+            "█".repeat((share * 20.0).round() as usize),
+            units.distance(distance, 1)
+        );
+        lower = upper;
+    }
+
+    let hours = profile.hours_above_grade(grade);
+    println!(
+        "\n  Above {}%: {} (~{}h{:02}m of walking)",
+        units.number(grade, 0),
+        units.distance(profile.distance_above_grade(grade), 1),
+        (hours * 60.0).round() as u32 / 60,
+        (hours * 60.0).round() as u32 % 60
+    );
+
+    Ok(())
+}
+
+fn print_steepest(label: &str, segment: Option<Segment>) {
+    let units = units::get();
+    match segment {
+        Some(s) => println!(
+            "    {:<10} {:>4}%  at {}",
+            label,
+            units.number(s.grade().abs(), 0),
+            units.distance(s.start_km, 1)
+        ),
+        None => println!("    {:<10}    -", label),
+    }
+}
+
+/// Filled area chart of elevation against distance
+fn print_chart(profile: &ElevationProfile, min: f64, max: f64) {
+    let units = units::get();
+    let range = (max - min).max(1.0);
+    let heights: Vec<usize> = profile
+        .resample(CHART_WIDTH)
+        .iter()
+        .map(|e| (((e - min) / range) * (CHART_HEIGHT - 1) as f64).round() as usize + 1)
+        .collect();
+
+    for row in (1..=CHART_HEIGHT).rev() {
+        let axis = match row {
+            r if r == CHART_HEIGHT => format!("{:>7}", units.elevation(max)),
+            1 => format!("{:>7}", units.elevation(min)),
+            _ => " ".repeat(7),
+        };
+        let line: String = heights
+            .iter()
+            .map(|&h| if h >= row { '█' } else { ' ' })
+            .collect();
+        println!("  {} │{}", axis, line.green());
+    }
+    println!("  {} └{}", " ".repeat(7), "─".repeat(CHART_WIDTH));
+    println!(
+        "  {}  0{:>width$}",
+        " ".repeat(7),
+        units.distance(profile.length_km(), 1),
+        width = CHART_WIDTH - 1
+    );
+}
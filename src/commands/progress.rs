@@ -0,0 +1,159 @@
+use crate::data::logs::load_logs;
+use crate::icons::Icons;
+use crate::progress::{Achievement, ParkProgress, achievements, newly_earned, park_progress};
+use crate::trails::{Trail, load_trails, match_parks};
+use crate::units;
+use anyhow::Result;
+use colored::Colorize;
+
+const BAR_WIDTH: usize = 20;
+
+pub fn handle_progress(park: Option<&str>) -> Result<()> {
+    let trails = load_trails()?;
+    let logs = load_logs()?;
+    let parks = park_progress(&trails, &logs.hikes);
+
+    if let Some(name) = park {
+        let names = match_parks(name, parks.iter().map(|p| (p.park, p.park_code)));
+        let matching: Vec<&ParkProgress> =
+            parks.iter().filter(|p| names.contains(p.park)).collect();
+        if matching.is_empty() {
+            anyhow::bail!("No park matches '{}'", name);
+        }
+        for progress in matching {
+            print_park_detail(progress);
+        }
+        return Ok(());
+    }
+
+    let mut started: Vec<&ParkProgress> = parks.iter().filter(|p| !p.done.is_empty()).collect();
+    started.sort_by(|a, b| b.ratio().total_cmp(&a.ratio()).then(a.park.cmp(b.park)));
+
+    println!("\n{} Park Progress\n", Icons::STATS);
+    if started.is_empty() {
+        println!("  No catalog trails hiked yet. Log one with: rando log <trail>");
+    }
+    let units = units::get();
+    let width = started
+        .iter()
+        .map(|p| p.park.chars().count())
+        .max()
+        .unwrap_or(0);
+    for progress in &started {
+        println!(
+            "  {:<width$}  {}  {:>7} trails  {} of {}",
+            progress.park,
+            bar(progress.ratio()),
+            format!("{}/{}", progress.done.len(), progress.trails.len()),
+            units.distance(progress.done_km(), 1),
+            units.distance(progress.total_km(), 1),
+        );
+    }
+    let not_started = parks.len() - started.len();
+    if not_started > 0 {
+        println!(
+            "\n  {} other park{} not started. See what's left in one with: rando progress --park <name>",
+            not_started,
+            if not_started == 1 { "" } else { "s" }
+        );
+    }
+
+    print_achievements(&achievements(&logs.hikes, &trails));
+    Ok(())
+}
+
+fn print_park_detail(progress: &ParkProgress) {
+    let units = units::get();
+    println!("\n{} {}", Icons::TRAIL.green(), progress.park.bold());
+    println!(
+        "  Trails:   {}/{} ({:.0}%)  {}",
+        progress.done.len(),
+        progress.trails.len(),
+        progress.ratio() * 100.0,
+        bar(progress.ratio())
+    );
+    println!(
+        "  Distance: {} of {}",
+        units.distance(progress.done_km(), 1),
+        units.distance(progress.total_km(), 1)
+    );
+
+    if progress.is_complete() {
+        println!("\n  {} Every trail hiked!", Icons::SUCCESS.green());
+        return;
+    }
+    if !progress.done.is_empty() {
+        println!("\n  Done:");
+        for trail in &progress.done {
+            println!("    {} {}", Icons::CHECK.green(), describe(trail));
+        }
+    }
+    println!("\n  Remaining:");
+    for trail in progress.remaining() {
+        println!("    - {}", describe(trail));
+    }
+}
+
+fn describe(trail: &Trail) -> String {
+    let units = units::get();
+    match trail.difficulty {
+        Some(difficulty) => format!(
+            "{} ({}, {})",
+            trail.name,
+            units.distance(trail.length_km, 1),
+            difficulty
+        ),
+        None => format!("{} ({})", trail.name, units.distance(trail.length_km, 1)),
+    }
+}
+
+fn print_achievements(all: &[Achievement]) {
+    let earned = all.iter().filter(|a| a.earned.is_some()).count();
+    println!(
+        "\n{} Achievements ({}/{})\n",
+        Icons::ACHIEVEMENT,
+        earned,
+        all.len()
+    );
+    for achievement in all {
+        match &achievement.earned {
+            Some(earned) => println!(
+                "  {} {:<16} {}  {}",
+                Icons::CHECK.green(),
+                achievement.name.bold(),
+                earned.date,
+                earned.detail
+            ),
+            None => println!(
+                "  {} {:<16} {}",
+                "[  ]".dimmed(),
+                achievement.name.dimmed(),
+                achievement.description.dimmed()
+            ),
+        }
+    }
+}
+
+/// Print the achievements the latest log entry unlocked, given those earned before it
+pub fn announce_achievements(before: &[Achievement], trails: &[Trail]) -> Result<()> {
+    let after = achievements(&load_logs()?.hikes, trails);
+    for achievement in newly_earned(before, after) {
+        let detail = achievement
+            .earned
+            .map(|e| format!(" ({})", e.detail))
+            .unwrap_or_default();
+        println!(
+            "\n{} Achievement unlocked: {}{} - {}",
+            Icons::ACHIEVEMENT.green(),
+            achievement.name.bold(),
+            detail,
+            achievement.description
+        );
+    }
+    Ok(())
+}
+
+fn bar(ratio: f64) -> String {
+    let filled = ((ratio * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+    format!("[{}{}]", "█".repeat(filled), "░".repeat(BAR_WIDTH - filled))
+}
@@ -1,6 +1,8 @@
+use crate::estimate::estimate_trail;
 use crate::icons::Icons;
 use crate::services::weather::get_weather;
 use crate::trails::{Difficulty, filter_trails, load_trails};
+use crate::units;
 use anyhow::Result;
 use colored::Colorize;
 use rand::seq::SliceRandom;
@@ -25,19 +27,25 @@ pub fn handle_random(difficulty: Option<String>, max_distance: Option<f64>) -> R
     println!("\n{} Random pick:\n", Icons::RANDOM);
     println!("  {}", trail.name.bold());
     println!("  {}", trail.park);
+    let units = units::get();
+    let estimate = estimate_trail(trail)?;
     println!(
-        "  {} • {:.1}km • ~{:.0}h",
+        "  {} • {} • ~{} ({})",
         format_difficulty(trail.difficulty),
-        trail.length_km,
-        (trail.length_km / 3.0).ceil()
+        units.distance(trail.length_km, 1),
+        estimate.range(),
+        estimate.describe()
+    );
+    println!(
+        "  {} from Montreal",
+        units.distance(trail.distance_from_mtl, 0)
     );
-    println!("  {:.0}km from Montreal", trail.distance_from_mtl);
 
     if let Ok(weather) = get_weather(trail.lat, trail.lng) {
         println!(
-            "\n  Today: {} {:.0}°C, {}",
+            "\n  Today: {} {}, {}",
             Icons::weather(weather.weather_code),
-            weather.temperature,
+            units.temperature(weather.temperature),
             weather.description()
         );
     }
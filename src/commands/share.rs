@@ -1,6 +1,8 @@
 use crate::conditions::get_park_url;
+use crate::estimate::estimate_trail;
 use crate::icons::Icons;
 use crate::trails::{find_trail_by_name, load_trails};
+use crate::units;
 use anyhow::Result;
 use colored::Colorize;
 use qrcode::QrCode;
@@ -22,26 +24,18 @@ pub fn handle_share(trail_name: &str) -> Result<()> {
         .map(|d| d.to_string())
         .unwrap_or_else(|| "Unknown".to_string());
 
-    let hours = (trail.length_km / 3.0).ceil() as u32;
-    let estimated_time = if hours >= 2 {
-        format!("~{}-{}h", hours - 1, hours + 1)
-    } else {
-        "~1-2h".to_string()
-    };
+    let estimate = estimate_trail(trail)?;
+    let estimated_time = format!("~{} ({})", estimate.range(), estimate.describe());
 
     println!("\n{} Share: {}\n", Icons::SHARE, trail.name.bold());
     println!("Text (copy/paste):");
     println!("──────────────────");
     println!("{} — {}", trail.name.bold(), trail.park);
-    println!(
-        "{:.1}km • {} • {}",
-        trail.length_km, difficulty_display, estimated_time
-    );
-    println!(
-        "{} {:.0}km from Montreal",
-        Icons::LOCATION,
-        trail.distance_from_mtl
-    );
+    let units = units::get();
+    let length = units.distance(trail.length_km, 1);
+    let from_montreal = units.distance(trail.distance_from_mtl, 0);
+    println!("{} • {} • {}", length, difficulty_display, estimated_time);
+    println!("{} {} from Montreal", Icons::LOCATION, from_montreal);
     println!("{} {}", Icons::LINK, url);
     println!("──────────────────\n");
 
@@ -63,14 +57,14 @@ pub fn handle_share(trail_name: &str) -> Result<()> {
     // Save to file
     let filename = format!("{}-share.txt", trail.name.replace(' ', "_").to_lowercase());
     let content = format!(
-        "{}\n{}\n{:.1}km • {} • {}\n{} {:.0}km from Montreal\n{} {}",
+        "{}\n{}\n{} • {} • {}\n{} {} from Montreal\n{} {}",
         trail.name,
         trail.park,
-        trail.length_km,
+        length,
         difficulty_display,
         estimated_time,
         Icons::LOCATION,
-        trail.distance_from_mtl,
+        from_montreal,
         Icons::LINK,
         url
     );
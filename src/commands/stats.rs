@@ -1,11 +1,81 @@
-use crate::data::logs::load_logs;
+use crate::data::logs::{HikeLog, WeatherSnapshot, load_logs, parse_date};
 use crate::icons::Icons;
+use crate::trails::{Trail, load_trails, match_parks};
+use crate::tui;
+use crate::units;
 use anyhow::Result;
-use chrono::{Datelike, Local};
+use chrono::{Datelike, Local, NaiveDate};
 use colored::Colorize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Width of the busiest month's bar
+const MONTH_BAR_WIDTH: usize = 20;
+
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Totals over a set of hikes
+#[derive(Debug, Default, Clone, Copy)]
+struct Totals {
+    hikes: usize,
+    km: f64,
+    /// Over the hikes with a known gain
+    gain: f64,
+    gain_hikes: usize,
+    minutes: u32,
+    /// Distance of the hikes with a duration, for the pace
+    timed_km: f64,
+}
+
+impl Totals {
+    fn of(hikes: &[&HikeLog], trails: &[Trail]) -> Self {
+        let mut totals = Self::default();
+        for hike in hikes {
+            totals.hikes += 1;
+            totals.km += hike.hiked_km();
+            if let Some(gain) = hike.gain(trails) {
+                totals.gain += gain;
+                totals.gain_hikes += 1;
+            }
+            if let Some(minutes) = hike.duration_minutes {
+                totals.minutes += minutes;
+                totals.timed_km += hike.hiked_km();
+            }
+        }
+        totals
+    }
+
+    /// Average speed over the timed hikes, in km/h
+    fn pace(&self) -> Option<f64> {
+        (self.minutes > 0).then(|| self.timed_km * 60.0 / f64::from(self.minutes))
+    }
+}
+
+pub fn handle_stats(
+    year: Option<i32>,
+    from: Option<&str>,
+    to: Option<&str>,
+    park: Option<&str>,
+    compare_years: bool,
+    tui: bool,
+) -> Result<()> {
+    let (from, to) = match year {
+        Some(year) => (
+            Some(format!("{}-01-01", year)),
+            Some(format!("{}-12-31", year)),
+        ),
+        None => (
+            from.map(parse_date).transpose()?,
+            to.map(parse_date).transpose()?,
+        ),
+    };
+    if let (Some(from), Some(to)) = (&from, &to)
+        && from > to
+    {
+        anyhow::bail!("--from {} is after --to {}", from, to);
+    }
 
-pub fn handle_stats() -> Result<()> {
     let logs = load_logs()?;
 
     if logs.hikes.is_empty() {
@@ -16,75 +86,421 @@ pub fn handle_stats() -> Result<()> {
         return Ok(());
     }
 
-    let total_hikes = logs.hikes.len();
-    let total_distance: f64 = logs.hikes.iter().map(|h| h.distance_km).sum();
-    let total_minutes: u32 = logs.hikes.iter().filter_map(|h| h.duration_minutes).sum();
-    let total_hours = total_minutes / 60;
-    let total_mins = total_minutes % 60;
+    let trails = load_trails()?;
+    let parks = park
+        .map(|name| matching_parks(name, &trails, &logs.hikes))
+        .transpose()?;
+    let in_scope = |from: Option<&str>, to: Option<&str>| -> Vec<&HikeLog> {
+        logs.between(from, to)
+            .into_iter()
+            .filter(|h| parks.as_ref().is_none_or(|p| p.contains(h.park.as_str())))
+            .collect()
+    };
+    let hikes = in_scope(from.as_deref(), to.as_deref());
 
-    let parks: std::collections::HashSet<_> = logs.hikes.iter().map(|h| h.park.as_str()).collect();
-    let trails: std::collections::HashSet<_> =
-        logs.hikes.iter().map(|h| h.trail_name.as_str()).collect();
+    let mut scope = Vec::new();
+    match (year, &from, &to) {
+        (Some(year), _, _) => scope.push(year.to_string()),
+        (None, Some(from), Some(to)) => scope.push(format!("{} to {}", from, to)),
+        (None, Some(from), None) => scope.push(format!("since {}", from)),
+        (None, None, Some(to)) => scope.push(format!("until {}", to)),
+        (None, None, None) => {}
+    }
+    if let Some(parks) = &parks {
+        match parks.iter().next() {
+            Some(only) if parks.len() == 1 => scope.push(only.to_string()),
+            _ => scope.push(format!("parks matching '{}'", park.unwrap_or_default())),
+        }
+    }
+    let scope = if scope.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", scope.join(", "))
+    };
 
-    let longest = logs.hikes.iter().max_by(|a, b| {
-        a.distance_km
-            .partial_cmp(&b.distance_km)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
+    if hikes.is_empty() {
+        println!("\n{} No hikes logged{}.", Icons::STATS.yellow(), scope);
+        return Ok(());
+    }
 
-    let trail_counts: HashMap<&str, usize> =
-        logs.hikes
-            .iter()
-            .map(|h| h.trail_name.as_str())
-            .fold(HashMap::new(), |mut acc, name| {
-                *acc.entry(name).or_insert(0) += 1;
-                acc
-            });
-
-    let most_visited = trail_counts.iter().max_by_key(|(_, count)| *count);
-
-    println!("\n{} Your Hiking Stats\n", Icons::STATS);
-    println!("  Total hikes:      {}", total_hikes);
-    println!("  Total distance:   {:.1} km", total_distance);
-    println!("  Total time:       {}h {}min\n", total_hours, total_mins);
-    println!("  Parks visited:    {}", parks.len());
-    println!("  Trails completed: {}\n", trails.len());
+    if tui {
+        tui::run_stats_tui(&scope, &hikes, &trails)?;
+        return Ok(());
+    }
 
-    if let Some(hike) = longest {
-        let duration_str = match hike.duration_minutes {
-            Some(m) => format!("{}h{}m", m / 60, m % 60),
-            None => "unknown".into(),
+    if compare_years {
+        println!("\n{} Year Over Year{}\n", Icons::STATS, scope);
+        print_years(&hikes, &trails);
+        return Ok(());
+    }
+
+    let totals = Totals::of(&hikes, &trails);
+    let units = units::get();
+    println!("\n{} Your Hiking Stats{}\n", Icons::STATS, scope);
+    println!("  Total hikes:      {}", totals.hikes);
+    println!("  Total distance:   {}", units.distance(totals.km, 1));
+    if totals.gain_hikes > 0 {
+        print!("  Elevation gain:   {}", units.elevation(totals.gain));
+        if totals.gain_hikes < totals.hikes {
+            print!(" ({} of {} hikes)", totals.gain_hikes, totals.hikes);
+        }
+        println!();
+    }
+    println!("  Total time:       {}", format_minutes(totals.minutes));
+    if let Some(pace) = totals.pace() {
+        println!("  Average pace:     {}", format_pace(pace));
+    }
+    println!();
+
+    if let Some(year) = year {
+        let today = Local::now().date_naive();
+        // A year in progress is compared with the same days of the year before
+        let (before, label) = if year == today.year() {
+            let same_day = today.format("-%m-%d").to_string();
+            (
+                in_scope(
+                    Some(&format!("{}-01-01", year - 1)),
+                    Some(&format!("{}{}", year - 1, same_day)),
+                ),
+                format!("{} by {}", year - 1, today.format("%b %-d")),
+            )
+        } else {
+            (
+                in_scope(
+                    Some(&format!("{}-01-01", year - 1)),
+                    Some(&format!("{}-12-31", year - 1)),
+                ),
+                (year - 1).to_string(),
+            )
         };
+        if before.is_empty() {
+            println!("  No hikes in {} to compare with\n", label);
+        } else {
+            println!(
+                "  vs {}: {}\n",
+                label,
+                changes(&totals, &Totals::of(&before, &trails))
+            );
+        }
+    }
+
+    let heart_rates: Vec<u32> = hikes
+        .iter()
+        .filter_map(|h| h.avg_heart_rate)
+        .map(u32::from)
+        .collect();
+    if !heart_rates.is_empty() {
+        let max_heart_rate = hikes.iter().filter_map(|h| h.max_heart_rate).max();
         println!(
-            "  Longest hike:     {} ({:.1}km, {})",
-            hike.trail_name.bold(),
-            hike.distance_km,
-            duration_str
+            "  Avg heart rate:   {} bpm over {} recorded hike{}{}",
+            heart_rates.iter().sum::<u32>() / heart_rates.len() as u32,
+            heart_rates.len(),
+            if heart_rates.len() == 1 { "" } else { "s" },
+            max_heart_rate
+                .map(|max| format!(", max {}", max))
+                .unwrap_or_default()
+        );
+    }
+    let calories: Vec<u32> = hikes.iter().filter_map(|h| h.calories).collect();
+    if !calories.is_empty() {
+        println!(
+            "  Calories:         {} kcal ({} per hike)\n",
+            calories.iter().sum::<u32>(),
+            calories.iter().sum::<u32>() / calories.len() as u32
         );
+    } else if !heart_rates.is_empty() {
+        println!();
     }
+    let with_weather: Vec<(&HikeLog, &WeatherSnapshot)> = hikes
+        .iter()
+        .filter_map(|&h| h.weather.as_ref().map(|w| (h, w)))
+        .collect();
+    if !with_weather.is_empty() {
+        let count = with_weather.len();
+        let mean_temp = with_weather.iter().map(|(_, w)| w.mean_temp()).sum::<f64>() / count as f64;
+        let wet = with_weather.iter().filter(|(_, w)| w.is_wet()).count();
+        println!(
+            "  Avg temperature:  {} ({} hike{} with weather)",
+            units.temperature(mean_temp),
+            count,
+            if count == 1 { "" } else { "s" }
+        );
+        println!(
+            "  In the rain:      {} hike{} ({:.0}%)",
+            wet,
+            if wet == 1 { "" } else { "s" },
+            wet as f64 * 100.0 / count as f64
+        );
+        let coldest = with_weather
+            .iter()
+            .min_by(|a, b| a.1.min_temp.total_cmp(&b.1.min_temp));
+        let warmest = with_weather
+            .iter()
+            .max_by(|a, b| a.1.max_temp.total_cmp(&b.1.max_temp));
+        if let (Some((cold, cold_weather)), Some((warm, warm_weather))) = (coldest, warmest) {
+            println!(
+                "  Coldest:          {} ({}, {})",
+                cold.trail_name.bold(),
+                units.temperature(cold_weather.min_temp),
+                cold.date
+            );
+            println!(
+                "  Warmest:          {} ({}, {})\n",
+                warm.trail_name.bold(),
+                units.temperature(warm_weather.max_temp),
+                warm.date
+            );
+        }
+    }
+
+    let parks: HashSet<_> = hikes.iter().map(|h| h.park.as_str()).collect();
+    let trail_names: HashSet<_> = hikes.iter().map(|h| h.trail_name.as_str()).collect();
+    println!("  Parks visited:    {}", parks.len());
+    println!("  Trails completed: {}\n", trail_names.len());
 
+    print_records(&hikes, &trails);
+
+    let mut trail_counts: HashMap<&str, usize> = HashMap::new();
+    for hike in &hikes {
+        *trail_counts.entry(hike.trail_name.as_str()).or_insert(0) += 1;
+    }
+    let most_visited = trail_counts
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)));
     if let Some((trail, count)) = most_visited {
         println!("  Most visited:     {} ({} times)\n", trail.bold(), count);
     }
 
-    // By month
-    println!("  By month ({}):", Local::now().year());
-    let mut month_counts: HashMap<u32, usize> = HashMap::new();
-    for hike in &logs.hikes {
-        if let Ok(date) = chrono::NaiveDate::parse_from_str(&hike.date, "%Y-%m-%d") {
-            *month_counts.entry(date.month()).or_insert(0) += 1;
+    print_months(&hikes);
+
+    Ok(())
+}
+
+/// Park names matching a name or code, as in `rando progress --park`
+fn matching_parks<'a>(
+    name: &str,
+    trails: &'a [Trail],
+    hikes: &'a [HikeLog],
+) -> Result<HashSet<&'a str>> {
+    // Hikes may name parks that left the catalog; they have no code
+    let parks: HashSet<&str> = match_parks(
+        name,
+        trails
+            .iter()
+            .map(|t| (t.park.as_str(), t.park_code.as_str()))
+            .chain(hikes.iter().map(|h| (h.park.as_str(), ""))),
+    )
+    .into_iter()
+    .collect();
+    if parks.is_empty() {
+        anyhow::bail!("No park matches '{}'", name);
+    }
+    Ok(parks)
+}
+
+fn print_records(hikes: &[&HikeLog], trails: &[Trail]) {
+    let units = units::get();
+    let record = |label: &str, hike: &HikeLog, value: String| {
+        println!(
+            "  {:<18}{} ({}, {})",
+            format!("{}:", label),
+            hike.trail_name.bold(),
+            value,
+            hike.date
+        );
+    };
+
+    if let Some(hike) = hikes
+        .iter()
+        .max_by(|a, b| a.hiked_km().total_cmp(&b.hiked_km()))
+    {
+        record("Longest hike", hike, units.distance(hike.hiked_km(), 1));
+    }
+    if let Some((hike, gain)) = hikes
+        .iter()
+        .filter_map(|h| h.gain(trails).map(|g| (h, g)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+    {
+        record("Biggest climb", hike, units.elevation(gain));
+    }
+    if let Some((hike, minutes)) = hikes
+        .iter()
+        .filter_map(|h| h.duration_minutes.map(|m| (h, m)))
+        .max_by_key(|(_, m)| *m)
+    {
+        record("Longest time", hike, format_minutes(minutes));
+    }
+    if let Some((hike, pace)) = hikes
+        .iter()
+        .filter_map(|h| {
+            h.duration_minutes
+                .filter(|&m| m > 0)
+                .map(|m| (h, h.hiked_km() * 60.0 / f64::from(m)))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+    {
+        record("Fastest pace", hike, format_pace(pace));
+    }
+}
+
+/// Hikes per month, over the years in scope
+fn print_months(hikes: &[&HikeLog]) {
+    let units = units::get();
+    let dates: Vec<(NaiveDate, &HikeLog)> = hikes
+        .iter()
+        .filter_map(|h| {
+            NaiveDate::parse_from_str(&h.date, "%Y-%m-%d")
+                .ok()
+                .map(|d| (d, *h))
+        })
+        .collect();
+    let years: BTreeSet<i32> = dates.iter().map(|(d, _)| d.year()).collect();
+    let label = match (years.first(), years.last()) {
+        (Some(first), Some(last)) if first == last => first.to_string(),
+        (Some(first), Some(last)) => format!("{}-{}, all years combined", first, last),
+        _ => return,
+    };
+
+    let mut counts = [0usize; 12];
+    let mut distances = [0.0; 12];
+    for (date, hike) in &dates {
+        counts[date.month0() as usize] += 1;
+        distances[date.month0() as usize] += hike.hiked_km();
+    }
+    let busiest = counts.iter().copied().max().unwrap_or(0).max(1);
+
+    println!("  By month ({}):", label);
+    for (idx, month_name) in MONTH_NAMES.iter().enumerate() {
+        let count = counts[idx];
+        if count == 0 {
+            println!("    {}", month_name);
+        } else {
+            println!(
+                "    {} {} {} ({})",
+                month_name,
+                "█".repeat((count * MONTH_BAR_WIDTH).div_ceil(busiest)),
+                count,
+                units.distance(distances[idx], 1)
+            );
         }
     }
+}
+
+/// Totals per calendar year, then how each year changed from the one before
+fn print_years(hikes: &[&HikeLog], trails: &[Trail]) {
+    let units = units::get();
+    let today = Local::now().date_naive();
+    let mut by_year: BTreeMap<i32, Vec<&HikeLog>> = BTreeMap::new();
+    for hike in hikes {
+        if let Ok(date) = NaiveDate::parse_from_str(&hike.date, "%Y-%m-%d") {
+            by_year.entry(date.year()).or_default().push(hike);
+        }
+    }
+    let (Some(&first), Some(&last)) = (by_year.keys().next(), by_year.keys().next_back()) else {
+        return;
+    };
+
+    println!(
+        "  {:<6} {:>5} {:>10} {:>9} {:>11} {:>9}",
+        "Year", "Hikes", "Distance", "Gain", "Time", "Pace"
+    );
+    let mut years = Vec::new();
+    for year in first..=last {
+        let year_hikes = by_year.get(&year).cloned().unwrap_or_default();
+        let totals = Totals::of(&year_hikes, trails);
+        println!(
+            "  {:<6} {:>5} {:>10} {:>9} {:>11} {:>9}",
+            year,
+            totals.hikes,
+            units.distance(totals.km, 1),
+            if totals.gain_hikes > 0 {
+                units.elevation(totals.gain)
+            } else {
+                "-".into()
+            },
+            format_minutes(totals.minutes),
+            totals.pace().map(format_pace).unwrap_or_else(|| "-".into())
+        );
+        years.push((year, year_hikes, totals));
+    }
+
+    if years.len() < 2 {
+        println!("\n  Only one year of hikes to compare");
+        return;
+    }
+
+    println!();
+    for pair in years.windows(2) {
+        let (previous_year, previous_hikes, previous) = &pair[0];
+        let (year, _, totals) = &pair[1];
+        if *year == today.year() {
+            // Compare the year so far with the same days of the year before
+            let same_day = today.format("%m-%d").to_string();
+            let so_far: Vec<&HikeLog> = previous_hikes
+                .iter()
+                .filter(|h| h.date.get(5..).is_some_and(|d| d <= same_day.as_str()))
+                .copied()
+                .collect();
+            println!(
+                "  {} vs {} by {}: {}",
+                year,
+                previous_year,
+                today.format("%b %-d"),
+                changes(totals, &Totals::of(&so_far, trails))
+            );
+        } else {
+            println!(
+                "  {} vs {}: {}",
+                year,
+                previous_year,
+                changes(totals, previous)
+            );
+        }
+    }
+}
 
-    let month_names = [
-        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+/// e.g. "+2 hikes (+20%), +23.4km (+23%), +1,200m (+30%), -1h 5min (-4%)"
+fn changes(now: &Totals, before: &Totals) -> String {
+    let units = units::get();
+    let mut parts = vec![
+        change(now.hikes as f64, before.hikes as f64, |n| {
+            format!("{:.0} hike{}", n, if n == 1.0 { "" } else { "s" })
+        }),
+        change(now.km, before.km, |km| units.distance(km, 1)),
     ];
-    for (idx, month_name) in month_names.iter().enumerate() {
-        let month_num = (idx + 1) as u32;
-        let count = month_counts.get(&month_num).copied().unwrap_or(0);
-        let bar = "█".repeat(count.min(10));
-        println!("    {} {}", month_name, bar);
+    // Left out when neither side has any data
+    if now.gain_hikes + before.gain_hikes > 0 {
+        parts.push(change(now.gain, before.gain, |m| units.elevation(m)));
     }
+    if now.minutes + before.minutes > 0 {
+        parts.push(change(
+            f64::from(now.minutes),
+            f64::from(before.minutes),
+            |m| format_minutes(m as u32),
+        ));
+    }
+    parts.join(", ")
+}
 
-    Ok(())
+fn change(now: f64, before: f64, format: impl Fn(f64) -> String) -> String {
+    let diff = now - before;
+    let sign = if diff < 0.0 { "-" } else { "+" };
+    let mut text = format!("{}{}", sign, format(diff.abs()));
+    if before > 0.0 {
+        text.push_str(&format!(" ({:+.0}%)", diff * 100.0 / before));
+    }
+    text
+}
+
+fn format_minutes(minutes: u32) -> String {
+    format!("{}h {}min", minutes / 60, minutes % 60)
+}
+
+fn format_pace(kmh: f64) -> String {
+    let units = units::get();
+    format!(
+        "{}{}/h",
+        units.number(units.distance_value(kmh), 1),
+        units.distance_unit()
+    )
 }
@@ -0,0 +1,128 @@
+use crate::config::load_config;
+use crate::estimate::{Estimator, format_hours};
+use crate::icons::Icons;
+use crate::profile::get_trail_profile;
+use crate::services::weather::get_hourly_convection;
+use crate::storm::{StormRisk, plan_exposure, storm_windows, summit_exposure};
+use crate::trails::{find_trail_by_name, load_trails};
+use crate::units;
+use anyhow::{Context, Result};
+use chrono::{Duration, Local, NaiveTime, Timelike};
+use colored::Colorize;
+
+pub fn handle_storm(trail_name: &str, start: Option<&str>) -> Result<()> {
+    let trails = load_trails()?;
+    let trail = find_trail_by_name(&trails, trail_name)
+        .ok_or_else(|| anyhow::anyhow!("Trail not found: {}", trail_name))?;
+
+    let now = Local::now().naive_local();
+    let start = match start {
+        Some(s) => now.date().and_time(
+            NaiveTime::parse_from_str(s, "%H:%M")
+                .with_context(|| format!("Invalid start time: '{}'. Use HH:MM", s))?,
+        ),
+        // Next full hour, which is tomorrow after 23:00
+        None => (now + Duration::hours(1))
+            .with_minute(0)
+            .and_then(|t| t.with_second(0))
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap_or(now),
+    };
+    let day = if start.date() == now.date() {
+        "today"
+    } else {
+        "tomorrow"
+    };
+
+    let hours = get_hourly_convection(trail.lat, trail.lng)?;
+    let windows = storm_windows(&hours, start.date());
+    let units = units::get();
+    let time = |t: chrono::NaiveDateTime| units.clock_time(t.hour(), t.minute());
+
+    println!(
+        "\n{} Lightning Check: {}\n",
+        Icons::STORM,
+        trail.name.bold()
+    );
+
+    if windows.is_empty() {
+        println!(
+            "  {} No thunderstorm risk in the forecast {}",
+            Icons::SUCCESS.green(),
+            day
+        );
+    } else {
+        println!("  Thunderstorm risk {}:", day);
+        for window in &windows {
+            let risk = match window.risk {
+                StormRisk::High => window.risk.label().red().bold(),
+                _ => window.risk.label().yellow(),
+            };
+            let lifted_index = window
+                .min_lifted_index
+                .map(|li| format!(", LI {}", units.number(li, 0)))
+                .unwrap_or_default();
+            println!(
+                "    {}-{}  {} ({:.0}% storm chance, CAPE {:.0} J/kg{})",
+                time(window.start),
+                time(window.end),
+                risk,
+                window.max_probability,
+                window.max_cape,
+                lifted_index
+            );
+        }
+    }
+
+    println!("\nFetching elevation data...");
+    let profile = get_trail_profile(trail).unwrap_or_default();
+    let estimate = Estimator::for_user(&load_config()?).estimate(trail.length_km, Some(&profile));
+    let duration_hours = estimate.hours;
+    let Some(exposure) = summit_exposure(&profile) else {
+        println!(
+            "  {} No exposed summit on this trail (or elevation unavailable)",
+            Icons::INFO
+        );
+        return Ok(());
+    };
+
+    let plan = plan_exposure(&exposure, &windows, start, duration_hours);
+    println!(
+        "  Summit exposure: above {} ({} relief)",
+        units.elevation(exposure.threshold),
+        units.elevation(exposure.relief)
+    );
+    println!(
+        "  Starting at {} (~{} hike, {}): exposed {}-{}",
+        time(start),
+        format_hours(duration_hours),
+        estimate.describe(),
+        time(plan.exposed_from),
+        time(plan.exposed_until)
+    );
+
+    if let Some(window) = &plan.conflict {
+        println!(
+            "\n  {} Exposed section overlaps the {} storm window starting {}",
+            Icons::WARNING.red(),
+            window.risk.label().to_lowercase(),
+            time(window.start)
+        );
+    } else if let Some(clearance) = plan.clearance {
+        println!(
+            "\n  {} Exposed section clears the storm windows with {} to spare",
+            Icons::SUCCESS.green(),
+            format_hours(clearance.num_minutes() as f64 / 60.0)
+        );
+    }
+
+    if let (Some(turnaround), Some(latest_start)) = (plan.turnaround, plan.latest_start) {
+        println!(
+            "  Turnaround: if you haven't reached the exposed section by {}, turn back",
+            time(turnaround)
+        );
+        println!("  Latest start from the trailhead: {}", time(latest_start));
+    }
+
+    Ok(())
+}
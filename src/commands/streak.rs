@@ -1,10 +1,35 @@
-use crate::data::logs::load_logs;
+use crate::config::load_config;
+use crate::data::logs::{HikeLog, load_logs};
 use crate::icons::Icons;
+use crate::streak::{
+    Cadence, Forecast, Goal, GoalMetric, Period, format_amount, goal_progress, streak,
+};
+use crate::trails::{Trail, load_trails};
 use anyhow::Result;
-use chrono::{Datelike, Local, NaiveDate};
+use chrono::{Local, NaiveDate};
 use colored::Colorize;
 
-pub fn handle_streak() -> Result<()> {
+const BAR_WIDTH: usize = 10;
+
+pub fn handle_streak(cadence: &str) -> Result<()> {
+    let cadence: Cadence = cadence.parse().map_err(|_| {
+        anyhow::anyhow!(
+            "Invalid cadence: '{}'. Valid options are: daily, weekly, monthly",
+            cadence
+        )
+    })?;
+    let goals = load_config()?
+        .goals
+        .iter()
+        .map(|g| {
+            g.parse::<Goal>().map_err(|_| {
+                anyhow::anyhow!(
+                    "Invalid goal in config: '{}'. Use e.g. \"2 hikes/week\" or \"40 km/month\" (hikes, km, m or h per day, week or month)",
+                    g
+                )
+            })
+        })
+        .collect::<Result<Vec<Goal>>>()?;
     let logs = load_logs()?;
 
     if logs.hikes.is_empty() {
@@ -15,65 +40,8 @@ pub fn handle_streak() -> Result<()> {
         return Ok(());
     }
 
-    // Parse dates and group by week
-    let mut week_hikes: std::collections::HashSet<(i32, u32)> = std::collections::HashSet::new();
-    let mut all_weeks: Vec<(i32, u32)> = Vec::new();
-
-    for hike in &logs.hikes {
-        if let Ok(date) = NaiveDate::parse_from_str(&hike.date, "%Y-%m-%d") {
-            let week = date.iso_week();
-            let week_key = (week.year(), week.week());
-            week_hikes.insert(week_key);
-            all_weeks.push(week_key);
-        }
-    }
-
-    all_weeks.sort();
-    all_weeks.dedup();
-
-    // Calculate current streak
-    let now = Local::now();
-    let current_week = now.iso_week();
-    let current_week_key = (current_week.year(), current_week.week());
-
-    let mut current_streak = 0;
-    let mut check_week = current_week_key;
-
-    loop {
-        if week_hikes.contains(&check_week) {
-            current_streak += 1;
-            // Go to previous week
-            if check_week.1 > 1 {
-                check_week.1 -= 1;
-            } else {
-                check_week.0 -= 1;
-                check_week.1 = 52; // Approximate
-            }
-        } else {
-            break;
-        }
-    }
-
-    // Find longest streak
-    let mut longest_streak = 1;
-    let mut current_run = 1;
-    for i in 1..all_weeks.len() {
-        let prev = all_weeks[i - 1];
-        let curr = all_weeks[i];
-
-        // Check if consecutive weeks
-        if (curr.0 == prev.0 && curr.1 == prev.1 + 1)
-            || (curr.0 == prev.0 + 1 && curr.1 == 1 && prev.1 >= 52)
-        {
-            current_run += 1;
-            longest_streak = longest_streak.max(current_run);
-        } else {
-            current_run = 1;
-        }
-    }
-
-    // Find last hike
-    let last_hike = logs
+    let today = Local::now().date_naive();
+    let dated: Vec<(NaiveDate, &HikeLog)> = logs
         .hikes
         .iter()
         .filter_map(|h| {
@@ -81,17 +49,31 @@ pub fn handle_streak() -> Result<()> {
                 .ok()
                 .map(|d| (d, h))
         })
-        .max_by_key(|(d, _)| *d);
+        .collect();
+    let dates: Vec<NaiveDate> = dated.iter().map(|(d, _)| *d).collect();
+    let streak = streak(&dates, cadence, today);
+    let noun = cadence.noun();
+    let this_period = match cadence {
+        Cadence::Daily => "today".to_string(),
+        cadence => format!("this {}", cadence.noun()),
+    };
+    let plural = |n: u32| if n == 1 { "" } else { "s" };
 
     println!("\n{} Hiking Streak\n", Icons::STREAK);
-    println!(
-        "  Current streak:  {} week{}",
-        current_streak,
-        if current_streak != 1 { "s" } else { "" }
+    print!(
+        "  Current streak:  {} {}{}",
+        streak.current,
+        noun,
+        plural(streak.current)
     );
+    if streak.current > 0 && streak.pending {
+        let this = Period::containing(cadence, today);
+        print!(" (hike by {} to keep it)", this.end().format("%a %b %-d"));
+    }
+    println!();
 
-    if let Some((date, hike)) = last_hike {
-        let days_ago = (Local::now().date_naive() - date).num_days();
+    if let Some((date, hike)) = dated.iter().max_by_key(|(d, _)| *d) {
+        let days_ago = (today - *date).num_days();
         println!(
             "  Last hike:       {} day{} ago ({})",
             days_ago,
@@ -100,30 +82,125 @@ pub fn handle_streak() -> Result<()> {
         );
     }
 
-    println!(
-        "  Longest streak:  {} week{}",
-        longest_streak,
-        if longest_streak != 1 { "s" } else { "" }
+    print!(
+        "  Longest streak:  {} {}{}",
+        streak.longest,
+        noun,
+        plural(streak.longest)
     );
+    if streak.current > 1 && streak.current == streak.longest {
+        print!(" (current)");
+    } else if let Some(end) = streak.longest_end
+        && streak.longest > 1
+    {
+        print!(" (ended {})", end.label());
+    }
+    println!();
 
-    // This month progress
-    let this_year = now.year();
-    let month_weeks: Vec<_> = all_weeks.iter().filter(|(y, _)| *y == this_year).collect();
-
-    let weeks_this_month = month_weeks.len();
-    println!("\n  This month:      {}/4 weeks", weeks_this_month);
-
-    if current_streak > 0 {
+    if goals.is_empty() {
         println!(
-            "\n  {} Keep it going! Hike this week to continue your streak.",
-            Icons::SUCCESS.green()
+            "\n  Set goals in config.json, e.g. \"goals\": [\"2 hikes/week\", \"40 km/month\"]"
         );
     } else {
+        let trails = if goals.iter().any(|g| g.metric == GoalMetric::Gain) {
+            load_trails()?
+        } else {
+            Vec::new()
+        };
+        println!("\n  Goals:");
+        for goal in &goals {
+            let entries: Vec<(NaiveDate, f64)> = dated
+                .iter()
+                .filter_map(|(d, h)| goal_amount(goal.metric, h, &trails).map(|a| (*d, a)))
+                .collect();
+            print_goal(goal, &entries, today);
+        }
+    }
+
+    if streak.current > 0 && streak.pending {
         println!(
-            "\n  {} Start a new streak by logging a hike this week!",
-            Icons::STREAK.yellow()
+            "\n  {} Keep it going! Hike {} to continue your streak.",
+            Icons::SUCCESS.green(),
+            this_period
+        );
+    } else if streak.current == 0 {
+        println!(
+            "\n  {} Start a new streak by logging a hike {}!",
+            Icons::STREAK.yellow(),
+            this_period
         );
     }
 
     Ok(())
 }
+
+/// What a hike adds toward a goal, None when unknown (no duration or profile)
+fn goal_amount(metric: GoalMetric, hike: &HikeLog, trails: &[Trail]) -> Option<f64> {
+    match metric {
+        GoalMetric::Hikes => Some(1.0),
+        GoalMetric::Distance => Some(hike.hiked_km()),
+        GoalMetric::Gain => hike.gain(trails),
+        GoalMetric::Hours => hike.duration_minutes.map(|m| f64::from(m) / 60.0),
+    }
+}
+
+fn print_goal(goal: &Goal, entries: &[(NaiveDate, f64)], today: NaiveDate) {
+    let progress = goal_progress(goal, entries, today);
+    let ratio = (progress.done / goal.target).min(1.0);
+    let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+    let this_period = match goal.cadence {
+        Cadence::Monthly => format!("in {}", progress.period.start.format("%B")),
+        cadence => format!("this {}", cadence.noun()),
+    };
+
+    println!(
+        "    {:<16} [{}{}] {}/{} {} {}",
+        goal.to_string(),
+        "█".repeat(filled),
+        "░".repeat(BAR_WIDTH - filled),
+        format_amount(progress.done),
+        format_amount(goal.target),
+        goal.metric.unit(),
+        this_period
+    );
+
+    let remaining = goal.target - progress.done;
+    let days_left = (progress.period.end() - today).num_days() + 1;
+    let status = match progress.forecast {
+        Forecast::Met(date) => format!("{} Met on {}", Icons::CHECK, date.format("%a %b %-d"))
+            .green()
+            .to_string(),
+        Forecast::OnTrack(date) => format!(
+            "On track to reach it by {} at your recent pace",
+            date.format("%a %b %-d")
+        ),
+        Forecast::Behind(date) => format!(
+            "{} {} {} to go in {} day{}; at your recent pace you'd reach it {}",
+            Icons::WARNING,
+            format_amount(remaining),
+            goal.metric.unit(),
+            days_left,
+            if days_left == 1 { "" } else { "s" },
+            date.format("%b %-d")
+        )
+        .yellow()
+        .to_string(),
+        Forecast::Unknown => format!(
+            "{} {} to go in {} day{}",
+            format_amount(remaining),
+            goal.metric.unit(),
+            days_left,
+            if days_left == 1 { "" } else { "s" }
+        ),
+    };
+    println!("    {:<16} {}", "", status);
+
+    if progress.met_in_a_row > 1 {
+        println!(
+            "    {:<16} Met {} {}s in a row",
+            "",
+            progress.met_in_a_row,
+            goal.cadence.noun()
+        );
+    }
+}
@@ -1,7 +1,9 @@
-use crate::conditions::{format_condition_url, get_park_url};
+use crate::conditions::{format_condition_url, get_park_url, get_trail_risk};
 use crate::icons::Icons;
+use crate::services::alerts::get_alerts_for_location;
 use crate::services::weather::get_weather;
 use crate::trails::{Difficulty, find_trail_by_name, load_trails};
+use crate::units;
 use anyhow::Result;
 use colored::{ColoredString, Colorize};
 
@@ -19,17 +21,29 @@ pub fn handle_trail(trail_name: &str) -> Result<()> {
     };
 
     println!("  Difficulty: {}", difficulty_display);
-    println!("  Length: {:.1}km", trail.length_km);
-    println!("  Distance from Montreal: {:.0}km", trail.distance_from_mtl);
+    let units = units::get();
+    println!("  Length: {}", units.distance(trail.length_km, 1));
+    if let Some(elevation) = trail.elevation {
+        println!(
+            "  Elevation: ↑{} ↓{}, high point {}",
+            units.elevation(elevation.gain),
+            units.elevation(elevation.loss),
+            units.elevation(elevation.max)
+        );
+    }
+    println!(
+        "  Distance from Montreal: {}",
+        units.distance(trail.distance_from_mtl, 0)
+    );
 
     match get_weather(trail.lat, trail.lng) {
         Ok(weather) => {
             println!(
-                "  {} {:.0}°C, {}, wind {:.0}km/h",
+                "  {} {}, {}, wind {}",
                 Icons::weather(weather.weather_code),
-                weather.temperature,
+                units.temperature(weather.temperature),
                 weather.description(),
-                weather.wind_speed
+                units.speed(weather.wind_speed)
             );
         }
         Err(e) => {
@@ -37,6 +51,35 @@ pub fn handle_trail(trail_name: &str) -> Result<()> {
         }
     }
 
+    match get_alerts_for_location(trail.lat, trail.lng, &trail.park) {
+        Ok(alerts) => super::print_weather_alerts(&alerts),
+        Err(e) => {
+            println!(
+                "  {} Weather alerts unavailable: {}",
+                Icons::WARNING.yellow(),
+                e
+            );
+        }
+    }
+
+    match get_trail_risk(trail) {
+        Ok(risk) => {
+            println!(
+                "  Trail risk: {} ({})",
+                super::format_risk_level(risk.level()),
+                risk.summary()
+            );
+            println!("    {}", risk.details());
+        }
+        Err(e) => {
+            println!(
+                "  {} Recent conditions unavailable: {}",
+                Icons::WARNING.yellow(),
+                e
+            );
+        }
+    }
+
     if !trail.park_code.is_empty() {
         println!(
             "  {}",
@@ -0,0 +1,135 @@
+use crate::cache;
+use crate::icons::Icons;
+use crate::services::elevation::{
+    elevation_provider, fetch_trail_elevation, is_current_sampling, uses_remote_elevation,
+};
+use crate::trails::load_trails;
+use anyhow::Result;
+use colored::Colorize;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Pause between Open-Meteo requests, well under its per-minute limit
+const REQUEST_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Profiles fetched between cache writes; an interruption loses at most this many
+const SAVE_EVERY: usize = 20;
+
+/// Consecutive failures after which the run stops (offline or rate limited)
+const MAX_CONSECUTIVE_FAILURES: usize = 5;
+
+const PROGRESS_WIDTH: usize = 30;
+
+/// Fetch and cache the elevation profile of every trail not cached yet,
+/// or cached with an older sampling
+pub fn handle_update_elevation() -> Result<()> {
+    let trails = load_trails()?;
+    let cached = cache::get_all_cached_elevations();
+    let (already_cached, uncached): (Vec<_>, Vec<_>) = trails.iter().partition(|t| {
+        cached
+            .get(&cache::elevation_key(&t.name, &t.park))
+            .is_some_and(|elevations| is_current_sampling(t, elevations))
+    });
+    let (missing, no_coordinates): (Vec<_>, Vec<_>) = uncached
+        .into_iter()
+        .partition(|t| !t.coordinates_wgs84.is_empty());
+    let skipped = (!no_coordinates.is_empty())
+        .then(|| format!("{} without coordinates skipped", no_coordinates.len()));
+
+    if missing.is_empty() {
+        println!(
+            "{} Elevation profiles already cached for all {} trails{}",
+            Icons::SUCCESS.green(),
+            already_cached.len(),
+            skipped.map(|s| format!(" ({})", s)).unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} Fetching elevation for {} of {} trails ({} already cached{})",
+        Icons::ELEVATION,
+        missing.len(),
+        trails.len(),
+        already_cached.len(),
+        skipped.map(|s| format!(", {}", s)).unwrap_or_default()
+    );
+
+    let provider = elevation_provider()?;
+    let throttle = uses_remote_elevation()?;
+    let mut pending = Vec::new();
+    let mut fetched = 0;
+    let mut failed = 0;
+    let mut consecutive_failures = 0;
+    let mut last_request: Option<Instant> = None;
+
+    for (i, trail) in missing.iter().enumerate() {
+        print_progress(i, missing.len(), &trail.name);
+
+        if throttle && let Some(last) = last_request {
+            thread::sleep(REQUEST_INTERVAL.saturating_sub(last.elapsed()));
+        }
+        last_request = Some(Instant::now());
+
+        match fetch_trail_elevation(trail, provider.as_ref()) {
+            Ok(elevations) => {
+                pending.push((cache::elevation_key(&trail.name, &trail.park), elevations));
+                fetched += 1;
+                consecutive_failures = 0;
+            }
+            Err(_) => {
+                failed += 1;
+                consecutive_failures += 1;
+            }
+        }
+
+        if pending.len() >= SAVE_EVERY {
+            cache::cache_elevations(std::mem::take(&mut pending))?;
+        }
+
+        if consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+            cache::cache_elevations(pending)?;
+            println!();
+            anyhow::bail!(
+                "Stopped after {} failed requests in a row ({} profiles saved). Run again later to resume",
+                consecutive_failures,
+                fetched
+            );
+        }
+    }
+
+    cache::cache_elevations(pending)?;
+    print_progress(missing.len(), missing.len(), "");
+    println!();
+
+    println!(
+        "{} Cached {} elevation profiles",
+        Icons::SUCCESS.green(),
+        fetched
+    );
+    if failed > 0 {
+        println!(
+            "{} {} trails failed; run again to retry them",
+            Icons::WARNING.yellow(),
+            failed
+        );
+    }
+
+    Ok(())
+}
+
+/// Redraw the progress bar on the current line
+fn print_progress(done: usize, total: usize, current: &str) {
+    let filled = done * PROGRESS_WIDTH / total.max(1);
+    let name: String = current.chars().take(30).collect();
+    print!(
+        "\r  [{}{}] {}/{} {:<30}",
+        "█".repeat(filled),
+        "░".repeat(PROGRESS_WIDTH - filled),
+        done,
+        total,
+        name
+    );
+    let _ = io::stdout().flush();
+}
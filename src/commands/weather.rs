@@ -1,17 +1,26 @@
+use crate::conditions::get_winter_conditions;
+use crate::config::load_config;
 use crate::icons::Icons;
-use crate::services::weather::{get_7day_forecast, get_weather};
+use crate::scoring::{Confidence, ScoringProfile, resolve_profile, score_day, score_ensemble};
+use crate::services::air_quality::get_air_quality;
+use crate::services::alerts::get_alerts_for_location;
+use crate::services::weather::{get_7day_forecast, get_hourly_convection, get_weather};
+use crate::storm::storm_windows;
 use crate::trails::{find_trail_by_name, load_trails};
+use crate::units;
 use anyhow::Result;
-use chrono::Datelike;
+use chrono::{Datelike, Timelike};
 use colored::Colorize;
 
-pub fn handle_weather(trail_name: &str, week: bool) -> Result<()> {
+pub fn handle_weather(trail_name: &str, week: bool, profile: Option<&str>) -> Result<()> {
     let trails = load_trails()?;
     let trail = find_trail_by_name(&trails, trail_name)
         .ok_or_else(|| anyhow::anyhow!("Trail not found: {}", trail_name))?;
 
     if week {
-        handle_week_forecast(trail)?;
+        let config = load_config()?;
+        let (profile_name, profile) = resolve_profile(profile, &config)?;
+        handle_week_forecast(trail, &profile_name, &profile)?;
     } else {
         let weather = get_weather(trail.lat, trail.lng)?;
         println!(
@@ -19,28 +28,67 @@ pub fn handle_weather(trail_name: &str, week: bool) -> Result<()> {
             Icons::weather(weather.weather_code),
             trail.name.bold()
         );
-        println!("  Temperature: {:.0}°C", weather.temperature);
-        println!("  Wind: {:.0}km/h", weather.wind_speed);
+        let units = units::get();
+        println!("  Temperature: {}", units.temperature(weather.temperature));
+        println!("  Wind: {}", units.speed(weather.wind_speed));
         println!("  Conditions: {}", weather.description());
+
+        if let Ok(hours) = get_hourly_convection(trail.lat, trail.lng) {
+            let today = chrono::Local::now().date_naive();
+            for window in storm_windows(&hours, today) {
+                println!(
+                    "  {} Thunderstorm risk {}: {}-{} (see rando storm)",
+                    Icons::STORM.yellow(),
+                    window.risk.label().to_lowercase(),
+                    units.clock_time(window.start.hour(), 0),
+                    units.clock_time(window.end.hour(), 0)
+                );
+            }
+        }
+    }
+
+    if let Ok(winter) = get_winter_conditions(trail)
+        && (winter.has_snow() || winter.snowfall_7d > 0.0)
+    {
+        println!("\n  Snow: {}", winter.summary());
+        println!("  {}", winter.details());
+    }
+
+    if let Ok(air) = get_air_quality(trail.lat, trail.lng) {
+        println!();
+        super::print_air_quality(&air);
+    }
+
+    if let Ok(alerts) = get_alerts_for_location(trail.lat, trail.lng, &trail.park)
+        && !alerts.is_empty()
+    {
+        println!();
+        super::print_weather_alerts(&alerts);
     }
 
     Ok(())
 }
 
-fn handle_week_forecast(trail: &crate::trails::Trail) -> Result<()> {
+fn handle_week_forecast(
+    trail: &crate::trails::Trail,
+    profile_name: &str,
+    profile: &ScoringProfile,
+) -> Result<()> {
     let forecast = get_7day_forecast(trail.lat, trail.lng)?;
 
     println!(
-        "\n{}  7-Day Forecast for {} ({})\n",
+        "\n{}  7-Day Forecast for {} ({})",
         Icons::WEATHER,
         trail.name.bold(),
         trail.park
     );
+    println!("  Scoring profile: {}\n", profile_name);
 
+    let units = units::get();
     let mut best_days = Vec::new();
 
     for day in forecast.iter() {
-        let (rating_icon, rating_text) = day.rating();
+        let score = score_day(day, profile);
         let weekday = day.date.weekday();
         let day_name = match weekday {
             chrono::Weekday::Mon => "Mon",
@@ -54,17 +102,46 @@ fn handle_week_forecast(trail: &crate::trails::Trail) -> Result<()> {
 
         let day_num = day.date.day();
         println!(
-            "  {} {:02}   {}  {:.0}°C  Wind: {:.0}km/h  {} {}",
+            "  {} {:02}   {}  {}/{}  Wind: {}  {} {:>3} {:<9} ({})",
             day_name,
             day_num,
             day.icon(),
-            day.max_temp,
-            day.wind_speed,
-            rating_icon,
-            rating_text
+            units.temperature(day.max_temp),
+            units.temperature(day.min_temp),
+            units.speed(day.wind_speed),
+            score.icon(),
+            score.score,
+            score.label(),
+            score.explanation()
         );
 
-        if rating_text == "Excellent" || rating_text == "Good" {
+        let ensemble_score = score_ensemble(day, profile);
+        if let (Some(spread), Some(ensemble)) = (&ensemble_score, &day.ensemble) {
+            let confidence = match spread.confidence() {
+                Confidence::High => spread.confidence().label().green(),
+                Confidence::Medium => spread.confidence().label().yellow(),
+                Confidence::Low => spread.confidence().label().red(),
+            };
+            println!(
+                "             {} confidence: score {}-{}, {:.0}% chance good | high {} to {}, rain {} to {} ({:.0}% wet)",
+                confidence,
+                spread.low,
+                spread.high,
+                spread.good_chance,
+                units.temperature(ensemble.percentile(|m| m.max_temp, 10.0)),
+                units.temperature(ensemble.percentile(|m| m.max_temp, 90.0)),
+                units.precipitation(ensemble.percentile(|m| m.precipitation, 10.0)),
+                units.precipitation(ensemble.percentile(|m| m.precipitation, 90.0)),
+                ensemble.precipitation_chance()
+            );
+        }
+
+        // Prefer days most ensemble members agree are good over a good median alone
+        let recommended = match &ensemble_score {
+            Some(spread) => score.is_good() && spread.is_likely_good(),
+            None => score.is_good(),
+        };
+        if recommended {
             best_days.push((day_name, day_num));
         }
     }
@@ -1,3 +1,13 @@
+use crate::services::weather::{PastDay, get_recent_weather};
+use crate::trails::Trail;
+use crate::units;
+use anyhow::Result;
+use std::fmt;
+use std::str::FromStr;
+
+/// Days of weather history used for the risk assessment
+pub const RECENT_DAYS: u32 = 14;
+
 pub fn get_park_url(park_code: &str) -> String {
     format!("https://www.sepaq.com/pq/{}/", park_code.to_lowercase())
 }
@@ -5,3 +15,370 @@ pub fn get_park_url(park_code: &str) -> String {
 pub fn format_condition_url(url: &str, link_icon: &str) -> String {
     format!("{} Check conditions: {}", link_icon, url)
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RiskLevel {
+    Low,
+    Moderate,
+    High,
+}
+
+impl FromStr for RiskLevel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(RiskLevel::Low),
+            "moderate" => Ok(RiskLevel::Moderate),
+            "high" => Ok(RiskLevel::High),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskLevel::Low => write!(f, "Low"),
+            RiskLevel::Moderate => write!(f, "Moderate"),
+            RiskLevel::High => write!(f, "High"),
+        }
+    }
+}
+
+/// Mud, ice and snow risk (0-10 each) derived from the recent weather history
+#[derive(Debug, Clone)]
+pub struct TrailRisk {
+    pub mud: u8,
+    pub ice: u8,
+    pub snow: u8,
+    pub rain_7d: f64,
+    pub snowfall_7d: f64,
+    pub freeze_thaw_cycles: usize,
+    pub snow_depth: Option<f64>,
+}
+
+impl TrailRisk {
+    pub fn score(&self) -> u8 {
+        self.mud.max(self.ice).max(self.snow)
+    }
+
+    pub fn level(&self) -> RiskLevel {
+        match self.score() {
+            0..=3 => RiskLevel::Low,
+            4..=6 => RiskLevel::Moderate,
+            _ => RiskLevel::High,
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Mud {}/10 • Ice {}/10 • Snow {}/10",
+            self.mud, self.ice, self.snow
+        )
+    }
+
+    pub fn details(&self) -> String {
+        let units = units::get();
+        let mut parts = vec![
+            format!("{} rain", units.precipitation(self.rain_7d)),
+            format!("{} snow", units.snow(self.snowfall_7d)),
+            format!("{} freeze/thaw", self.freeze_thaw_cycles),
+        ];
+        if let Some(depth) = self.snow_depth {
+            parts.push(format!("{} on ground", units.snow(depth)));
+        }
+        format!("last 7 days: {}", parts.join(", "))
+    }
+}
+
+/// Fetch the recent weather for a trail and assess its conditions
+pub fn get_trail_risk(trail: &Trail) -> Result<TrailRisk> {
+    let history = get_recent_weather(trail.lat, trail.lng, RECENT_DAYS)?;
+    Ok(assess_recent_conditions(&history))
+}
+
+/// Fetch the recent weather once for both the risk and the winter assessment
+pub fn get_recent_conditions(trail: &Trail) -> Result<(TrailRisk, WinterConditions)> {
+    let history = get_recent_weather(trail.lat, trail.lng, RECENT_DAYS)?;
+    Ok((
+        assess_recent_conditions(&history),
+        assess_winter_conditions(&history),
+    ))
+}
+
+/// Fetch the recent weather for a trail and assess its snow cover
+pub fn get_winter_conditions(trail: &Trail) -> Result<WinterConditions> {
+    let history = get_recent_weather(trail.lat, trail.lng, RECENT_DAYS)?;
+    Ok(assess_winter_conditions(&history))
+}
+
+/// Score mud, ice and snow risk from daily history (oldest first)
+pub fn assess_recent_conditions(history: &[PastDay]) -> TrailRisk {
+    let last_week = &history[history.len().saturating_sub(7)..];
+    let last_days = &history[history.len().saturating_sub(3)..];
+
+    let rain_7d: f64 = last_week.iter().map(|d| d.rain).sum();
+    let rain_3d: f64 = last_days.iter().map(|d| d.rain).sum();
+    let snowfall_7d: f64 = last_week.iter().map(|d| d.snowfall).sum();
+    let snowfall_3d: f64 = last_days.iter().map(|d| d.snowfall).sum();
+
+    let freeze_thaw_cycles = last_week
+        .iter()
+        .filter(|d| d.max_temp > 0.0 && d.min_temp < 0.0)
+        .count();
+
+    // Rain falling on frozen ground glazes over
+    let freezing_rain = last_week
+        .iter()
+        .filter(|d| d.min_temp < 0.0 && d.rain > 1.0)
+        .count();
+
+    // Warm days with a shrinking snowpack turn trails into streams
+    let melt_days = history
+        .windows(2)
+        .rev()
+        .take(7)
+        .filter(|w| match (w[0].snow_depth, w[1].snow_depth) {
+            (Some(before), Some(after)) => after < before && w[1].max_temp > 3.0,
+            _ => false,
+        })
+        .count();
+
+    let snow_depth = history.iter().rev().find_map(|d| d.snow_depth);
+    let ground_covered = snow_depth.is_some_and(|depth| depth >= 10.0);
+
+    let mut mud = rain_7d / 5.0 + rain_3d / 5.0 + melt_days as f64 * 1.5;
+    if ground_covered {
+        mud /= 2.0;
+    }
+
+    let mut ice = freeze_thaw_cycles as f64 * 1.5 + freezing_rain as f64 * 2.0;
+    if last_days.iter().all(|d| d.min_temp > 2.0) {
+        ice /= 3.0;
+    }
+
+    let depth_score = match snow_depth.unwrap_or(0.0) {
+        d if d < 1.0 => 0.0,
+        d if d < 5.0 => 2.0,
+        d if d < 15.0 => 4.0,
+        d if d < 30.0 => 6.0,
+        d if d < 50.0 => 8.0,
+        _ => 10.0,
+    };
+    let snow = depth_score + snowfall_3d / 5.0;
+
+    TrailRisk {
+        mud: to_score(mud),
+        ice: to_score(ice),
+        snow: to_score(snow),
+        rain_7d,
+        snowfall_7d,
+        freeze_thaw_cycles,
+        snow_depth,
+    }
+}
+
+fn to_score(value: f64) -> u8 {
+    value.round().clamp(0.0, 10.0) as u8
+}
+
+/// Snow depth (cm) below which there is no real snow cover
+const SNOW_COVER_MIN: f64 = 2.0;
+
+/// Snow depth (cm) from which snowshoes beat microspikes
+const SNOWSHOE_DEPTH: f64 = 20.0;
+
+/// Likely state of the snow surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnowSurface {
+    Bare,
+    Patchy,
+    FreshPowder,
+    Packed,
+    Crust,
+    Slush,
+}
+
+impl SnowSurface {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SnowSurface::Bare => "bare",
+            SnowSurface::Patchy => "thin/patchy snow",
+            SnowSurface::FreshPowder => "fresh powder",
+            SnowSurface::Packed => "packed snow",
+            SnowSurface::Crust => "icy crust",
+            SnowSurface::Slush => "wet slush",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Traction {
+    None,
+    Microspikes,
+    Snowshoes,
+}
+
+impl Traction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Traction::None => "no traction needed",
+            Traction::Microspikes => "microspikes",
+            Traction::Snowshoes => "snowshoes",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemperatureTrend {
+    Warming,
+    Steady,
+    Cooling,
+}
+
+impl TemperatureTrend {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TemperatureTrend::Warming => "warming",
+            TemperatureTrend::Steady => "steady",
+            TemperatureTrend::Cooling => "cooling",
+        }
+    }
+}
+
+/// Snow cover, recent snowfall and temperature trend at a trail
+#[derive(Debug, Clone)]
+pub struct WinterConditions {
+    /// Snow on the ground in cm, when the archive has it
+    pub snow_depth: Option<f64>,
+    pub snowfall_3d: f64,
+    pub snowfall_7d: f64,
+    /// Days since the last snowfall of 1cm or more, within the history
+    pub days_since_snowfall: Option<usize>,
+    pub trend: TemperatureTrend,
+    pub surface: SnowSurface,
+    pub traction: Traction,
+}
+
+impl WinterConditions {
+    pub fn has_snow(&self) -> bool {
+        self.snow_depth.is_some_and(|depth| depth >= SNOW_COVER_MIN)
+    }
+
+    /// Enough base for snowshoeing, and not rotting into slush
+    pub fn snowshoe_ready(&self) -> bool {
+        self.snow_depth.is_some_and(|depth| depth >= SNOWSHOE_DEPTH)
+            && self.surface != SnowSurface::Slush
+    }
+
+    /// Crust forms when a thaw refreezes with no new snow on top
+    pub fn crust_risk(&self) -> bool {
+        self.surface == SnowSurface::Crust
+    }
+
+    pub fn summary(&self) -> String {
+        let units = units::get();
+        let depth = match self.snow_depth {
+            Some(depth) if depth >= SNOW_COVER_MIN => format!("{} on ground", units.snow(depth)),
+            _ => "no snow cover".to_string(),
+        };
+        format!(
+            "{}, {}, {} - {}",
+            depth,
+            self.surface.label(),
+            self.trend.label(),
+            self.traction.label()
+        )
+    }
+
+    pub fn details(&self) -> String {
+        let units = units::get();
+        let last_snow = match self.days_since_snowfall {
+            Some(0) => "snowed yesterday".to_string(),
+            Some(days) => format!("last snowfall {} days ago", days + 1),
+            None => "no recent snowfall".to_string(),
+        };
+        format!(
+            "{} new snow in 3 days, {} in 7 days, {}",
+            units.snow(self.snowfall_3d),
+            units.snow(self.snowfall_7d),
+            last_snow
+        )
+    }
+}
+
+/// Estimate the snow surface and needed traction from daily history (oldest first)
+pub fn assess_winter_conditions(history: &[PastDay]) -> WinterConditions {
+    let last_week = &history[history.len().saturating_sub(7)..];
+    let last_days = &history[history.len().saturating_sub(3)..];
+
+    let snowfall_3d: f64 = last_days.iter().map(|d| d.snowfall).sum();
+    let snowfall_7d: f64 = last_week.iter().map(|d| d.snowfall).sum();
+    let days_since_snowfall = history.iter().rev().position(|d| d.snowfall >= 1.0);
+    let snow_depth = history.iter().rev().find_map(|d| d.snow_depth);
+
+    // Mean daytime high of the last 3 days against the 4 before
+    let mean_max = |days: &[PastDay]| {
+        (!days.is_empty()).then(|| days.iter().map(|d| d.max_temp).sum::<f64>() / days.len() as f64)
+    };
+    let earlier = &last_week[..last_week.len() - last_days.len()];
+    let trend = match (mean_max(last_days), mean_max(earlier)) {
+        (Some(recent), Some(before)) if recent - before >= 3.0 => TemperatureTrend::Warming,
+        (Some(recent), Some(before)) if before - recent >= 3.0 => TemperatureTrend::Cooling,
+        _ => TemperatureTrend::Steady,
+    };
+
+    let depth = snow_depth.unwrap_or(0.0);
+    let thawed_since_snow = last_days
+        .iter()
+        .rev()
+        .take_while(|d| d.snowfall < 1.0)
+        .any(|d| d.max_temp > 0.0);
+    let freezing_now = last_days.last().is_some_and(|d| d.min_temp < -2.0);
+    let melting_now = last_days
+        .iter()
+        .all(|d| d.min_temp > -1.0 && d.max_temp > 2.0);
+
+    let surface = if depth < SNOW_COVER_MIN {
+        SnowSurface::Bare
+    } else if depth < 10.0 {
+        SnowSurface::Patchy
+    } else if snowfall_3d >= 10.0 {
+        SnowSurface::FreshPowder
+    } else if melting_now {
+        SnowSurface::Slush
+    } else if thawed_since_snow && freezing_now {
+        SnowSurface::Crust
+    } else {
+        SnowSurface::Packed
+    };
+
+    let traction = match surface {
+        SnowSurface::Bare => {
+            // Refrozen puddles and glazed rock
+            if last_week
+                .iter()
+                .any(|d| d.max_temp > 0.0 && d.min_temp < 0.0)
+                && freezing_now
+            {
+                Traction::Microspikes
+            } else {
+                Traction::None
+            }
+        }
+        SnowSurface::FreshPowder => Traction::Snowshoes,
+        SnowSurface::Crust | SnowSurface::Slush if depth >= SNOWSHOE_DEPTH => Traction::Snowshoes,
+        _ => Traction::Microspikes,
+    };
+
+    WinterConditions {
+        snow_depth,
+        snowfall_3d,
+        snowfall_7d,
+        days_since_snowfall,
+        trend,
+        surface,
+        traction,
+    }
+}
@@ -0,0 +1,56 @@
+use crate::estimate::TimeModel;
+use crate::scoring::ScoringProfile;
+use crate::units::{Clock, UnitSystem};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+const CONFIG_DIR: &str = "rando-qc";
+const CONFIG_FILE: &str = "config.json";
+
+/// User settings read from `config.json` in the config directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Scoring profile used when `--profile` is not given
+    pub scoring_profile: Option<String>,
+    /// User-defined scoring profiles, by name (override built-ins of the same name)
+    pub profiles: HashMap<String, ScoringProfile>,
+    /// "metric" or "imperial"
+    pub units: UnitSystem,
+    /// "24h" or "12h"
+    pub clock: Clock,
+    /// Decimal separator, e.g. ","
+    pub decimal_separator: Option<char>,
+    /// Directory of SRTM `.hgt` or GeoTIFF tiles to read elevations from instead of Open-Meteo
+    pub dem_dir: Option<PathBuf>,
+    /// "naismith", "tobler" or "langmuir"
+    pub time_model: TimeModel,
+    /// Multiplier on estimated hiking times, e.g. 1.2 if you're 20% slower;
+    /// replaces the pace calibrated from logged hikes
+    pub pace: Option<f64>,
+    /// Hiking goals shown by `rando streak`, e.g. "2 hikes/week" or "40 km/month"
+    pub goals: Vec<String>,
+}
+
+pub fn get_config_path() -> Result<PathBuf> {
+    let config_dir = dirs::config_dir()
+        .or_else(|| dirs::home_dir().map(|h| h.join(".config")))
+        .context("Could not find config directory")?
+        .join(CONFIG_DIR);
+
+    Ok(config_dir.join(CONFIG_FILE))
+}
+
+pub fn load_config() -> Result<Config> {
+    let path = get_config_path()?;
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let data = fs::read_to_string(&path).context("Failed to read config file")?;
+    serde_json::from_str(&data)
+        .with_context(|| format!("Failed to parse config file {}", path.display()))
+}
@@ -0,0 +1,584 @@
+use super::logs::{HikeDetails, HikeLog, TrailRef, WeatherSnapshot, get_tracks_dir};
+use crate::tracks::read_track;
+use crate::trails::Trail;
+use crate::units;
+use crate::xml::escape_xml;
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, SecondsFormat, Utc};
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Column order of CSV exports; imports accept any order with these headers
+const CSV_COLUMNS: [&str; 26] = [
+    "id",
+    "date",
+    "start_time",
+    "trail",
+    "park",
+    "distance_km",
+    "actual_distance_km",
+    "duration_minutes",
+    "elevation_gain",
+    "avg_heart_rate",
+    "max_heart_rate",
+    "calories",
+    "other_trails",
+    "track_file",
+    "companions",
+    "rating",
+    "perceived_difficulty",
+    "conditions",
+    "wildlife",
+    "photos",
+    "min_temp_c",
+    "max_temp_c",
+    "precipitation_mm",
+    "wind_kmh",
+    "weather_code",
+    "notes",
+];
+
+/// Separator of items in list columns such as `other_trails`
+const LIST_SEPARATOR: &str = "; ";
+
+/// Between a trail and its park in `other_trails`, when the park isn't the hike's
+const PARK_SEPARATOR: &str = " | ";
+
+/// Longest iCalendar content line, in bytes, before folding
+const ICS_LINE_LIMIT: usize = 75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Gpx,
+    Ics,
+}
+
+impl FromStr for ExportFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            "gpx" => Ok(ExportFormat::Gpx),
+            "ics" | "ical" => Ok(ExportFormat::Ics),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Gpx => "gpx",
+            ExportFormat::Ics => "ics",
+        }
+    }
+}
+
+pub fn to_csv(hikes: &[&HikeLog]) -> String {
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push('\n');
+
+    for hike in hikes {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        let list = |items: &[String]| items.join(LIST_SEPARATOR);
+        let details = &hike.details;
+        let row = [
+            hike.id.to_string(),
+            hike.date.clone(),
+            optional(details.start_time.clone()),
+            hike.trail_name.clone(),
+            hike.park.clone(),
+            format!("{:.2}", hike.distance_km),
+            optional(details.actual_distance_km.map(|km| format!("{:.2}", km))),
+            optional(hike.duration_minutes.map(|m| m.to_string())),
+            optional(hike.elevation_gain.map(|g| format!("{:.0}", g))),
+            optional(hike.avg_heart_rate.map(|hr| hr.to_string())),
+            optional(hike.max_heart_rate.map(|hr| hr.to_string())),
+            optional(hike.calories.map(|c| c.to_string())),
+            list(
+                &hike
+                    .other_trails
+                    .iter()
+                    .map(|t| {
+                        if t.park == hike.park {
+                            t.name.clone()
+                        } else {
+                            format!("{}{}{}", t.name, PARK_SEPARATOR, t.park)
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            optional(hike.track_file.clone()),
+            list(&details.companions),
+            optional(details.rating.map(|r| r.to_string())),
+            optional(details.perceived_difficulty.map(|d| d.to_string())),
+            list(
+                &details
+                    .conditions
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>(),
+            ),
+            list(&details.wildlife),
+            list(&details.photos),
+            optional(hike.weather.map(|w| format!("{:.1}", w.min_temp))),
+            optional(hike.weather.map(|w| format!("{:.1}", w.max_temp))),
+            optional(hike.weather.map(|w| format!("{:.1}", w.precipitation))),
+            optional(hike.weather.map(|w| format!("{:.0}", w.wind_speed))),
+            optional(hike.weather.map(|w| w.weather_code.to_string())),
+            optional(hike.notes.clone()),
+        ];
+        let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Quote fields containing separators, quotes or line breaks (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Split CSV text into rows of fields, honouring quoted fields
+fn parse_csv_rows(text: &str) -> Result<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                // Skip blank lines
+                if row.iter().any(|f| !f.is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                } else {
+                    row.clear();
+                }
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        anyhow::bail!("Unterminated quoted field in CSV");
+    }
+    row.push(field);
+    if row.iter().any(|f| !f.is_empty()) {
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// Hikes from a CSV export; unknown columns are ignored, `date`, `trail` and `park` are required
+pub fn from_csv(text: &str) -> Result<Vec<HikeLog>> {
+    let rows = parse_csv_rows(text.trim_start_matches('\u{feff}'))?;
+    let Some((header, records)) = rows.split_first() else {
+        return Ok(Vec::new());
+    };
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+    };
+    let required =
+        |name: &str| column(name).with_context(|| format!("CSV is missing the '{}' column", name));
+    let (date_col, trail_col, park_col) =
+        (required("date")?, required("trail")?, required("park")?);
+
+    records
+        .iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let line = i + 2;
+            let get = |name: &str| {
+                column(name)
+                    .and_then(|c| record.get(c))
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty())
+            };
+            let text = |col: usize| record.get(col).map(|v| v.trim().to_string());
+            let list = |name: &str| -> Vec<String> {
+                get(name)
+                    .map(|t| t.split(';').map(|s| s.trim().to_string()).collect())
+                    .unwrap_or_default()
+            };
+
+            let park = text(park_col).unwrap_or_default();
+            let other_trails = list("other_trails")
+                .into_iter()
+                .map(|entry| match entry.rsplit_once(PARK_SEPARATOR.trim()) {
+                    Some((name, park)) => TrailRef {
+                        name: name.trim().to_string(),
+                        park: park.trim().to_string(),
+                    },
+                    None => TrailRef {
+                        name: entry,
+                        park: park.clone(),
+                    },
+                })
+                .collect();
+
+            Ok(HikeLog {
+                id: parse(get("id"), "id", line)?.unwrap_or(0),
+                trail_name: text(trail_col)
+                    .filter(|t| !t.is_empty())
+                    .with_context(|| format!("Line {}: missing trail", line))?,
+                park,
+                date: text(date_col).unwrap_or_default(),
+                duration_minutes: parse(get("duration_minutes"), "duration", line)?,
+                distance_km: parse(get("distance_km"), "distance", line)?.unwrap_or(0.0),
+                notes: get("notes").map(str::to_string),
+                elevation_gain: parse(get("elevation_gain"), "elevation gain", line)?,
+                other_trails,
+                avg_heart_rate: parse(get("avg_heart_rate"), "heart rate", line)?,
+                max_heart_rate: parse(get("max_heart_rate"), "heart rate", line)?,
+                calories: parse(get("calories"), "calories", line)?,
+                track_file: get("track_file").map(str::to_string),
+                weather: match (
+                    parse(get("min_temp_c"), "temperature", line)?,
+                    parse(get("max_temp_c"), "temperature", line)?,
+                ) {
+                    (Some(min_temp), Some(max_temp)) => Some(WeatherSnapshot {
+                        min_temp,
+                        max_temp,
+                        precipitation: parse(get("precipitation_mm"), "precipitation", line)?
+                            .unwrap_or(0.0),
+                        wind_speed: parse(get("wind_kmh"), "wind", line)?.unwrap_or(0.0),
+                        weather_code: parse(get("weather_code"), "weather code", line)?
+                            .unwrap_or(0),
+                    }),
+                    _ => None,
+                },
+                details: HikeDetails {
+                    start_time: get("start_time").map(str::to_string),
+                    companions: list("companions"),
+                    rating: parse(get("rating"), "rating", line)?,
+                    perceived_difficulty: parse(
+                        get("perceived_difficulty"),
+                        "perceived difficulty",
+                        line,
+                    )?,
+                    conditions: list("conditions")
+                        .iter()
+                        .map(|c| parse(Some(c), "condition", line))
+                        .filter_map(Result::transpose)
+                        .collect::<Result<_>>()?,
+                    wildlife: list("wildlife"),
+                    photos: list("photos"),
+                    actual_distance_km: parse(get("actual_distance_km"), "actual distance", line)?,
+                },
+            })
+        })
+        .collect()
+}
+
+fn parse<T: FromStr>(value: Option<&str>, name: &str, line: usize) -> Result<Option<T>> {
+    value
+        .map(|v| {
+            v.parse()
+                .map_err(|_| anyhow::anyhow!("Line {}: invalid {} '{}'", line, name, v))
+        })
+        .transpose()
+}
+
+pub fn to_json(hikes: &[&HikeLog]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(hikes)?)
+}
+
+/// One track per hike: the recorded track when one was imported, otherwise the
+/// catalog geometry of the trail. Hikes with neither are left out
+pub fn to_gpx(hikes: &[&HikeLog], trails: &[Trail]) -> Result<(String, usize)> {
+    let tracks_dir = get_tracks_dir()?;
+    let mut gpx = String::new();
+    let mut written = 0;
+    writeln!(gpx, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(gpx, "<gpx version=\"1.1\" creator=\"rando-qc\">")?;
+
+    for hike in hikes {
+        let recorded = hike
+            .track_file
+            .as_ref()
+            .and_then(|file| read_track(&tracks_dir.join(file)).ok());
+        let points: Vec<(f64, f64, Option<f64>, Option<String>)> = match recorded {
+            Some(track) => track
+                .points
+                .iter()
+                .map(|p| {
+                    (
+                        p.lat,
+                        p.lng,
+                        p.elevation,
+                        p.time.map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true)),
+                    )
+                })
+                .collect(),
+            None => match hike.trail(trails) {
+                Some(trail) => trail
+                    .coordinates_wgs84
+                    .iter()
+                    .map(|&(lat, lng)| (lat, lng, None, None))
+                    .collect(),
+                None => continue,
+            },
+        };
+
+        writeln!(gpx, "  <trk>")?;
+        writeln!(
+            gpx,
+            "    <name>{} {}</name>",
+            hike.date,
+            escape_xml(&hike.trail_name)
+        )?;
+        let desc = match &hike.notes {
+            Some(notes) => format!("{} - {}", hike.park, notes),
+            None => hike.park.clone(),
+        };
+        writeln!(gpx, "    <desc>{}</desc>", escape_xml(&desc))?;
+        writeln!(gpx, "    <trkseg>")?;
+        for (lat, lng, ele, time) in points {
+            write!(gpx, "      <trkpt lat=\"{:.6}\" lon=\"{:.6}\">", lat, lng)?;
+            if let Some(ele) = ele {
+                write!(gpx, "<ele>{:.1}</ele>", ele)?;
+            }
+            if let Some(time) = time {
+                write!(gpx, "<time>{}</time>", time)?;
+            }
+            writeln!(gpx, "</trkpt>")?;
+        }
+        writeln!(gpx, "    </trkseg>")?;
+        writeln!(gpx, "  </trk>")?;
+        written += 1;
+    }
+
+    writeln!(gpx, "</gpx>")?;
+    Ok((gpx, written))
+}
+
+/// An all-day event per hike
+pub fn to_ics(hikes: &[&HikeLog]) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//rando-qc//Hike log//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for hike in hikes {
+        let Ok(date) = NaiveDate::parse_from_str(&hike.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let end = date.succ_opt().unwrap_or(date);
+
+        let units = units::get();
+        let mut description = units.distance(hike.hiked_km(), 1);
+        if let Some(minutes) = hike.duration_minutes {
+            description.push_str(&format!(", {}h{:02}", minutes / 60, minutes % 60));
+        }
+        if let Some(gain) = hike.elevation_gain {
+            description.push_str(&format!(", {} gain", units.elevation(gain)));
+        }
+        if let Some(notes) = &hike.notes {
+            description.push_str(&format!("\n{}", notes));
+        }
+
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:hike-{}@rando-qc", hike.id),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+            format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+            format!(
+                "SUMMARY:{}",
+                ics_text(&format!("Hike: {}", hike.trail_name))
+            ),
+            format!("LOCATION:{}", ics_text(&hike.park)),
+            format!("DESCRIPTION:{}", ics_text(&description)),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| fold_ics_line(line))
+        .collect::<Vec<_>>()
+        .join("\r\n")
+        + "\r\n"
+}
+
+fn ics_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Split lines longer than 75 bytes, continuing with a leading space (RFC 5545)
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > ICS_LINE_LIMIT {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::logs::TrailCondition;
+    use crate::trails::Difficulty;
+
+    fn hike() -> HikeLog {
+        HikeLog {
+            id: 7,
+            trail_name: "Les Loups".to_string(),
+            park: "Parc national de la Jacques-Cartier".to_string(),
+            date: "2025-09-14".to_string(),
+            duration_minutes: Some(185),
+            distance_km: 11.5,
+            notes: Some("Fog at the top, then \"clear\",\nwindy at the lookout".to_string()),
+            elevation_gain: Some(640.0),
+            other_trails: vec![
+                TrailRef {
+                    name: "La Croche".to_string(),
+                    park: "Parc national de la Jacques-Cartier".to_string(),
+                },
+                TrailRef {
+                    name: "Le Draveur".to_string(),
+                    park: "Parc national des Grands-Jardins".to_string(),
+                },
+            ],
+            avg_heart_rate: Some(128),
+            max_heart_rate: Some(171),
+            calories: Some(1450),
+            track_file: Some("2025-09-14_les_loups.gpx".to_string()),
+            weather: Some(WeatherSnapshot {
+                min_temp: 4.5,
+                max_temp: 16.0,
+                precipitation: 1.5,
+                wind_speed: 25.0,
+                weather_code: 3,
+            }),
+            details: HikeDetails {
+                start_time: Some("08:30".to_string()),
+                companions: vec!["Marie".to_string(), "Jean, Luc".to_string()],
+                rating: Some(4),
+                perceived_difficulty: Some(Difficulty::Intermediaire),
+                conditions: vec![TrailCondition::Mud, TrailCondition::Wind],
+                wildlife: vec!["orignal".to_string()],
+                photos: vec!["/home/me/photos/sommet.jpg".to_string()],
+                actual_distance_km: Some(12.25),
+            },
+        }
+    }
+
+    #[test]
+    fn csv_round_trips_every_field() {
+        let bare = HikeLog {
+            id: 8,
+            date: "2025-09-20".to_string(),
+            duration_minutes: None,
+            notes: None,
+            elevation_gain: None,
+            other_trails: Vec::new(),
+            avg_heart_rate: None,
+            max_heart_rate: None,
+            calories: None,
+            track_file: None,
+            weather: None,
+            details: HikeDetails::default(),
+            ..hike()
+        };
+        let hikes = [hike(), bare];
+
+        let csv = to_csv(&hikes.iter().collect::<Vec<_>>());
+        assert!(csv.contains("La Croche; Le Draveur | Parc national des Grands-Jardins"));
+        let restored = from_csv(&csv).unwrap();
+
+        assert_eq!(restored.len(), 2);
+        for (original, restored) in hikes.iter().zip(&restored) {
+            assert_eq!(
+                serde_json::to_value(original).unwrap(),
+                serde_json::to_value(restored).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn csv_import_takes_columns_in_any_order() {
+        let csv = "\u{feff}Park,notes,date,Trail\r\n\r\n\"Parc A\",\"a, b\r\nc\",2025-06-01,Les Loups\r\n";
+        let hikes = from_csv(csv).unwrap();
+        assert_eq!(hikes.len(), 1);
+        assert_eq!(hikes[0].park, "Parc A");
+        assert_eq!(hikes[0].notes.as_deref(), Some("a, b\r\nc"));
+        assert_eq!(hikes[0].distance_km, 0.0);
+
+        assert!(from_csv("date,trail\n2025-06-01,Les Loups").is_err());
+        assert!(from_csv("date,trail,park\n2025-06-01,Les Loups,\"Parc A").is_err());
+        assert!(from_csv("date,trail,park,rating\n2025-06-01,Les Loups,Parc A,five").is_err());
+    }
+
+    #[test]
+    fn ics_lines_fold_between_characters() {
+        let line = format!("DESCRIPTION:{}", "é".repeat(80));
+        let folded = fold_ics_line(&line);
+
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(parts.len(), 3);
+        assert!(parts.iter().all(|p| p.len() <= ICS_LINE_LIMIT));
+        // A two-byte character that would end at byte 76 moves to the next line
+        assert_eq!(parts[0].len(), 74);
+        assert!(parts[1..].iter().all(|p| p.starts_with(' ')));
+        let unfolded: String = folded.replace("\r\n ", "");
+        assert_eq!(unfolded, line);
+
+        assert_eq!(fold_ics_line("SUMMARY:Hike"), "SUMMARY:Hike");
+    }
+
+    #[test]
+    fn ics_escapes_text_values() {
+        let ics = to_ics(&[&hike()]);
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250914\r\n"));
+        assert!(ics.contains("DTEND;VALUE=DATE:20250915\r\n"));
+        let description: String = ics
+            .split("DESCRIPTION:")
+            .nth(1)
+            .unwrap()
+            .split("\r\nEND:VEVENT")
+            .next()
+            .unwrap()
+            .replace("\r\n ", "");
+        assert!(description.starts_with("12.2km\\, 3h05\\, 640m gain\\nFog"));
+    }
+}
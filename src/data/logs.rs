@@ -1,35 +1,428 @@
-use crate::trails::Trail;
+use crate::services::weather::{DayWeather, get_day_weather};
+use crate::trails::{Difficulty, Trail};
 use anyhow::{Context, Result};
-use chrono::Local;
+use chrono::{Local, NaiveDate, NaiveTime};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Longest duration accepted for a single hike, in minutes
+const MAX_DURATION_MINUTES: u32 = 72 * 60;
+
+/// Precipitation (mm) from which a hike counts as a rainy one
+const WET_DAY_MM: f64 = 1.0;
+
+/// Layout version of `logs.json` written by this build
+pub const SCHEMA_VERSION: u32 = 4;
+
+/// Upgrade steps, `MIGRATIONS[n]` taking a file from version n to n + 1
+const MIGRATIONS: [fn(&mut Value); SCHEMA_VERSION as usize] = [
+    migrate_ids,
+    migrate_details,
+    migrate_other_trails,
+    migrate_weather,
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HikeLog {
+    /// Stable identifier, never reused after a delete (0 in files written before IDs)
+    #[serde(default)]
+    pub id: u32,
     pub trail_name: String,
     pub park: String,
     pub date: String, // YYYY-MM-DD
     pub duration_minutes: Option<u32>,
     pub distance_km: f64,
     pub notes: Option<String>,
+    /// Measured from an imported track
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elevation_gain: Option<f64>,
+    /// Other catalog trails covered by an imported track
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub other_trails: Vec<TrailRef>,
+    /// Heart rate (bpm) and energy from an imported watch recording
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_heart_rate: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_heart_rate: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calories: Option<u32>,
+    /// File name of the imported track in the tracks directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_file: Option<String>,
+    /// Weather at the trailhead that day, fetched when the hike is logged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weather: Option<WeatherSnapshot>,
+    #[serde(flatten)]
+    pub details: HikeDetails,
+}
+
+impl HikeLog {
+    /// Distance actually walked: the recorded one when it differs from the trail
+    pub fn hiked_km(&self) -> f64 {
+        self.details.actual_distance_km.unwrap_or(self.distance_km)
+    }
+
+    /// The catalog trail this hike was logged on, by exact name and park
+    pub fn trail<'a>(&self, trails: &'a [Trail]) -> Option<&'a Trail> {
+        trails
+            .iter()
+            .find(|t| t.name == self.trail_name && t.park == self.park)
+    }
+
+    /// Elevation gain from the imported track, or else from the trail's cached
+    /// profile when the whole trail was hiked
+    pub fn gain(&self, trails: &[Trail]) -> Option<f64> {
+        self.elevation_gain.or_else(|| {
+            self.trail(trails)
+                .filter(|_| self.details.actual_distance_km.is_none())
+                .and_then(|t| t.elevation)
+                .map(|e| e.gain)
+        })
+    }
+}
+
+/// A catalog trail by name and park, since names repeat across parks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrailRef {
+    pub name: String,
+    pub park: String,
+}
+
+impl TrailRef {
+    pub fn of(trail: &Trail) -> Self {
+        Self {
+            name: trail.name.clone(),
+            park: trail.park.clone(),
+        }
+    }
+}
+
+/// Daily weather stored with a hike
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WeatherSnapshot {
+    pub min_temp: f64,
+    pub max_temp: f64,
+    /// Precipitation in mm
+    pub precipitation: f64,
+    /// Strongest wind in km/h
+    pub wind_speed: f64,
+    pub weather_code: u32,
+}
+
+impl WeatherSnapshot {
+    pub fn is_wet(&self) -> bool {
+        self.precipitation >= WET_DAY_MM
+    }
+
+    pub fn mean_temp(&self) -> f64 {
+        (self.min_temp + self.max_temp) / 2.0
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+impl From<DayWeather> for WeatherSnapshot {
+    fn from(day: DayWeather) -> Self {
+        Self {
+            min_temp: day.min_temp,
+            max_temp: day.max_temp,
+            precipitation: day.precipitation,
+            wind_speed: day.wind_speed,
+            weather_code: day.weather_code,
+        }
+    }
+}
+
+/// Weather of the hike's day at the trailhead; None when it can't be fetched
+/// (offline, invalid date), since the log shouldn't depend on it
+pub fn fetch_weather(trail: &Trail, date: &str) -> Option<WeatherSnapshot> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    get_day_weather(trail.lat, trail.lng, date)
+        .ok()
+        .map(WeatherSnapshot::from)
+}
+
+/// Optional details entered with `rando log` flags or its prompt
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HikeDetails {
+    /// Local start time, HH:MM
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub companions: Vec<String>,
+    /// 1 to 5 stars
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<u8>,
+    /// How hard it felt, on the catalog's scale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub perceived_difficulty: Option<Difficulty>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<TrailCondition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wildlife: Vec<String>,
+    /// Absolute paths of photos taken on the hike
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub photos: Vec<String>,
+    /// Distance walked when it differs from the trail length (detours, turning back)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual_distance_km: Option<f64>,
+}
+
+impl HikeDetails {
+    pub fn is_empty(&self) -> bool {
+        self.start_time.is_none()
+            && self.companions.is_empty()
+            && self.rating.is_none()
+            && self.perceived_difficulty.is_none()
+            && self.conditions.is_empty()
+            && self.wildlife.is_empty()
+            && self.photos.is_empty()
+            && self.actual_distance_km.is_none()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(time) = &self.start_time {
+            NaiveTime::parse_from_str(time, "%H:%M")
+                .with_context(|| format!("Invalid start time: '{}'. Use HH:MM", time))?;
+        }
+        if let Some(rating) = self.rating
+            && !(1..=5).contains(&rating)
+        {
+            anyhow::bail!("Invalid rating: {}. Use 1 to 5", rating);
+        }
+        if let Some(km) = self.actual_distance_km
+            && !(km > 0.0 && km.is_finite())
+        {
+            anyhow::bail!("Invalid distance: {} km", km);
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self, field: DetailField) {
+        match field {
+            DetailField::Start => self.start_time = None,
+            DetailField::Companions => self.companions.clear(),
+            DetailField::Rating => self.rating = None,
+            DetailField::Felt => self.perceived_difficulty = None,
+            DetailField::Conditions => self.conditions.clear(),
+            DetailField::Wildlife => self.wildlife.clear(),
+            DetailField::Photos => self.photos.clear(),
+            DetailField::Distance => self.actual_distance_km = None,
+        }
+    }
+
+    /// Overwrite with the fields set in `other`; lists are replaced, not extended
+    pub fn merge(&mut self, other: HikeDetails) {
+        fn replace<T>(list: &mut Vec<T>, new: Vec<T>) {
+            if !new.is_empty() {
+                *list = new;
+            }
+        }
+        self.start_time = other.start_time.or(self.start_time.take());
+        self.rating = other.rating.or(self.rating);
+        self.perceived_difficulty = other.perceived_difficulty.or(self.perceived_difficulty);
+        self.actual_distance_km = other.actual_distance_km.or(self.actual_distance_km);
+        replace(&mut self.companions, other.companions);
+        replace(&mut self.conditions, other.conditions);
+        replace(&mut self.wildlife, other.wildlife);
+        replace(&mut self.photos, other.photos);
+    }
+}
+
+/// A detail `rando log edit --clear` can remove, named after its flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetailField {
+    Start,
+    Companions,
+    Rating,
+    Felt,
+    Conditions,
+    Wildlife,
+    Photos,
+    Distance,
+}
+
+impl DetailField {
+    pub const ALL: [DetailField; 8] = [
+        DetailField::Start,
+        DetailField::Companions,
+        DetailField::Rating,
+        DetailField::Felt,
+        DetailField::Conditions,
+        DetailField::Wildlife,
+        DetailField::Photos,
+        DetailField::Distance,
+    ];
+}
+
+impl FromStr for DetailField {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "start" => Ok(DetailField::Start),
+            "with" | "companions" => Ok(DetailField::Companions),
+            "rating" => Ok(DetailField::Rating),
+            "felt" => Ok(DetailField::Felt),
+            "conditions" => Ok(DetailField::Conditions),
+            "wildlife" => Ok(DetailField::Wildlife),
+            "photo" | "photos" => Ok(DetailField::Photos),
+            "distance" => Ok(DetailField::Distance),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for DetailField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DetailField::Start => "start",
+            DetailField::Companions => "with",
+            DetailField::Rating => "rating",
+            DetailField::Felt => "felt",
+            DetailField::Conditions => "conditions",
+            DetailField::Wildlife => "wildlife",
+            DetailField::Photos => "photo",
+            DetailField::Distance => "distance",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Something on the trail worth remembering next time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrailCondition {
+    Mud,
+    Ice,
+    Snow,
+    Water,
+    Bugs,
+    Deadfall,
+    Wind,
+}
+
+impl TrailCondition {
+    pub const ALL: [TrailCondition; 7] = [
+        TrailCondition::Mud,
+        TrailCondition::Ice,
+        TrailCondition::Snow,
+        TrailCondition::Water,
+        TrailCondition::Bugs,
+        TrailCondition::Deadfall,
+        TrailCondition::Wind,
+    ];
+}
+
+impl FromStr for TrailCondition {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "mud" | "muddy" => Ok(TrailCondition::Mud),
+            "ice" | "icy" => Ok(TrailCondition::Ice),
+            "snow" => Ok(TrailCondition::Snow),
+            "water" | "flooded" => Ok(TrailCondition::Water),
+            "bugs" | "mosquitoes" => Ok(TrailCondition::Bugs),
+            "deadfall" | "blowdown" => Ok(TrailCondition::Deadfall),
+            "wind" | "windy" => Ok(TrailCondition::Wind),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for TrailCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TrailCondition::Mud => "mud",
+            TrailCondition::Ice => "ice",
+            TrailCondition::Snow => "snow",
+            TrailCondition::Water => "water",
+            TrailCondition::Bugs => "bugs",
+            TrailCondition::Deadfall => "deadfall",
+            TrailCondition::Wind => "wind",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LogsData {
+    /// Layout version; files from before versioning are 0
+    #[serde(default)]
+    pub version: u32,
     pub hikes: Vec<HikeLog>,
+    /// ID given to the next logged hike
+    #[serde(default)]
+    pub next_id: u32,
 }
 
-fn get_logs_path() -> Result<PathBuf> {
+impl Default for LogsData {
+    fn default() -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            hikes: Vec::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl LogsData {
+    pub fn find(&self, id: u32) -> Option<&HikeLog> {
+        self.hikes.iter().find(|h| h.id == id)
+    }
+
+    /// Hikes within an inclusive YYYY-MM-DD range, oldest first
+    pub fn between(&self, from: Option<&str>, to: Option<&str>) -> Vec<&HikeLog> {
+        // YYYY-MM-DD strings sort chronologically
+        let mut hikes: Vec<&HikeLog> = self
+            .hikes
+            .iter()
+            .filter(|h| from.is_none_or(|f| h.date.as_str() >= f))
+            .filter(|h| to.is_none_or(|t| h.date.as_str() <= t))
+            .collect();
+        hikes.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+        hikes
+    }
+}
+
+/// Fields changed by `rando log edit`; None leaves a field as is
+#[derive(Debug, Default)]
+pub struct HikeUpdate {
+    pub date: Option<String>,
+    pub duration_minutes: Option<u32>,
+    /// Some("") clears the notes
+    pub notes: Option<String>,
+    pub details: HikeDetails,
+    /// Details removed before the new ones are applied
+    pub clear: Vec<DetailField>,
+}
+
+fn get_data_dir() -> Result<PathBuf> {
     let data_dir = dirs::data_local_dir()
         .or_else(|| dirs::home_dir().map(|h| h.join(".local/share")))
         .context("Could not find data directory")?
         .join("rando-qc");
 
     fs::create_dir_all(&data_dir).context("Failed to create data directory")?;
-    Ok(data_dir.join("logs.json"))
+    Ok(data_dir)
+}
+
+fn get_logs_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("logs.json"))
 }
 
+/// Where imported tracks are kept, next to `logs.json`
+pub fn get_tracks_dir() -> Result<PathBuf> {
+    let dir = get_data_dir()?.join("tracks");
+    fs::create_dir_all(&dir).context("Failed to create tracks directory")?;
+    Ok(dir)
+}
+
+/// Read `logs.json`, upgrading files written by older versions. The original
+/// is kept as `logs.v<N>.json.bak` before the upgraded file replaces it
 pub fn load_logs() -> Result<LogsData> {
     let path = get_logs_path()?;
     if !path.exists() {
@@ -37,13 +430,105 @@ pub fn load_logs() -> Result<LogsData> {
     }
 
     let data = fs::read_to_string(&path).context("Failed to read logs file")?;
-    serde_json::from_str(&data).context("Failed to parse logs JSON")
+    let mut value: Value = serde_json::from_str(&data).context("Failed to parse logs JSON")?;
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    if version > SCHEMA_VERSION {
+        anyhow::bail!(
+            "logs.json uses schema version {}, newer than this rando ({}). Update rando to read it",
+            version,
+            SCHEMA_VERSION
+        );
+    }
+
+    for migrate in &MIGRATIONS[version as usize..] {
+        migrate(&mut value);
+    }
+    value["version"] = SCHEMA_VERSION.into();
+    let logs: LogsData = serde_json::from_value(value).context("Failed to parse logs JSON")?;
+
+    if version < SCHEMA_VERSION {
+        let backup = path.with_file_name(format!("logs.v{}.json.bak", version));
+        fs::copy(&path, &backup).context("Failed to back up logs file")?;
+        save_logs(&logs)?;
+    }
+    Ok(logs)
+}
+
+/// v0 → v1: number hikes logged before IDs existed, in file order
+fn migrate_ids(logs: &mut Value) {
+    let Some(hikes) = logs.get_mut("hikes").and_then(Value::as_array_mut) else {
+        return;
+    };
+    let id_of = |hike: &Value| hike.get("id").and_then(Value::as_u64).unwrap_or(0);
+    let mut next_id = hikes.iter().map(id_of).max().unwrap_or(0) + 1;
+    for hike in hikes.iter_mut().filter(|h| id_of(h) == 0) {
+        hike["id"] = next_id.into();
+        next_id += 1;
+    }
+    let stored = logs.get("next_id").and_then(Value::as_u64).unwrap_or(0);
+    logs["next_id"] = stored.max(next_id).into();
 }
 
+/// v1 → v2: structured details (companions, rating, conditions...). They are
+/// all optional, so existing entries stay as they are
+fn migrate_details(_logs: &mut Value) {}
+
+/// v2 → v3: `other_trails` entries name their park. Older entries were bare
+/// names from the hike's own park
+fn migrate_other_trails(logs: &mut Value) {
+    let Some(hikes) = logs.get_mut("hikes").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for hike in hikes {
+        let park = hike.get("park").cloned().unwrap_or_default();
+        let Some(others) = hike.get_mut("other_trails").and_then(Value::as_array_mut) else {
+            continue;
+        };
+        for other in others.iter_mut().filter(|o| o.is_string()) {
+            *other = serde_json::json!({ "name": other.take(), "park": park });
+        }
+    }
+}
+
+/// v3 → v4: weather snapshots. Optional, so existing entries stay as they are;
+/// `rando log weather` fills them in
+fn migrate_weather(_logs: &mut Value) {}
+
+/// Write to a temporary file and rename it over `logs.json`, so a crash
+/// mid-write never leaves a truncated log
 pub fn save_logs(logs: &LogsData) -> Result<()> {
     let path = get_logs_path()?;
     let data = serde_json::to_string_pretty(logs)?;
-    fs::write(&path, data).context("Failed to write logs file")
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).context("Failed to write logs file")?;
+    fs::rename(&tmp, &path).context("Failed to replace logs file")
+}
+
+/// A YYYY-MM-DD date, normalized so it compares with logged dates
+pub fn parse_date(date: &str) -> Result<String> {
+    let parsed = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date: '{}'. Use YYYY-MM-DD", date))?;
+    Ok(parsed.format("%Y-%m-%d").to_string())
+}
+
+/// A hike date: valid and not in the future
+pub fn validate_date(date: &str) -> Result<()> {
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    if parse_date(date)? > today {
+        anyhow::bail!("Date {} is in the future", date);
+    }
+    Ok(())
+}
+
+pub fn validate_duration(minutes: u32) -> Result<()> {
+    if minutes == 0 || minutes > MAX_DURATION_MINUTES {
+        anyhow::bail!(
+            "Invalid duration: {} minutes. Use e.g. 4h30m, up to {}h",
+            minutes,
+            MAX_DURATION_MINUTES / 60
+        );
+    }
+    Ok(())
 }
 
 pub fn add_hike(
@@ -51,24 +536,175 @@ pub fn add_hike(
     date: Option<String>,
     duration_minutes: Option<u32>,
     notes: Option<String>,
-) -> Result<()> {
-    let mut logs = load_logs()?;
-
+    details: HikeDetails,
+) -> Result<HikeLog> {
     let date_str = date.unwrap_or_else(|| Local::now().format("%Y-%m-%d").to_string());
+    validate_date(&date_str)?;
+    let weather = fetch_weather(trail, &date_str);
 
     let hike = HikeLog {
+        id: 0,
         trail_name: trail.name.clone(),
         park: trail.park.clone(),
         date: date_str,
         duration_minutes,
         distance_km: trail.length_km,
         notes,
+        elevation_gain: None,
+        other_trails: Vec::new(),
+        avg_heart_rate: None,
+        max_heart_rate: None,
+        calories: None,
+        track_file: None,
+        weather,
+        details,
     };
 
-    logs.hikes.push(hike);
+    add_hike_log(hike, None)
+}
+
+/// Validate and save a new entry under the next ID, copying `track` into
+/// the tracks directory when given
+pub fn add_hike_log(mut hike: HikeLog, track: Option<&Path>) -> Result<HikeLog> {
+    let mut logs = load_logs()?;
+
+    validate_date(&hike.date)?;
+    if let Some(minutes) = hike.duration_minutes {
+        validate_duration(minutes)?;
+    }
+    hike.details.validate()?;
+
+    hike.id = logs.next_id;
+    if let Some(source) = track {
+        let extension = source
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("gpx")
+            .to_lowercase();
+        let file_name = format!("{}.{}", hike.id, extension);
+        fs::copy(source, get_tracks_dir()?.join(&file_name))
+            .with_context(|| format!("Failed to store track {}", source.display()))?;
+        hike.track_file = Some(file_name);
+    }
+
+    logs.next_id += 1;
+    logs.hikes.push(hike.clone());
+    save_logs(&logs)?;
+    Ok(hike)
+}
+
+/// Add hikes from an export in one save. Entries already logged (same date,
+/// trail and park) are skipped; IDs are kept unless taken. Returns (added, skipped)
+pub fn import_hikes(hikes: Vec<HikeLog>) -> Result<(usize, usize)> {
+    let mut logs = load_logs()?;
+    let (mut added, mut skipped) = (0, 0);
+
+    for (i, mut hike) in hikes.into_iter().enumerate() {
+        validate_date(&hike.date).map_err(|e| anyhow::anyhow!("Entry {}: {}", i + 1, e))?;
+        if let Some(minutes) = hike.duration_minutes {
+            validate_duration(minutes).map_err(|e| anyhow::anyhow!("Entry {}: {}", i + 1, e))?;
+        }
+        hike.details
+            .validate()
+            .map_err(|e| anyhow::anyhow!("Entry {}: {}", i + 1, e))?;
+
+        let duplicate = logs
+            .hikes
+            .iter()
+            .any(|h| h.date == hike.date && h.trail_name == hike.trail_name && h.park == hike.park);
+        if duplicate {
+            skipped += 1;
+            continue;
+        }
+
+        if hike.id == 0 || logs.find(hike.id).is_some() {
+            hike.id = logs.next_id;
+        }
+        logs.next_id = logs.next_id.max(hike.id + 1);
+        // Track files aren't part of the export
+        if let Some(file) = &hike.track_file
+            && !get_tracks_dir()?.join(file).exists()
+        {
+            hike.track_file = None;
+        }
+        logs.hikes.push(hike);
+        added += 1;
+    }
+
+    save_logs(&logs)?;
+    Ok((added, skipped))
+}
+
+/// Apply an edit to the hike with this ID and save; returns the updated entry
+pub fn update_hike(id: u32, update: HikeUpdate) -> Result<HikeLog> {
+    let mut logs = load_logs()?;
+    let hike = logs
+        .hikes
+        .iter_mut()
+        .find(|h| h.id == id)
+        .ok_or_else(|| anyhow::anyhow!("No logged hike with ID {}", id))?;
+
+    if let Some(date) = update.date {
+        validate_date(&date)?;
+        if date != hike.date {
+            // Belongs to the old date; see `set_weather`
+            hike.weather = None;
+        }
+        hike.date = date;
+    }
+    if let Some(minutes) = update.duration_minutes {
+        validate_duration(minutes)?;
+        hike.duration_minutes = Some(minutes);
+    }
+    if let Some(notes) = update.notes {
+        hike.notes = Some(notes).filter(|n| !n.trim().is_empty());
+    }
+    update.details.validate()?;
+    for field in update.clear {
+        hike.details.clear(field);
+    }
+    hike.details.merge(update.details);
+
+    let updated = hike.clone();
+    save_logs(&logs)?;
+    Ok(updated)
+}
+
+/// Store fetched weather on hikes by ID, in one save
+pub fn set_weather(snapshots: Vec<(u32, WeatherSnapshot)>) -> Result<()> {
+    if snapshots.is_empty() {
+        return Ok(());
+    }
+    let mut logs = load_logs()?;
+    for (id, snapshot) in snapshots {
+        if let Some(hike) = logs.hikes.iter_mut().find(|h| h.id == id) {
+            hike.weather = Some(snapshot);
+        }
+    }
     save_logs(&logs)
 }
 
+/// Remove the hike with this ID and save; returns the removed entry
+pub fn delete_hike(id: u32) -> Result<HikeLog> {
+    let mut logs = load_logs()?;
+    let index = logs
+        .hikes
+        .iter()
+        .position(|h| h.id == id)
+        .ok_or_else(|| anyhow::anyhow!("No logged hike with ID {}", id))?;
+
+    let removed = logs.hikes.remove(index);
+    save_logs(&logs)?;
+
+    // The log is the record; a leftover track file is harmless
+    if let Some(file_name) = &removed.track_file
+        && let Ok(dir) = get_tracks_dir()
+    {
+        let _ = fs::remove_file(dir.join(file_name));
+    }
+    Ok(removed)
+}
+
 pub fn parse_duration(time_str: &str) -> Result<u32> {
     let mut total_minutes = 0u32;
     let mut current_num = String::new();
@@ -93,3 +729,44 @@ pub fn parse_duration(time_str: &str) -> Result<u32> {
 
     Ok(total_minutes)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn older_files_get_parks_on_other_trails() {
+        let mut value = json!({
+            "version": 2,
+            "next_id": 3,
+            "hikes": [
+                {"id": 1, "trail_name": "Les Loups", "park": "Jacques-Cartier",
+                 "date": "2024-07-01", "duration_minutes": null, "distance_km": 11.0,
+                 "notes": null, "other_trails": ["L'Éperon", "La Croix"]},
+                {"id": 2, "trail_name": "La Chute", "park": "Mont-Orford",
+                 "date": "2024-07-02", "duration_minutes": 90, "distance_km": 4.0,
+                 "notes": null}
+            ]
+        });
+        for migrate in &MIGRATIONS[2..] {
+            migrate(&mut value);
+        }
+
+        let logs: LogsData = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            logs.hikes[0].other_trails,
+            vec![
+                TrailRef {
+                    name: "L'Éperon".to_string(),
+                    park: "Jacques-Cartier".to_string()
+                },
+                TrailRef {
+                    name: "La Croix".to_string(),
+                    park: "Jacques-Cartier".to_string()
+                },
+            ]
+        );
+        assert!(logs.hikes[1].other_trails.is_empty());
+    }
+}
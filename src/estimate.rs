@@ -0,0 +1,399 @@
+use crate::config::{Config, load_config};
+use crate::pace::{PersonalPace, load_personal_pace};
+use crate::profile::{ElevationProfile, get_trail_profile};
+use crate::scoring::Confidence;
+use crate::services::elevation::calculate_elevation_stats;
+use crate::trails::Trail;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Naismith: 5 km/h on the flat plus 1h per 600m of ascent
+pub const NAISMITH_SPEED: f64 = 5.0;
+pub const NAISMITH_CLIMB_RATE: f64 = 600.0;
+
+/// Langmuir: per 300m of descent, -10 min between 5° and 12°, +10 min steeper than 12°
+const LANGMUIR_DESCENT_STEP: f64 = 300.0;
+const LANGMUIR_ADJUSTMENT_HOURS: f64 = 10.0 / 60.0;
+const LANGMUIR_GENTLE_DEG: f64 = 5.0;
+const LANGMUIR_STEEP_DEG: f64 = 12.0;
+
+/// Spread of the range around the estimate; people run late more often than early
+const RANGE_LOW: f64 = 0.85;
+const RANGE_HIGH: f64 = 1.25;
+
+/// Tighter or wider spread for a personal pace, by how many hikes back it
+const PERSONAL_RANGES: [(Confidence, f64, f64); 3] = [
+    (Confidence::High, 0.9, 1.15),
+    (Confidence::Medium, 0.85, 1.25),
+    (Confidence::Low, 0.8, 1.35),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeModel {
+    Naismith,
+    #[default]
+    Tobler,
+    Langmuir,
+}
+
+impl FromStr for TimeModel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "naismith" => Ok(TimeModel::Naismith),
+            "tobler" => Ok(TimeModel::Tobler),
+            "langmuir" => Ok(TimeModel::Langmuir),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TimeModel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimeModel::Naismith => "Naismith",
+            TimeModel::Tobler => "Tobler",
+            TimeModel::Langmuir => "Langmuir",
+        }
+    }
+}
+
+/// Estimated moving time for a hike
+#[derive(Debug, Clone, Copy)]
+pub struct TimeEstimate {
+    pub hours: f64,
+    pub low: f64,
+    pub high: f64,
+    pub model: TimeModel,
+    pub pace: f64,
+    /// False when no elevation profile was available and the trail was treated as flat
+    pub terrain: bool,
+    /// Set when the time comes from the pace calibrated on logged hikes
+    pub personal: Option<PersonalPace>,
+}
+
+impl TimeEstimate {
+    /// e.g. "2h50-4h10"
+    pub fn range(&self) -> String {
+        format!("{}-{}", format_hours(self.low), format_hours(self.high))
+    }
+
+    /// e.g. "Tobler ×1.2", "Naismith, no elevation data" or
+    /// "personal pace, 6 hikes, medium confidence"
+    pub fn describe(&self) -> String {
+        let mut text = match self.personal {
+            Some(personal) => format!(
+                "personal pace, {} hike{}, {} confidence",
+                personal.hikes,
+                if personal.hikes == 1 { "" } else { "s" },
+                personal.confidence().label()
+            ),
+            None => self.model.label().to_string(),
+        };
+        if self.personal.is_none() && (self.pace - 1.0).abs() > f64::EPSILON {
+            text.push_str(&format!(" ×{}", crate::units::get().number(self.pace, 2)));
+        }
+        if !self.terrain {
+            text.push_str(", no elevation data");
+        }
+        text
+    }
+}
+
+/// Hours as "3h05"
+pub fn format_hours(hours: f64) -> String {
+    let minutes = (hours * 60.0).round().max(0.0) as u32;
+    format!("{}h{:02}", minutes / 60, minutes % 60)
+}
+
+/// Hiking time model with the user's pace multiplier
+#[derive(Debug, Clone, Copy)]
+pub struct Estimator {
+    pub model: TimeModel,
+    /// Multiplier on the model's time: 1.2 is 20% slower
+    pub pace: f64,
+    /// Calibrated pace, used instead of the model when set
+    pub personal: Option<PersonalPace>,
+}
+
+impl Estimator {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            model: config.time_model,
+            pace: config.pace.filter(|p| *p > 0.0).unwrap_or(1.0),
+            personal: None,
+        }
+    }
+
+    /// Like `from_config`, with the pace calibrated from logged hikes unless
+    /// the config sets a fixed `pace`
+    pub fn for_user(config: &Config) -> Self {
+        let mut estimator = Self::from_config(config);
+        if config.pace.is_none() {
+            // A broken log file shouldn't stop the estimate
+            estimator.personal = load_personal_pace(config.time_model).ok().flatten();
+        }
+        estimator
+    }
+
+    pub fn estimate(&self, length_km: f64, profile: Option<&ElevationProfile>) -> TimeEstimate {
+        let profile = profile.filter(|p| p.points.len() >= 2);
+        if let Some(personal) = self.personal {
+            let gain = profile.map(profile_gain).unwrap_or(0.0);
+            let hours = personal.hours(length_km, gain);
+            let (_, low, high) = PERSONAL_RANGES
+                .iter()
+                .find(|(c, _, _)| *c == personal.confidence())
+                .copied()
+                .unwrap_or((Confidence::Medium, RANGE_LOW, RANGE_HIGH));
+            return TimeEstimate {
+                hours,
+                low: hours * low,
+                high: hours * high,
+                model: self.model,
+                pace: 1.0,
+                terrain: profile.is_some(),
+                personal: Some(personal),
+            };
+        }
+
+        let hours = match profile {
+            Some(profile) => match self.model {
+                TimeModel::Naismith => naismith(length_km, profile),
+                TimeModel::Tobler => tobler(profile),
+                TimeModel::Langmuir => naismith(length_km, profile) + langmuir_correction(profile),
+            },
+            None => flat_hours(self.model, length_km),
+        } * self.pace;
+
+        TimeEstimate {
+            hours,
+            low: hours * RANGE_LOW,
+            high: hours * RANGE_HIGH,
+            model: self.model,
+            pace: self.pace,
+            terrain: profile.is_some(),
+            personal: None,
+        }
+    }
+}
+
+/// Time on level ground at the model's own speed, for trails without elevation data
+fn flat_hours(model: TimeModel, length_km: f64) -> f64 {
+    match model {
+        TimeModel::Naismith | TimeModel::Langmuir => length_km / NAISMITH_SPEED,
+        TimeModel::Tobler => length_km / tobler_speed(0.0),
+    }
+}
+
+/// Smoothed total ascent of a profile in metres
+pub fn profile_gain(profile: &ElevationProfile) -> f64 {
+    calculate_elevation_stats(&profile.elevations(), profile.length_km()).total_gain
+}
+
+fn naismith(length_km: f64, profile: &ElevationProfile) -> f64 {
+    length_km / NAISMITH_SPEED + profile_gain(profile) / NAISMITH_CLIMB_RATE
+}
+
+/// Walking speed (km/h) on a grade (%) by Tobler's hiking function
+pub fn tobler_speed(grade: f64) -> f64 {
+    6.0 * (-3.5 * (grade / 100.0 + 0.05).abs()).exp()
+}
+
+fn tobler(profile: &ElevationProfile) -> f64 {
+    profile
+        .smoothed()
+        .segments()
+        .iter()
+        .map(|s| s.length_km() / tobler_speed(s.grade()))
+        .sum()
+}
+
+fn langmuir_correction(profile: &ElevationProfile) -> f64 {
+    profile
+        .smoothed()
+        .segments()
+        .iter()
+        .filter(|s| s.rise < 0.0)
+        .map(|s| {
+            let angle = s.grade().abs().atan2(100.0).to_degrees();
+            let steps = -s.rise / LANGMUIR_DESCENT_STEP;
+            if angle > LANGMUIR_STEEP_DEG {
+                steps * LANGMUIR_ADJUSTMENT_HOURS
+            } else if angle >= LANGMUIR_GENTLE_DEG {
+                -steps * LANGMUIR_ADJUSTMENT_HOURS
+            } else {
+                0.0
+            }
+        })
+        .sum()
+}
+
+/// Estimate for a trail with the user's pace, using its elevation profile when available
+pub fn estimate_trail(trail: &Trail) -> Result<TimeEstimate> {
+    let config = load_config()?;
+    let profile = get_trail_profile(trail).ok();
+    Ok(Estimator::for_user(&config).estimate(trail.length_km, profile.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::ProfilePoint;
+
+    /// From (distance km, elevation m) pairs, spaced widely enough that smoothing
+    /// leaves them as they are
+    fn profile(points: &[(f64, f64)]) -> ElevationProfile {
+        ElevationProfile {
+            points: points
+                .iter()
+                .map(|&(distance_km, elevation)| ProfilePoint {
+                    distance_km,
+                    elevation,
+                })
+                .collect(),
+        }
+    }
+
+    /// 3 km: 2 km climbing 200 m at 10%, then 1 km flat
+    fn climb() -> ElevationProfile {
+        profile(&[(0.0, 100.0), (1.0, 200.0), (2.0, 300.0), (3.0, 300.0)])
+    }
+
+    fn estimator(model: TimeModel) -> Estimator {
+        Estimator {
+            model,
+            pace: 1.0,
+            personal: None,
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn models_weigh_the_climb_their_own_way() {
+        let climb = climb();
+        let naismith = estimator(TimeModel::Naismith).estimate(3.0, Some(&climb));
+        assert!(close(naismith.hours, 3.0 / 5.0 + 200.0 / 600.0));
+        assert!(naismith.terrain);
+        assert_eq!(naismith.describe(), "Naismith");
+
+        let tobler = estimator(TimeModel::Tobler).estimate(3.0, Some(&climb));
+        assert!(close(
+            tobler.hours,
+            2.0 / tobler_speed(10.0) + 1.0 / tobler_speed(0.0)
+        ));
+        // Fastest slightly downhill, 6 km/h at -5%
+        assert!(close(tobler_speed(-5.0), 6.0));
+        assert!(tobler_speed(0.0) > tobler_speed(10.0));
+
+        // No descent, nothing to correct
+        let langmuir = estimator(TimeModel::Langmuir).estimate(3.0, Some(&climb));
+        assert!(close(langmuir.hours, naismith.hours));
+    }
+
+    #[test]
+    fn langmuir_corrects_descents_by_steepness() {
+        // Up 600 m, then 100 m down at 10% (5.7°) and 150 m down at 30% (16.7°)
+        let descent = profile(&[(0.0, 0.0), (1.0, 600.0), (2.0, 500.0), (2.5, 350.0)]);
+        let naismith = estimator(TimeModel::Naismith).estimate(2.5, Some(&descent));
+        assert!(close(naismith.hours, 1.5));
+
+        let langmuir = estimator(TimeModel::Langmuir).estimate(2.5, Some(&descent));
+        let gentle = -100.0 / 300.0 / 6.0;
+        let steep = 150.0 / 300.0 / 6.0;
+        assert!(close(langmuir.hours, 1.5 + gentle + steep));
+    }
+
+    #[test]
+    fn flat_fallback_keeps_each_model_speed() {
+        for (model, hours) in [
+            (TimeModel::Naismith, 2.0),
+            (TimeModel::Langmuir, 2.0),
+            (TimeModel::Tobler, 10.0 / tobler_speed(0.0)),
+        ] {
+            let estimate = estimator(model).estimate(10.0, None);
+            assert!(close(estimate.hours, hours), "{:?}", model);
+            assert!(!estimate.terrain);
+            assert!(estimate.describe().ends_with(", no elevation data"));
+
+            // A single point is no profile
+            let point = profile(&[(0.0, 300.0)]);
+            assert!(close(
+                estimator(model).estimate(10.0, Some(&point)).hours,
+                hours
+            ));
+        }
+        assert!((tobler_speed(0.0) - 5.04).abs() < 0.01);
+    }
+
+    #[test]
+    fn pace_scales_the_model_and_its_range() {
+        let slow = Estimator {
+            pace: 1.2,
+            ..estimator(TimeModel::Naismith)
+        };
+        let estimate = slow.estimate(10.0, None);
+        assert!(close(estimate.hours, 2.4));
+        assert!(close(estimate.low, 2.4 * RANGE_LOW));
+        assert!(close(estimate.high, 2.4 * RANGE_HIGH));
+        assert_eq!(estimate.range(), "2h02-3h00");
+        assert_eq!(estimate.describe(), "Naismith ×1.20, no elevation data");
+    }
+
+    #[test]
+    fn personal_pace_replaces_the_model() {
+        let personal = PersonalPace {
+            flat_speed: 4.0,
+            climb_rate: 400.0,
+            hikes: 6,
+            ratio: 1.1,
+            fitted: true,
+        };
+        let estimator = Estimator {
+            pace: 1.3,
+            personal: Some(personal),
+            ..estimator(TimeModel::Tobler)
+        };
+
+        let estimate = estimator.estimate(3.0, Some(&climb()));
+        assert!(close(estimate.hours, 3.0 / 4.0 + 200.0 / 400.0));
+        assert_eq!(estimate.pace, 1.0);
+        assert!(close(estimate.low, estimate.hours * 0.85));
+        assert!(close(estimate.high, estimate.hours * 1.25));
+        assert_eq!(
+            estimate.describe(),
+            "personal pace, 6 hikes, medium confidence"
+        );
+
+        let flat = estimator.estimate(3.0, None);
+        assert!(close(flat.hours, 0.75));
+
+        let one_hike = Estimator {
+            personal: Some(PersonalPace {
+                hikes: 1,
+                ..personal
+            }),
+            ..estimator
+        }
+        .estimate(3.0, None);
+        assert!(close(one_hike.high, 0.75 * 1.35));
+        assert_eq!(
+            one_hike.describe(),
+            "personal pace, 1 hike, low confidence, no elevation data"
+        );
+    }
+
+    #[test]
+    fn hours_format_as_clock_durations() {
+        assert_eq!(format_hours(2.0), "2h00");
+        assert_eq!(format_hours(1.9999), "2h00");
+        assert_eq!(format_hours(0.75), "0h45");
+        assert_eq!(format_hours(-1.0), "0h00");
+    }
+}
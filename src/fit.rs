@@ -0,0 +1,338 @@
+use crate::tracks::{SessionSummary, Track, TrackPoint};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Seconds between the Unix epoch and the FIT epoch (1989-12-31T00:00:00Z)
+const FIT_EPOCH_OFFSET: i64 = 631_065_600;
+
+const MESG_SESSION: u16 = 18;
+const MESG_RECORD: u16 = 20;
+
+const FIELD_TIMESTAMP: u8 = 253;
+
+/// Record fields
+const RECORD_LAT: u8 = 0;
+const RECORD_LNG: u8 = 1;
+const RECORD_ALTITUDE: u8 = 2;
+const RECORD_HEART_RATE: u8 = 3;
+const RECORD_ENHANCED_ALTITUDE: u8 = 78;
+
+/// Session fields
+const SESSION_START_TIME: u8 = 2;
+const SESSION_DISTANCE: u8 = 9;
+const SESSION_CALORIES: u8 = 11;
+const SESSION_AVG_HEART_RATE: u8 = 16;
+const SESSION_MAX_HEART_RATE: u8 = 17;
+const SESSION_ASCENT: u8 = 22;
+
+/// Degrees per semicircle (2^31 semicircles = 180°)
+const SEMICIRCLE_DEG: f64 = 180.0 / 2_147_483_648.0;
+
+/// Layout of one local message type, from its definition message
+#[derive(Debug, Clone)]
+struct Definition {
+    global: u16,
+    big_endian: bool,
+    /// (field number, size in bytes)
+    fields: Vec<(u8, usize)>,
+    /// Bytes of developer fields to skip after the regular ones
+    developer_size: usize,
+}
+
+/// Field values of one data message, unsigned and still raw
+type Fields = HashMap<u8, u64>;
+
+/// Decode a FIT activity: records become track points, the session becomes the summary
+pub fn parse_fit(bytes: &[u8]) -> Result<Track> {
+    let header_size = *bytes.first().context("Empty FIT file")? as usize;
+    if header_size < 12 || bytes.len() < header_size || &bytes[8..12] != b".FIT" {
+        anyhow::bail!("Not a FIT file");
+    }
+    let data_size = u32::from_le_bytes(bytes[4..8].try_into()?) as usize;
+    let data_end = header_size + data_size;
+    if bytes.len() < data_end + 2 {
+        anyhow::bail!("FIT file is truncated");
+    }
+    let expected_crc = u16::from_le_bytes([bytes[data_end], bytes[data_end + 1]]);
+    // Some writers leave the CRC at 0
+    if expected_crc != 0 && crc(&bytes[..data_end]) != expected_crc {
+        anyhow::bail!("FIT file is corrupt (CRC mismatch)");
+    }
+
+    let mut definitions: HashMap<u8, Definition> = HashMap::new();
+    let mut points = Vec::new();
+    let mut session = None;
+    let mut last_timestamp: u32 = 0;
+    let mut pos = header_size;
+
+    while pos < data_end {
+        let header = bytes[pos];
+        pos += 1;
+
+        // Compressed timestamp header: local type in bits 5-6, time offset in bits 0-4
+        if header & 0x80 != 0 {
+            let local = (header >> 5) & 0x03;
+            let offset = u32::from(header & 0x1F);
+            let mut timestamp = (last_timestamp & !0x1F) | offset;
+            if offset < last_timestamp & 0x1F {
+                timestamp = timestamp.wrapping_add(0x20);
+            }
+            let definition = definitions
+                .get(&local)
+                .context("FIT data message before its definition")?;
+            let mut fields = read_fields(bytes, &mut pos, definition)?;
+            fields.insert(FIELD_TIMESTAMP, u64::from(timestamp));
+            last_timestamp = timestamp;
+            handle_message(definition.global, &fields, &mut points, &mut session);
+            continue;
+        }
+
+        let local = header & 0x0F;
+        if header & 0x40 != 0 {
+            let has_developer_fields = header & 0x20 != 0;
+            let definition = read_definition(bytes, &mut pos, has_developer_fields)?;
+            definitions.insert(local, definition);
+        } else {
+            let definition = definitions
+                .get(&local)
+                .context("FIT data message before its definition")?;
+            let fields = read_fields(bytes, &mut pos, definition)?;
+            if let Some(&timestamp) = fields.get(&FIELD_TIMESTAMP) {
+                last_timestamp = timestamp as u32;
+            }
+            handle_message(definition.global, &fields, &mut points, &mut session);
+        }
+    }
+
+    if points.len() < 2 {
+        anyhow::bail!("FIT file has fewer than 2 GPS records");
+    }
+
+    Ok(Track {
+        name: None,
+        points,
+        session,
+    })
+}
+
+fn read_definition(bytes: &[u8], pos: &mut usize, developer: bool) -> Result<Definition> {
+    let fixed = take(bytes, pos, 5)?;
+    let big_endian = fixed[1] == 1;
+    let global = if big_endian {
+        u16::from_be_bytes([fixed[2], fixed[3]])
+    } else {
+        u16::from_le_bytes([fixed[2], fixed[3]])
+    };
+    let count = fixed[4] as usize;
+    let fields = take(bytes, pos, count * 3)?
+        .chunks_exact(3)
+        .map(|f| (f[0], f[1] as usize))
+        .collect();
+
+    let mut developer_size = 0;
+    if developer {
+        let count = take(bytes, pos, 1)?[0] as usize;
+        developer_size = take(bytes, pos, count * 3)?
+            .chunks_exact(3)
+            .map(|f| f[1] as usize)
+            .sum();
+    }
+
+    Ok(Definition {
+        global,
+        big_endian,
+        fields,
+        developer_size,
+    })
+}
+
+/// Read the fields we know to be scalars of 1, 2 or 4 bytes; arrays and strings are skipped
+fn read_fields(bytes: &[u8], pos: &mut usize, definition: &Definition) -> Result<Fields> {
+    let mut fields = Fields::new();
+    for &(number, size) in &definition.fields {
+        let raw = take(bytes, pos, size)?;
+        let value = match (size, definition.big_endian) {
+            (1, _) => u64::from(raw[0]),
+            (2, false) => u64::from(u16::from_le_bytes([raw[0], raw[1]])),
+            (2, true) => u64::from(u16::from_be_bytes([raw[0], raw[1]])),
+            (4, false) => u64::from(u32::from_le_bytes(raw.try_into()?)),
+            (4, true) => u64::from(u32::from_be_bytes(raw.try_into()?)),
+            _ => continue,
+        };
+        if let Some(value) = valid(value, size) {
+            fields.insert(number, value);
+        }
+    }
+    take(bytes, pos, definition.developer_size)?;
+    Ok(fields)
+}
+
+fn take<'a>(bytes: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8]> {
+    let slice = bytes
+        .get(*pos..*pos + len)
+        .context("FIT message runs past the end of the file")?;
+    *pos += len;
+    Ok(slice)
+}
+
+/// None for the all-ones "invalid" marker (and the signed 32-bit one, 0x7FFFFFFF)
+fn valid(value: u64, size: usize) -> Option<u64> {
+    let invalid = match size {
+        1 => 0xFF,
+        2 => 0xFFFF,
+        _ => 0xFFFF_FFFF,
+    };
+    (value != invalid && !(size == 4 && value == 0x7FFF_FFFF)).then_some(value)
+}
+
+fn handle_message(
+    global: u16,
+    fields: &Fields,
+    points: &mut Vec<TrackPoint>,
+    session: &mut Option<SessionSummary>,
+) {
+    match global {
+        MESG_RECORD => {
+            let (Some(&lat), Some(&lng)) = (fields.get(&RECORD_LAT), fields.get(&RECORD_LNG))
+            else {
+                return;
+            };
+            // Altitude is stored as (m + 500) × 5
+            let elevation = fields
+                .get(&RECORD_ENHANCED_ALTITUDE)
+                .or_else(|| fields.get(&RECORD_ALTITUDE))
+                .map(|&a| a as f64 / 5.0 - 500.0);
+            points.push(TrackPoint {
+                lat: semicircles(lat),
+                lng: semicircles(lng),
+                elevation,
+                time: fields.get(&FIELD_TIMESTAMP).and_then(|&t| fit_time(t)),
+                heart_rate: fields.get(&RECORD_HEART_RATE).map(|&hr| hr as u8),
+            });
+        }
+        MESG_SESSION => {
+            *session = Some(SessionSummary {
+                start_time: fields.get(&SESSION_START_TIME).and_then(|&t| fit_time(t)),
+                distance_km: fields
+                    .get(&SESSION_DISTANCE)
+                    .map(|&cm| cm as f64 / 100_000.0),
+                ascent: fields.get(&SESSION_ASCENT).map(|&m| m as f64),
+                calories: fields.get(&SESSION_CALORIES).map(|&c| c as u32),
+                avg_heart_rate: fields.get(&SESSION_AVG_HEART_RATE).map(|&hr| hr as u8),
+                max_heart_rate: fields.get(&SESSION_MAX_HEART_RATE).map(|&hr| hr as u8),
+            });
+        }
+        _ => {}
+    }
+}
+
+fn semicircles(raw: u64) -> f64 {
+    f64::from(raw as u32 as i32) * SEMICIRCLE_DEG
+}
+
+fn fit_time(raw: u64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(raw as i64 + FIT_EPOCH_OFFSET, 0)
+}
+
+/// FIT's CRC-16 over the header and data
+fn crc(bytes: &[u8]) -> u16 {
+    const TABLE: [u16; 16] = [
+        0x0000, 0xCC01, 0xD801, 0x1400, 0xF001, 0x3C00, 0x2800, 0xE401, 0xA001, 0x6C00, 0x7800,
+        0xB401, 0x5000, 0x9C01, 0x8801, 0x4400,
+    ];
+    bytes.iter().fold(0u16, |crc, &byte| {
+        let crc = (crc >> 4) ^ TABLE[(crc & 0xF) as usize] ^ TABLE[(byte & 0xF) as usize];
+        (crc >> 4) ^ TABLE[(crc & 0xF) as usize] ^ TABLE[(byte >> 4) as usize]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn fixture(name: &str) -> Vec<u8> {
+        let path = format!("{}/tests/fixtures/fit/{}", env!("CARGO_MANIFEST_DIR"), name);
+        std::fs::read(path).unwrap()
+    }
+
+    fn error(bytes: &[u8]) -> String {
+        parse_fit(bytes).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn reads_records_and_session() {
+        let track = parse_fit(&fixture("hike.fit")).unwrap();
+        assert_eq!(track.points.len(), 7);
+
+        let first = &track.points[0];
+        assert!((first.lat - 47.0).abs() < 1e-6);
+        assert!((first.lng + 71.0).abs() < 1e-6);
+        assert_eq!(first.elevation, Some(300.0));
+        assert_eq!(first.heart_rate, Some(120));
+        let start = Utc.with_ymd_and_hms(2025, 7, 10, 13, 0, 0).unwrap();
+        assert_eq!(first.time, Some(start));
+
+        // Compressed timestamps, across a 32-second rollover, then a full one
+        let times: Vec<i64> = track
+            .points
+            .iter()
+            .map(|p| (p.time.unwrap() - start).num_seconds())
+            .collect();
+        assert_eq!(times, vec![0, 10, 20, 30, 40, 50, 170]);
+
+        let session = track.session.unwrap();
+        assert_eq!(session.start_time, Some(start));
+        assert_eq!(session.distance_km, Some(0.78));
+        assert_eq!(session.ascent, Some(14.0));
+        assert_eq!(session.calories, Some(412));
+        assert_eq!(session.avg_heart_rate, Some(128));
+        assert_eq!(session.max_heart_rate, Some(141));
+    }
+
+    #[test]
+    fn big_endian_and_developer_fields_read_the_same() {
+        let expected = parse_fit(&fixture("hike.fit")).unwrap();
+        for name in ["big_endian.fit", "developer_fields.fit"] {
+            let track = parse_fit(&fixture(name)).unwrap();
+            assert_eq!(track.coordinates(), expected.coordinates(), "{}", name);
+            let times = |t: &Track| t.points.iter().map(|p| p.time).collect::<Vec<_>>();
+            assert_eq!(times(&track), times(&expected), "{}", name);
+            assert_eq!(
+                track.session.and_then(|s| s.distance_km),
+                Some(0.78),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn compressed_timestamp_wraps_instead_of_overflowing() {
+        let track = parse_fit(&fixture("timestamp_wrap.fit")).unwrap();
+        assert_eq!(track.points.len(), 6);
+        assert!(track.points.iter().all(|p| p.time.is_some()));
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let bytes = fixture("hike.fit");
+        assert!(error(&bytes[..bytes.len() - 10]).contains("truncated"));
+        assert!(error(&bytes[..10]).contains("Not a FIT file"));
+        assert!(error(&[]).contains("Empty"));
+    }
+
+    #[test]
+    fn checks_the_crc() {
+        let mut bytes = fixture("hike.fit");
+        bytes[40] ^= 0x01;
+        assert!(error(&bytes).contains("CRC mismatch"));
+
+        // A zero CRC means the writer didn't compute one
+        let mut bytes = fixture("hike.fit");
+        let len = bytes.len();
+        bytes[len - 2..].fill(0);
+        assert!(parse_fit(&bytes).is_ok());
+    }
+}
@@ -48,6 +48,40 @@ pub fn distance_km(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
     EARTH_RADIUS_KM * c
 }
 
+/// Distance (km) from the first coordinate to each one, along the line
+pub fn cumulative_distances(coordinates: &[(f64, f64)]) -> Vec<f64> {
+    let mut distance = 0.0;
+    let mut distances = Vec::with_capacity(coordinates.len());
+    for (i, &(lat, lng)) in coordinates.iter().enumerate() {
+        if i > 0 {
+            let (prev_lat, prev_lng) = coordinates[i - 1];
+            distance += distance_km(prev_lat, prev_lng, lat, lng);
+        }
+        distances.push(distance);
+    }
+    distances
+}
+
+/// Ray-casting test of a point against a (lat, lng) polygon ring
+pub fn point_in_polygon(lat: f64, lng: f64, polygon: &[(f64, f64)]) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len().wrapping_sub(1);
+
+    for i in 0..polygon.len() {
+        let (lat_i, lng_i) = polygon[i];
+        let (lat_j, lng_j) = polygon[j];
+
+        if (lat_i > lat) != (lat_j > lat)
+            && lng < (lng_j - lng_i) * (lat - lat_i) / (lat_j - lat_i) + lng_i
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+
+    inside
+}
+
 pub fn extract_all_coordinates(geometry: &serde_json::Value) -> Vec<(f64, f64)> {
     let geom_type = match geometry.get("type").and_then(|v| v.as_str()) {
         Some(t) => t,
@@ -20,6 +20,8 @@ impl Icons {
     pub const INFO: &str = "[i]";
     pub const CHECK: &str = "[OK]";
     pub const CALENDAR: &str = "[#]";
+    pub const STORM: &str = "[^]";
+    pub const ACHIEVEMENT: &str = "[A]";
 
     pub fn weather(code: u32) -> &'static str {
         match code {
@@ -1,18 +1,31 @@
 mod cache;
 mod cli;
+mod climate;
 mod commands;
 mod conditions;
+mod config;
 mod data;
+mod estimate;
+mod fit;
 mod geo;
 mod icons;
+mod pace;
+mod profile;
+mod progress;
+mod scoring;
 mod services;
+mod storm;
+mod streak;
+mod tracks;
 mod trails;
 mod tui;
+mod units;
+mod xml;
 
 use crate::icons::Icons;
 use anyhow::Result;
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, DemCommands, LogCommands};
 use colored::Colorize;
 
 fn main() {
@@ -24,9 +37,20 @@ fn main() {
 
 fn run() -> Result<()> {
     let cli = Cli::parse();
+    // A broken config must not lock out every command, `rando update` included
+    let config = config::load_config().unwrap_or_else(|e| {
+        eprintln!(
+            "{} {:#}; using default settings",
+            Icons::WARNING.yellow(),
+            e
+        );
+        config::Config::default()
+    });
+    units::init(display_units(&cli, &config)?);
 
     match &cli.command {
-        Commands::Update => {
+        Commands::Update { elevation: true } => commands::handle_update_elevation()?,
+        Commands::Update { elevation: false } => {
             cache::download_trail_data()?;
             println!("{} Trail data updated successfully", Icons::SUCCESS.green());
         }
@@ -49,12 +73,20 @@ fn run() -> Result<()> {
             commands::export_gpx(trail, output_path)?;
 
             println!("{} Exported: {}", Icons::SUCCESS.green(), output_path);
-            println!("   Trail: {} ({:.0}km)", trail.name, trail.length_km);
+            println!(
+                "   Trail: {} ({})",
+                trail.name,
+                units::get().distance(trail.length_km, 0)
+            );
             println!("   Points: {}", trail.coordinates_wgs84.len());
             println!("   Ready for: Gaia GPS, OsmAnd, AllTrails");
         }
-        Commands::Weather { trail, week } => {
-            commands::handle_weather(trail, *week)?;
+        Commands::Weather {
+            trail,
+            week,
+            profile,
+        } => {
+            commands::handle_weather(trail, *week, profile.as_deref())?;
         }
         Commands::Nearby {
             lat,
@@ -74,18 +106,107 @@ fn run() -> Result<()> {
             commands::handle_random(difficulty.clone(), *max_distance)?;
         }
         Commands::Log {
+            command: Some(command),
+            ..
+        } => match command {
+            LogCommands::List {
+                from,
+                to,
+                park,
+                trail,
+            } => commands::handle_log_list(
+                from.as_deref(),
+                to.as_deref(),
+                park.as_deref(),
+                trail.as_deref(),
+            )?,
+            LogCommands::Show { id } => commands::handle_log_show(*id)?,
+            LogCommands::Edit {
+                id,
+                time,
+                date,
+                notes,
+                details,
+                clear,
+            } => commands::handle_log_edit(
+                *id,
+                time.clone(),
+                date.clone(),
+                notes.clone(),
+                details,
+                clear,
+            )?,
+            LogCommands::Import {
+                file,
+                format,
+                trail,
+                notes,
+            } => commands::handle_log_import(
+                file,
+                format.as_deref(),
+                trail.as_deref(),
+                notes.clone(),
+            )?,
+            LogCommands::Weather => commands::handle_log_weather()?,
+            LogCommands::Export {
+                format,
+                from,
+                to,
+                output,
+            } => commands::handle_log_export(
+                format,
+                from.as_deref(),
+                to.as_deref(),
+                output.as_deref(),
+            )?,
+            LogCommands::Delete { id, yes } => commands::handle_log_delete(*id, *yes)?,
+        },
+        Commands::Log {
+            command: None,
             trail,
             time,
             date,
             notes,
+            details,
+            interactive,
+        } => {
+            let trail = trail
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("Missing trail name"))?;
+            commands::handle_log(
+                trail,
+                time.clone(),
+                date.clone(),
+                notes.clone(),
+                details,
+                *interactive,
+            )?;
+        }
+        Commands::Stats {
+            year,
+            from,
+            to,
+            park,
+            compare_years,
+            tui,
         } => {
-            commands::handle_log(trail, time.clone(), date.clone(), notes.clone())?;
+            commands::handle_stats(
+                *year,
+                from.as_deref(),
+                to.as_deref(),
+                park.as_deref(),
+                *compare_years,
+                *tui,
+            )?;
         }
-        Commands::Stats => {
-            commands::handle_stats()?;
+        Commands::Pace => {
+            commands::handle_pace()?;
         }
-        Commands::Streak => {
-            commands::handle_streak()?;
+        Commands::Progress { park } => {
+            commands::handle_progress(park.as_deref())?;
+        }
+        Commands::Streak { cadence } => {
+            commands::handle_streak(cadence)?;
         }
         Commands::Daylight { trail } => {
             commands::handle_daylight(trail)?;
@@ -96,13 +217,58 @@ fn run() -> Result<()> {
         Commands::Hunt => {
             commands::handle_hunt()?;
         }
-        Commands::Alerts => {
-            commands::handle_alerts()?;
+        Commands::Alerts { trail } => {
+            commands::handle_alerts(trail.as_deref())?;
         }
         Commands::Share { trail } => {
             commands::handle_share(trail)?;
         }
+        Commands::Storm { trail, start } => {
+            commands::handle_storm(trail, start.as_deref())?;
+        }
+        Commands::Climate {
+            trail,
+            years,
+            profile,
+        } => {
+            commands::handle_climate(trail, *years, profile.as_deref())?;
+        }
+        Commands::Profile { trail, grade } => {
+            commands::handle_profile(trail, *grade)?;
+        }
+        Commands::Dem { command } => match command {
+            DemCommands::FetchList { park } => commands::handle_dem_fetch_list(park)?,
+        },
     }
 
     Ok(())
 }
+
+/// Display units from the config file, overridden by command-line flags
+fn display_units(cli: &Cli, config: &config::Config) -> Result<units::Units> {
+    let system = match cli.units.as_deref() {
+        Some(s) => s.parse().map_err(|_| {
+            anyhow::anyhow!(
+                "Invalid units: '{}'. Valid options are: metric, imperial",
+                s
+            )
+        })?,
+        None => config.units,
+    };
+
+    let clock = match cli.clock.as_deref() {
+        Some(s) => s
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid clock: '{}'. Valid options are: 24h, 12h", s))?,
+        None => config.clock,
+    };
+
+    Ok(units::Units {
+        system,
+        clock,
+        decimal_separator: cli
+            .decimal_separator
+            .or(config.decimal_separator)
+            .unwrap_or('.'),
+    })
+}
@@ -0,0 +1,312 @@
+use crate::cache::{elevation_key, get_all_cached_elevations};
+use crate::data::logs::{HikeLog, load_logs};
+use crate::estimate::{Estimator, NAISMITH_CLIMB_RATE, NAISMITH_SPEED, TimeModel, profile_gain};
+use crate::profile::{ElevationProfile, get_trail_profile, trail_profile};
+use crate::scoring::Confidence;
+use crate::trails::load_trails;
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// Average speeds (km/h) outside this range are typos, multi-day trips or partial hikes
+const PLAUSIBLE_SPEED: std::ops::RangeInclusive<f64> = 0.8..=10.0;
+
+/// Fitted models outside these ranges fall back to scaling Naismith
+const PLAUSIBLE_FLAT_SPEED: std::ops::RangeInclusive<f64> = 2.0..=9.0;
+const PLAUSIBLE_CLIMB_RATE: std::ops::RangeInclusive<f64> = 150.0..=2000.0;
+
+/// Hikes needed before fitting speed and climb rate separately
+const MIN_HIKES_FOR_FIT: usize = 3;
+
+/// A logged hike with a duration and the elevation profile of its trail
+#[derive(Debug, Clone)]
+pub struct PaceSample {
+    pub trail_name: String,
+    pub date: String,
+    pub distance_km: f64,
+    pub gain: f64,
+    pub hours: f64,
+    /// Time given by the configured model at standard pace
+    pub standard_hours: f64,
+}
+
+impl PaceSample {
+    /// Actual time over the standard estimate; below 1 is faster
+    pub fn ratio(&self) -> f64 {
+        self.hours / self.standard_hours
+    }
+}
+
+/// Personal hiking model fitted from logged hikes: time = distance / flat_speed + gain / climb_rate
+#[derive(Debug, Clone, Copy)]
+pub struct PersonalPace {
+    /// km/h on the flat
+    pub flat_speed: f64,
+    /// Metres climbed per hour, on top of the flat time
+    pub climb_rate: f64,
+    /// Hikes the model was fitted on
+    pub hikes: usize,
+    /// Median actual over standard time; below 1 is faster
+    pub ratio: f64,
+    /// False when there were too few or too similar hikes to fit both terms,
+    /// and Naismith was scaled instead
+    pub fitted: bool,
+}
+
+impl PersonalPace {
+    pub fn hours(&self, length_km: f64, gain: f64) -> f64 {
+        length_km / self.flat_speed + gain / self.climb_rate
+    }
+
+    pub fn confidence(&self) -> Confidence {
+        match self.hikes {
+            0..=3 => Confidence::Low,
+            4..=9 => Confidence::Medium,
+            _ => Confidence::High,
+        }
+    }
+
+    /// e.g. "12% faster than Tobler"
+    pub fn comparison(&self, model: TimeModel) -> String {
+        let percent = ((self.ratio - 1.0) * 100.0).round();
+        if percent == 0.0 {
+            format!("same as {}", model.label())
+        } else if percent < 0.0 {
+            format!("{:.0}% faster than {}", -percent, model.label())
+        } else {
+            format!("{:.0}% slower than {}", percent, model.label())
+        }
+    }
+}
+
+/// Fit a personal pace by least squares on distance and gain, or scale Naismith
+/// when the hikes can't separate the two
+pub fn calibrate(samples: &[PaceSample]) -> Option<PersonalPace> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    let mut ratios: Vec<f64> = samples.iter().map(PaceSample::ratio).collect();
+    ratios.sort_by(f64::total_cmp);
+    let ratio = ratios[ratios.len() / 2];
+
+    let fit = (samples.len() >= MIN_HIKES_FOR_FIT)
+        .then(|| least_squares(samples))
+        .flatten()
+        .filter(|(speed, rate)| {
+            PLAUSIBLE_FLAT_SPEED.contains(speed) && PLAUSIBLE_CLIMB_RATE.contains(rate)
+        });
+
+    let (flat_speed, climb_rate, fitted) = match fit {
+        Some((speed, rate)) => (speed, rate, true),
+        None => {
+            let naismith: f64 = samples
+                .iter()
+                .map(|s| s.distance_km / NAISMITH_SPEED + s.gain / NAISMITH_CLIMB_RATE)
+                .sum();
+            let actual: f64 = samples.iter().map(|s| s.hours).sum();
+            let scale = actual / naismith;
+            (NAISMITH_SPEED / scale, NAISMITH_CLIMB_RATE / scale, false)
+        }
+    };
+
+    Some(PersonalPace {
+        flat_speed,
+        climb_rate,
+        hikes: samples.len(),
+        ratio,
+        fitted,
+    })
+}
+
+/// Solve hours = a·distance + b·gain for (1/a, 1/b), if the system is well conditioned
+fn least_squares(samples: &[PaceSample]) -> Option<(f64, f64)> {
+    let (mut dd, mut dg, mut gg, mut dt, mut gt) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for s in samples {
+        dd += s.distance_km * s.distance_km;
+        dg += s.distance_km * s.gain;
+        gg += s.gain * s.gain;
+        dt += s.distance_km * s.hours;
+        gt += s.gain * s.hours;
+    }
+
+    // Near-proportional distance and gain can't be told apart
+    let det = dd * gg - dg * dg;
+    if det <= 1e-6 * dd * gg {
+        return None;
+    }
+    let a = (dt * gg - gt * dg) / det;
+    let b = (gt * dd - dt * dg) / det;
+    (a > 0.0 && b > 0.0).then(|| (1.0 / a, 1.0 / b))
+}
+
+/// Logged hikes with a plausible duration and a known elevation profile,
+/// walked as the full trail. Without `fetch`, only cached profiles are used.
+pub fn load_pace_samples(model: TimeModel, fetch: bool) -> Result<Vec<PaceSample>> {
+    let logs = load_logs()?;
+    let timed: Vec<&HikeLog> = logs
+        .hikes
+        .iter()
+        .filter(|h| h.duration_minutes.is_some_and(|m| m > 0) && h.distance_km > 0.0)
+        // The trail's profile doesn't describe a detour or a turnaround
+        .filter(|h| h.details.actual_distance_km.is_none())
+        .collect();
+    if timed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let trails = load_trails()?;
+    let standard = Estimator {
+        model,
+        pace: 1.0,
+        personal: None,
+    };
+    let cached = if fetch {
+        HashMap::new()
+    } else {
+        get_all_cached_elevations()
+    };
+    let profile_of = |trail| {
+        if fetch {
+            get_trail_profile(trail).ok()
+        } else {
+            let elevations = cached.get(&elevation_key(&trail.name, &trail.park))?;
+            Some(trail_profile(trail, elevations))
+        }
+    };
+    let mut profiles: HashMap<(&str, &str), Option<ElevationProfile>> = HashMap::new();
+    let mut samples = Vec::new();
+
+    for hike in timed {
+        let hours = f64::from(hike.duration_minutes.unwrap_or(0)) / 60.0;
+        if !PLAUSIBLE_SPEED.contains(&(hike.distance_km / hours)) {
+            continue;
+        }
+
+        let profile = profiles
+            .entry((&hike.trail_name, &hike.park))
+            .or_insert_with(|| hike.trail(&trails).and_then(profile_of));
+        let Some(profile) = profile.as_ref().filter(|p| p.points.len() >= 2) else {
+            continue;
+        };
+
+        samples.push(PaceSample {
+            trail_name: hike.trail_name.clone(),
+            date: hike.date.clone(),
+            distance_km: hike.distance_km,
+            gain: profile_gain(profile),
+            hours,
+            standard_hours: standard.estimate(hike.distance_km, Some(profile)).hours,
+        });
+    }
+
+    Ok(samples)
+}
+
+/// Personal pace from the logs and cached profiles, or None without any usable hikes
+pub fn load_personal_pace(model: TimeModel) -> Result<Option<PersonalPace>> {
+    Ok(calibrate(&load_pace_samples(model, false)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(distance_km: f64, gain: f64, hours: f64) -> PaceSample {
+        PaceSample {
+            trail_name: String::new(),
+            date: String::new(),
+            distance_km,
+            gain,
+            hours,
+            standard_hours: distance_km / NAISMITH_SPEED + gain / NAISMITH_CLIMB_RATE,
+        }
+    }
+
+    /// Hikes timed exactly by `speed` km/h on the flat and `rate` m/h climbing
+    fn hikes(speed: f64, rate: f64) -> Vec<PaceSample> {
+        [
+            (8.0, 200.0),
+            (12.0, 900.0),
+            (5.0, 50.0),
+            (15.0, 400.0),
+            (10.0, 1100.0),
+        ]
+        .iter()
+        .map(|&(d, g)| sample(d, g, d / speed + g / rate))
+        .collect()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn no_samples_no_pace() {
+        assert!(calibrate(&[]).is_none());
+    }
+
+    #[test]
+    fn fits_speed_and_climb_rate() {
+        let pace = calibrate(&hikes(4.0, 450.0)).unwrap();
+        assert!(pace.fitted);
+        assert!(close(pace.flat_speed, 4.0));
+        assert!(close(pace.climb_rate, 450.0));
+        assert_eq!(pace.hikes, 5);
+        assert!(pace.ratio > 1.0);
+    }
+
+    #[test]
+    fn few_hikes_scale_naismith() {
+        // 20% slower than Naismith on both hikes
+        let samples: Vec<PaceSample> = [(8.0, 200.0), (12.0, 900.0)]
+            .iter()
+            .map(|&(d, g)| sample(d, g, 1.2 * (d / NAISMITH_SPEED + g / NAISMITH_CLIMB_RATE)))
+            .collect();
+        let pace = calibrate(&samples).unwrap();
+        assert!(!pace.fitted);
+        assert!(close(pace.flat_speed, NAISMITH_SPEED / 1.2));
+        assert!(close(pace.climb_rate, NAISMITH_CLIMB_RATE / 1.2));
+        assert!(close(pace.ratio, 1.2));
+    }
+
+    #[test]
+    fn implausible_fit_falls_back() {
+        // Climbing barely costs any time: a 20000 m/h climb rate is noise, not a pace
+        let pace = calibrate(&hikes(4.0, 20_000.0)).unwrap();
+        assert!(!pace.fitted);
+        assert!(PLAUSIBLE_FLAT_SPEED.contains(&pace.flat_speed));
+
+        let pace = calibrate(&hikes(12.0, 600.0)).unwrap();
+        assert!(!pace.fitted);
+    }
+
+    #[test]
+    fn proportional_hikes_cant_be_separated() {
+        // Every hike climbs 100 m per km
+        let samples: Vec<PaceSample> = [4.0, 8.0, 12.0, 16.0]
+            .iter()
+            .map(|&d| sample(d, d * 100.0, d / 4.0 + d * 100.0 / 500.0))
+            .collect();
+        assert!(least_squares(&samples).is_none());
+        assert!(!calibrate(&samples).unwrap().fitted);
+
+        // Tiny differences in grade don't make it well conditioned either
+        let samples: Vec<PaceSample> = [4.0, 8.0, 12.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &d)| sample(d, d * (100.0 + i as f64 * 1e-3), d / 3.0))
+            .collect();
+        assert!(least_squares(&samples).is_none());
+    }
+
+    #[test]
+    fn negative_terms_are_rejected() {
+        // Longer hikes went faster: no positive flat time fits
+        let samples = vec![
+            sample(5.0, 500.0, 4.0),
+            sample(10.0, 500.0, 3.0),
+            sample(15.0, 600.0, 2.5),
+        ];
+        assert!(least_squares(&samples).is_none());
+    }
+}
@@ -0,0 +1,452 @@
+use crate::estimate::tobler_speed;
+use crate::services::elevation::{get_trail_elevation, sample_along, smooth_elevations};
+use crate::trails::Trail;
+use anyhow::Result;
+
+/// Climb score (length in m × average grade in %) thresholds, easiest first
+const CLIMB_CATEGORIES: [(f64, ClimbCategory); 5] = [
+    (8_000.0, ClimbCategory::Cat4),
+    (16_000.0, ClimbCategory::Cat3),
+    (32_000.0, ClimbCategory::Cat2),
+    (64_000.0, ClimbCategory::Cat1),
+    (80_000.0, ClimbCategory::Hc),
+];
+
+/// Descent (m) that ends a climb
+const CLIMB_END_DROP: f64 = 10.0;
+
+/// Gain (m) below which an uphill stretch isn't a climb
+const CLIMB_MIN_GAIN: f64 = 30.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ProfilePoint {
+    pub distance_km: f64,
+    pub elevation: f64,
+}
+
+/// Stretch between two consecutive profile points
+#[derive(Debug, Clone, Copy)]
+pub struct Segment {
+    pub start_km: f64,
+    pub end_km: f64,
+    pub rise: f64,
+}
+
+impl Segment {
+    pub fn length_km(&self) -> f64 {
+        self.end_km - self.start_km
+    }
+
+    /// Grade in %, positive uphill
+    pub fn grade(&self) -> f64 {
+        let run = self.length_km() * 1000.0;
+        if run <= 0.0 {
+            0.0
+        } else {
+            self.rise / run * 100.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ClimbCategory {
+    Cat4,
+    Cat3,
+    Cat2,
+    Cat1,
+    Hc,
+}
+
+impl ClimbCategory {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ClimbCategory::Cat4 => "Cat 4",
+            ClimbCategory::Cat3 => "Cat 3",
+            ClimbCategory::Cat2 => "Cat 2",
+            ClimbCategory::Cat1 => "Cat 1",
+            ClimbCategory::Hc => "HC",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Climb {
+    pub start_km: f64,
+    pub end_km: f64,
+    pub gain: f64,
+    /// None for climbs too short or gentle to categorize
+    pub category: Option<ClimbCategory>,
+}
+
+impl Climb {
+    pub fn length_km(&self) -> f64 {
+        self.end_km - self.start_km
+    }
+
+    pub fn grade(&self) -> f64 {
+        let run = self.length_km() * 1000.0;
+        if run <= 0.0 {
+            0.0
+        } else {
+            self.gain / run * 100.0
+        }
+    }
+}
+
+/// Elevations along a trail, indexed by distance from the start
+#[derive(Debug, Clone, Default)]
+pub struct ElevationProfile {
+    pub points: Vec<ProfilePoint>,
+}
+
+impl ElevationProfile {
+    /// Profile assuming the samples are evenly spread over `length_km`
+    pub fn evenly_spaced(elevations: &[f64], length_km: f64) -> Self {
+        let last = elevations.len().saturating_sub(1).max(1) as f64;
+        Self {
+            points: elevations
+                .iter()
+                .enumerate()
+                .map(|(i, &elevation)| ProfilePoint {
+                    distance_km: i as f64 / last * length_km,
+                    elevation,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn length_km(&self) -> f64 {
+        self.points.last().map(|p| p.distance_km).unwrap_or(0.0)
+    }
+
+    pub fn elevations(&self) -> Vec<f64> {
+        self.points.iter().map(|p| p.elevation).collect()
+    }
+
+    /// Mean spacing between points in metres
+    pub fn spacing_m(&self) -> f64 {
+        if self.points.len() < 2 {
+            return 0.0;
+        }
+        self.length_km() * 1000.0 / (self.points.len() - 1) as f64
+    }
+
+    /// Same distances with the elevations run through the moving-average filter
+    pub fn smoothed(&self) -> Self {
+        let smoothed = smooth_elevations(&self.elevations(), self.spacing_m());
+        Self {
+            points: self
+                .points
+                .iter()
+                .zip(smoothed)
+                .map(|(p, elevation)| ProfilePoint {
+                    distance_km: p.distance_km,
+                    elevation,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn segments(&self) -> Vec<Segment> {
+        self.points
+            .windows(2)
+            .map(|w| Segment {
+                start_km: w[0].distance_km,
+                end_km: w[1].distance_km,
+                rise: w[1].elevation - w[0].elevation,
+            })
+            .collect()
+    }
+
+    /// Elevation at a distance, interpolated between points
+    pub fn elevation_at(&self, distance_km: f64) -> Option<f64> {
+        let first = self.points.first()?;
+        if distance_km <= first.distance_km {
+            return Some(first.elevation);
+        }
+        for w in self.points.windows(2) {
+            if distance_km <= w[1].distance_km {
+                let span = w[1].distance_km - w[0].distance_km;
+                let t = if span > 0.0 {
+                    (distance_km - w[0].distance_km) / span
+                } else {
+                    0.0
+                };
+                return Some(w[0].elevation + t * (w[1].elevation - w[0].elevation));
+            }
+        }
+        self.points.last().map(|p| p.elevation)
+    }
+
+    /// `count` evenly spaced elevations along the distance axis
+    pub fn resample(&self, count: usize) -> Vec<f64> {
+        let length = self.length_km();
+        let last = count.saturating_sub(1).max(1) as f64;
+        (0..count)
+            .filter_map(|i| self.elevation_at(i as f64 / last * length))
+            .collect()
+    }
+
+    /// Steepest stretch of at least `window_km`, uphill when `uphill` or downhill otherwise
+    pub fn steepest(&self, window_km: f64, uphill: bool) -> Option<Segment> {
+        let points = &self.smoothed().points;
+        let mut best: Option<Segment> = None;
+
+        for (i, start) in points.iter().enumerate() {
+            let Some(end) = points[i + 1..]
+                .iter()
+                .find(|p| p.distance_km - start.distance_km >= window_km)
+            else {
+                break;
+            };
+            let segment = Segment {
+                start_km: start.distance_km,
+                end_km: end.distance_km,
+                rise: end.elevation - start.elevation,
+            };
+            let steeper = match &best {
+                Some(b) if uphill => segment.grade() > b.grade(),
+                Some(b) => segment.grade() < b.grade(),
+                None => true,
+            };
+            if steeper {
+                best = Some(segment);
+            }
+        }
+
+        best.filter(|s| if uphill { s.rise > 0.0 } else { s.rise < 0.0 })
+    }
+
+    /// Uphill stretches of the smoothed profile, ended by a drop of `CLIMB_END_DROP`
+    pub fn climbs(&self) -> Vec<Climb> {
+        let points = &self.smoothed().points;
+        let mut climbs = Vec::new();
+        let Some(first) = points.first() else {
+            return climbs;
+        };
+
+        let mut low = *first;
+        let mut high = *first;
+        for &point in &points[1..] {
+            if point.elevation > high.elevation {
+                high = point;
+            } else if high.elevation - point.elevation >= CLIMB_END_DROP
+                || point.elevation < low.elevation
+            {
+                push_climb(&mut climbs, low, high);
+                low = point;
+                high = point;
+            }
+        }
+        push_climb(&mut climbs, low, high);
+
+        climbs
+    }
+
+    /// Distance (km) on segments steeper than `grade` %, up or down
+    pub fn distance_above_grade(&self, grade: f64) -> f64 {
+        self.smoothed()
+            .segments()
+            .iter()
+            .filter(|s| s.grade().abs() > grade)
+            .map(Segment::length_km)
+            .sum()
+    }
+
+    /// Walking time (h) on segments steeper than `grade` %, up or down
+    pub fn hours_above_grade(&self, grade: f64) -> f64 {
+        self.smoothed()
+            .segments()
+            .iter()
+            .filter(|s| s.grade().abs() > grade)
+            .map(|s| s.length_km() / tobler_speed(s.grade()))
+            .sum()
+    }
+}
+
+fn push_climb(climbs: &mut Vec<Climb>, low: ProfilePoint, high: ProfilePoint) {
+    let gain = high.elevation - low.elevation;
+    if gain < CLIMB_MIN_GAIN || high.distance_km <= low.distance_km {
+        return;
+    }
+    let mut climb = Climb {
+        start_km: low.distance_km,
+        end_km: high.distance_km,
+        gain,
+        category: None,
+    };
+    let score = climb.length_km() * 1000.0 * climb.grade();
+    climb.category = CLIMB_CATEGORIES
+        .iter()
+        .rev()
+        .find(|(min, _)| score >= *min)
+        .map(|(_, category)| *category);
+    climbs.push(climb);
+}
+
+/// Elevation profile of a trail, with distances measured along its geometry
+pub fn get_trail_profile(trail: &Trail) -> Result<ElevationProfile> {
+    Ok(trail_profile(trail, &get_trail_elevation(trail)?))
+}
+
+/// Profile of a trail from elevations already sampled along it
+pub fn trail_profile(trail: &Trail, elevations: &[f64]) -> ElevationProfile {
+    let samples = sample_along(&trail.coordinates_wgs84);
+
+    // Cached profiles from another sampling can't be matched to coordinates
+    if samples.len() != elevations.len() {
+        return ElevationProfile::evenly_spaced(elevations, trail.length_km);
+    }
+
+    // Samples are measured along every vertex, so corners aren't cut;
+    // rescale to the official length
+    let along: Vec<f64> = samples.iter().map(|(distance, _)| *distance).collect();
+    let scale = length_scale(&along, trail.length_km);
+
+    ElevationProfile {
+        points: along
+            .iter()
+            .zip(elevations)
+            .map(|(&distance, &elevation)| ProfilePoint {
+                distance_km: distance * scale,
+                elevation,
+            })
+            .collect(),
+    }
+}
+
+/// Factor bringing the measured length to the official one, when known
+fn length_scale(distances: &[f64], length_km: f64) -> f64 {
+    match distances.last() {
+        Some(&measured) if measured > 0.0 && length_km > 0.0 => length_km / measured,
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Profile from (distance km, elevation m) pairs; spaced widely enough
+    /// that smoothing leaves them as they are
+    fn profile(points: &[(f64, f64)]) -> ElevationProfile {
+        ElevationProfile {
+            points: points
+                .iter()
+                .map(|&(distance_km, elevation)| ProfilePoint {
+                    distance_km,
+                    elevation,
+                })
+                .collect(),
+        }
+    }
+
+    /// Grades 10%, 20%, 10%, 5% then -14%, on stretches of 200 to 800 m
+    fn uneven() -> ElevationProfile {
+        profile(&[
+            (0.0, 100.0),
+            (0.2, 120.0),
+            (0.5, 180.0),
+            (1.3, 260.0),
+            (1.5, 270.0),
+            (2.0, 200.0),
+        ])
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn steepest_measures_by_distance() {
+        let profile = uneven();
+        assert!(profile.spacing_m() > 150.0);
+
+        let up = profile.steepest(0.25, true).unwrap();
+        assert_eq!((up.start_km, up.end_km), (0.2, 0.5));
+        assert!(close(up.grade(), 20.0));
+
+        let down = profile.steepest(0.25, false).unwrap();
+        assert_eq!((down.start_km, down.end_km), (1.5, 2.0));
+        assert!(close(down.grade(), -14.0));
+
+        // Windows start at a point and end at the first one far enough along
+        let long = profile.steepest(1.0, true).unwrap();
+        assert_eq!((long.start_km, long.end_km), (0.2, 1.3));
+
+        // The same elevations spread evenly put the steepest stretch elsewhere
+        let even = ElevationProfile::evenly_spaced(&profile.elevations(), 2.0);
+        assert_eq!(even.steepest(0.25, true).unwrap().start_km, 0.8);
+
+        assert!(profile.steepest(5.0, true).is_none());
+        let flat = self::profile(&[(0.0, 100.0), (1.0, 100.0), (2.0, 100.0)]);
+        assert!(flat.steepest(0.5, true).is_none());
+        assert!(flat.steepest(0.5, false).is_none());
+    }
+
+    #[test]
+    fn climbs_end_on_a_real_drop() {
+        let profile = profile(&[
+            (0.0, 100.0),
+            (0.5, 150.0),
+            (1.0, 200.0),
+            // 15 m down ends the first climb
+            (1.2, 185.0),
+            (2.0, 300.0),
+            // 5 m down doesn't
+            (2.2, 295.0),
+            (3.0, 400.0),
+            (3.5, 380.0),
+            // Too small to count
+            (4.0, 395.0),
+        ]);
+        let climbs = profile.climbs();
+        assert_eq!(climbs.len(), 2);
+
+        assert_eq!((climbs[0].start_km, climbs[0].end_km), (0.0, 1.0));
+        assert_eq!(climbs[0].gain, 100.0);
+        assert!(close(climbs[0].grade(), 10.0));
+        assert_eq!(climbs[0].category, Some(ClimbCategory::Cat4));
+
+        assert_eq!((climbs[1].start_km, climbs[1].end_km), (1.2, 3.0));
+        assert_eq!(climbs[1].gain, 215.0);
+        assert_eq!(climbs[1].category, Some(ClimbCategory::Cat3));
+
+        assert!(uneven().climbs()[0].category.is_some());
+        assert!(ElevationProfile::default().climbs().is_empty());
+    }
+
+    #[test]
+    fn grade_thresholds_weigh_stretches_by_length() {
+        let profile = uneven();
+        assert!(close(profile.distance_above_grade(15.0), 0.3));
+        assert!(close(profile.distance_above_grade(9.0), 1.8));
+        assert!(close(profile.distance_above_grade(12.0), 0.8));
+        assert_eq!(profile.distance_above_grade(25.0), 0.0);
+
+        assert!(close(
+            profile.hours_above_grade(15.0),
+            0.3 / tobler_speed(20.0)
+        ));
+        assert!(close(
+            profile.hours_above_grade(12.0),
+            0.3 / tobler_speed(20.0) + 0.5 / tobler_speed(-14.0)
+        ));
+        assert_eq!(profile.hours_above_grade(25.0), 0.0);
+    }
+
+    #[test]
+    fn elevation_is_interpolated_along_distance() {
+        let profile = uneven();
+        assert_eq!(profile.length_km(), 2.0);
+        assert_eq!(profile.elevation_at(-1.0), Some(100.0));
+        assert!(close(profile.elevation_at(0.9).unwrap(), 220.0));
+        assert_eq!(profile.elevation_at(3.0), Some(200.0));
+        assert_eq!(profile.resample(3), vec![100.0, 230.0, 200.0]);
+        assert_eq!(ElevationProfile::default().elevation_at(1.0), None);
+    }
+}
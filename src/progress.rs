@@ -0,0 +1,531 @@
+use crate::data::logs::HikeLog;
+use crate::trails::{Difficulty, Trail};
+use crate::units;
+use chrono::{Datelike, NaiveDate};
+use std::collections::{HashMap, HashSet};
+
+/// Sépaq tourist regions, by keywords of their parks' names. Only regions with
+/// at least two parks in the catalog count for the region achievement
+const REGIONS: &[(&str, &[&str])] = &[
+    (
+        "Capitale-Nationale",
+        &["jacques-cartier", "grands-jardins", "hautes-gorges"],
+    ),
+    (
+        "Cantons-de-l'Est",
+        &["mont-orford", "mont-mégantic", "yamaska"],
+    ),
+    ("Montérégie", &["mont-saint-bruno", "îles-de-boucherville"]),
+    ("Laurentides", &["mont-tremblant", "d'oka"]),
+    (
+        "Saguenay–Lac-Saint-Jean",
+        &["fjord-du-saguenay", "monts-valin", "pointe-taillon"],
+    ),
+    (
+        "Gaspésie",
+        &["de la gaspésie", "île-bonaventure", "miguasha"],
+    ),
+    ("Bas-Saint-Laurent", &["du bic", "lac-témiscouata"]),
+    ("Abitibi-Témiscamingue", &["aiguebelle", "opémican"]),
+];
+
+/// Distance of one outing for the long haul achievement, in km
+const LONG_HAUL_KM: f64 = 20.0;
+
+/// Climb in one hike for the big climb achievement, in metres
+const BIG_CLIMB_M: f64 = 1000.0;
+
+/// Total climb for the Everest achievement, in metres
+const EVEREST_M: f64 = 8849.0;
+
+/// Distance in one season for the century achievement, in km
+const CENTURY_KM: f64 = 100.0;
+
+/// Trails hiked, as (trail, park), counting the other trails an imported track covered
+pub fn hiked_trails(hikes: &[HikeLog]) -> HashSet<(&str, &str)> {
+    hikes
+        .iter()
+        .flat_map(|h| {
+            std::iter::once((h.trail_name.as_str(), h.park.as_str())).chain(
+                h.other_trails
+                    .iter()
+                    .map(|t| (t.name.as_str(), t.park.as_str())),
+            )
+        })
+        .collect()
+}
+
+/// How much of a park's catalog has been hiked
+#[derive(Debug)]
+pub struct ParkProgress<'a> {
+    pub park: &'a str,
+    pub park_code: &'a str,
+    pub trails: Vec<&'a Trail>,
+    pub done: Vec<&'a Trail>,
+}
+
+impl ParkProgress<'_> {
+    pub fn total_km(&self) -> f64 {
+        self.trails.iter().map(|t| t.length_km).sum()
+    }
+
+    pub fn done_km(&self) -> f64 {
+        self.done.iter().map(|t| t.length_km).sum()
+    }
+
+    /// Share of the park's trails hiked, 0 to 1
+    pub fn ratio(&self) -> f64 {
+        self.done.len() as f64 / self.trails.len().max(1) as f64
+    }
+
+    pub fn is_complete(&self) -> bool {
+        !self.trails.is_empty() && self.done.len() == self.trails.len()
+    }
+
+    pub fn remaining(&self) -> Vec<&Trail> {
+        self.trails
+            .iter()
+            .filter(|t| !self.done.iter().any(|d| d.name == t.name))
+            .copied()
+            .collect()
+    }
+}
+
+/// Completion of every park in the catalog, by park name
+pub fn park_progress<'a>(trails: &'a [Trail], hikes: &[HikeLog]) -> Vec<ParkProgress<'a>> {
+    let hiked = hiked_trails(hikes);
+    let mut parks: Vec<ParkProgress> = Vec::new();
+
+    // The catalog is sorted by park, then trail
+    for trail in trails {
+        if parks.last().is_none_or(|p| p.park != trail.park) {
+            parks.push(ParkProgress {
+                park: &trail.park,
+                park_code: &trail.park_code,
+                trails: Vec::new(),
+                done: Vec::new(),
+            });
+        }
+        if let Some(progress) = parks.last_mut() {
+            progress.trails.push(trail);
+            if hiked.contains(&(trail.name.as_str(), trail.park.as_str())) {
+                progress.done.push(trail);
+            }
+        }
+    }
+
+    parks
+}
+
+/// A milestone, earned or not
+#[derive(Debug, Clone)]
+pub struct Achievement {
+    pub name: &'static str,
+    pub description: String,
+    pub earned: Option<Earned>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Earned {
+    pub date: String,
+    /// What did it, e.g. the trail or the park
+    pub detail: String,
+}
+
+struct Rule {
+    name: &'static str,
+    /// In the display units, hence built when listed
+    description: fn() -> String,
+    /// Checked after each hike, oldest first; Some(detail) once met
+    check: fn(&Tally, &HikeLog) -> Option<String>,
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        name: "First steps",
+        description: || "Log your first hike".into(),
+        check: |_, hike| Some(hike.trail_name.clone()),
+    },
+    Rule {
+        name: "Regular",
+        description: || "Log 10 hikes".into(),
+        check: |tally, _| (tally.hikes >= 10).then(|| "10 hikes".to_string()),
+    },
+    Rule {
+        name: "Seasoned",
+        description: || "Log 50 hikes".into(),
+        check: |tally, _| (tally.hikes >= 50).then(|| "50 hikes".to_string()),
+    },
+    Rule {
+        name: "First Difficile",
+        description: || "Hike a trail rated Difficile".into(),
+        check: |tally, hike| {
+            (tally.difficulty == Some(Difficulty::Difficile)).then(|| hike.trail_name.clone())
+        },
+    },
+    Rule {
+        name: "Full range",
+        description: || "Hike a Facile, an Intermédiaire and a Difficile trail".into(),
+        check: |tally, _| (tally.difficulties.len() == 3).then(|| "all 3 difficulties".into()),
+    },
+    Rule {
+        name: "Long haul",
+        description: || {
+            format!(
+                "Hike {} in one outing",
+                units::get().distance(LONG_HAUL_KM, 0)
+            )
+        },
+        check: |_, hike| {
+            (hike.hiked_km() >= LONG_HAUL_KM).then(|| {
+                format!(
+                    "{} on {}",
+                    units::get().distance(hike.hiked_km(), 1),
+                    hike.trail_name
+                )
+            })
+        },
+    },
+    Rule {
+        name: "Big climb",
+        description: || format!("Climb {} in one hike", units::get().elevation(BIG_CLIMB_M)),
+        check: |tally, hike| {
+            tally
+                .gain
+                .filter(|&gain| gain >= BIG_CLIMB_M)
+                .map(|gain| format!("{} on {}", units::get().elevation(gain), hike.trail_name))
+        },
+    },
+    Rule {
+        name: "Everest",
+        description: || format!("Climb {} in total", units::get().elevation(EVEREST_M)),
+        check: |tally, _| {
+            (tally.total_gain >= EVEREST_M).then(|| units::get().elevation(tally.total_gain))
+        },
+    },
+    Rule {
+        name: "Century season",
+        description: || {
+            format!(
+                "Hike {} in one season",
+                units::get().distance(CENTURY_KM, 0)
+            )
+        },
+        check: |tally, _| {
+            tally
+                .season_km
+                .iter()
+                .find(|(_, km)| **km >= CENTURY_KM)
+                .map(|((season, year), _)| format!("{} {}", season, year))
+        },
+    },
+    Rule {
+        name: "Park hopper",
+        description: || "Hike in 5 different parks".into(),
+        check: |tally, _| (tally.parks.len() >= 5).then(|| "5 parks".to_string()),
+    },
+    Rule {
+        name: "Park complete",
+        description: || "Hike every trail of a park".into(),
+        check: |tally, hike| {
+            // A track can finish off a neighbouring park through its other trails
+            std::iter::once(hike.park.as_str())
+                .chain(hike.other_trails.iter().map(|t| t.park.as_str()))
+                .find(|&park| {
+                    tally.park_trails.get(park).is_some_and(|trails| {
+                        trails
+                            .iter()
+                            .all(|&trail| tally.done.contains(&(trail, park)))
+                    })
+                })
+                .map(str::to_string)
+        },
+    },
+    Rule {
+        name: "Regional",
+        description: || "Hike in every park of a region".into(),
+        check: |tally, _| {
+            tally
+                .regions
+                .iter()
+                .find(|(_, parks)| parks.iter().all(|p| tally.parks.contains(p)))
+                .map(|(region, _)| region.to_string())
+        },
+    },
+];
+
+/// Running totals while replaying the log
+struct Tally<'a> {
+    hikes: usize,
+    parks: HashSet<&'a str>,
+    difficulties: HashSet<Difficulty>,
+    done: HashSet<(&'a str, &'a str)>,
+    season_km: HashMap<(&'static str, i32), f64>,
+    total_gain: f64,
+    /// Of the latest hike
+    difficulty: Option<Difficulty>,
+    gain: Option<f64>,
+    /// Catalog trails of each park
+    park_trails: HashMap<&'a str, Vec<&'a str>>,
+    /// Regions with their catalog parks
+    regions: Vec<(&'static str, Vec<&'a str>)>,
+}
+
+impl<'a> Tally<'a> {
+    fn new(trails: &'a [Trail]) -> Self {
+        let mut park_trails: HashMap<&str, Vec<&str>> = HashMap::new();
+        for trail in trails {
+            park_trails
+                .entry(trail.park.as_str())
+                .or_default()
+                .push(trail.name.as_str());
+        }
+        let regions = REGIONS
+            .iter()
+            .map(|(region, keywords)| {
+                let parks: Vec<&str> = park_trails
+                    .keys()
+                    .filter(|park| {
+                        let park = park.to_lowercase();
+                        keywords.iter().any(|k| park.contains(k))
+                    })
+                    .copied()
+                    .collect();
+                (*region, parks)
+            })
+            .filter(|(_, parks)| parks.len() >= 2)
+            .collect();
+
+        Self {
+            hikes: 0,
+            parks: HashSet::new(),
+            difficulties: HashSet::new(),
+            done: HashSet::new(),
+            season_km: HashMap::new(),
+            total_gain: 0.0,
+            difficulty: None,
+            gain: None,
+            park_trails,
+            regions,
+        }
+    }
+
+    fn record(&mut self, hike: &'a HikeLog, trails: &'a [Trail]) {
+        let trail = hike.trail(trails);
+        self.hikes += 1;
+        self.parks.insert(&hike.park);
+        self.done.extend(hiked_trails(std::slice::from_ref(hike)));
+        self.difficulty = trail.and_then(|t| t.difficulty);
+        self.difficulties.extend(self.difficulty);
+        self.gain = hike.gain(trails);
+        self.total_gain += self.gain.unwrap_or(0.0);
+        if let Ok(date) = NaiveDate::parse_from_str(&hike.date, "%Y-%m-%d") {
+            *self.season_km.entry(season(date)).or_insert(0.0) += hike.hiked_km();
+        }
+    }
+}
+
+/// Meteorological season and its year; December counts toward the next winter
+fn season(date: NaiveDate) -> (&'static str, i32) {
+    match date.month() {
+        12 => ("Winter", date.year() + 1),
+        1 | 2 => ("Winter", date.year()),
+        3..=5 => ("Spring", date.year()),
+        6..=8 => ("Summer", date.year()),
+        _ => ("Fall", date.year()),
+    }
+}
+
+/// Every achievement, with the hike that earned it for those reached
+pub fn achievements(hikes: &[HikeLog], trails: &[Trail]) -> Vec<Achievement> {
+    let mut sorted: Vec<&HikeLog> = hikes.iter().collect();
+    sorted.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+
+    let mut earned: Vec<Option<Earned>> = vec![None; RULES.len()];
+    let mut tally = Tally::new(trails);
+    for hike in sorted {
+        tally.record(hike, trails);
+        for (rule, earned) in RULES.iter().zip(earned.iter_mut()) {
+            if earned.is_none()
+                && let Some(detail) = (rule.check)(&tally, hike)
+            {
+                *earned = Some(Earned {
+                    date: hike.date.clone(),
+                    detail,
+                });
+            }
+        }
+    }
+
+    RULES
+        .iter()
+        .zip(earned)
+        .map(|(rule, earned)| Achievement {
+            name: rule.name,
+            description: (rule.description)(),
+            earned,
+        })
+        .collect()
+}
+
+/// Achievements earned in `after` that weren't in `before`
+pub fn newly_earned(before: &[Achievement], after: Vec<Achievement>) -> Vec<Achievement> {
+    after
+        .into_iter()
+        .filter(|a| a.earned.is_some())
+        .filter(|a| {
+            !before
+                .iter()
+                .any(|b| b.name == a.name && b.earned.is_some())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::logs::{HikeDetails, TrailRef};
+
+    fn trail(name: &str, park: &str, difficulty: Difficulty, length_km: f64) -> Trail {
+        Trail {
+            name: name.to_string(),
+            park: park.to_string(),
+            park_code: String::new(),
+            difficulty: Some(difficulty),
+            length_km,
+            lat: 46.0,
+            lng: -72.0,
+            distance_from_mtl: 0.0,
+            coordinates_wgs84: Vec::new(),
+            elevation: None,
+        }
+    }
+
+    fn catalog() -> Vec<Trail> {
+        vec![
+            trail("Les Loups", "Parc A", Difficulty::Facile, 5.0),
+            trail("La Coulée", "Parc A", Difficulty::Intermediaire, 8.0),
+            trail("Le Sommet", "Parc B", Difficulty::Difficile, 12.0),
+            trail("Le Lac", "Parc B", Difficulty::Facile, 3.0),
+        ]
+    }
+
+    fn hike(id: u32, date: &str, trail: &Trail, distance_km: f64) -> HikeLog {
+        HikeLog {
+            id,
+            trail_name: trail.name.clone(),
+            park: trail.park.clone(),
+            date: date.to_string(),
+            duration_minutes: None,
+            distance_km,
+            notes: None,
+            elevation_gain: None,
+            other_trails: Vec::new(),
+            avg_heart_rate: None,
+            max_heart_rate: None,
+            calories: None,
+            track_file: None,
+            weather: None,
+            details: HikeDetails::default(),
+        }
+    }
+
+    fn earned<'a>(all: &'a [Achievement], name: &str) -> Option<&'a Earned> {
+        all.iter().find(|a| a.name == name)?.earned.as_ref()
+    }
+
+    #[test]
+    fn achievements_follow_the_log_in_date_order() {
+        let trails = catalog();
+        let mut big = hike(2, "2025-07-01", &trails[2], 21.5);
+        big.elevation_gain = Some(1200.0);
+        // Logged first but hiked later
+        let hikes = vec![big, hike(1, "2025-06-01", &trails[0], 5.0)];
+
+        let all = achievements(&hikes, &trails);
+        assert_eq!(all.len(), RULES.len());
+        assert_eq!(all[0].name, "First steps");
+        let first = earned(&all, "First steps").unwrap();
+        assert_eq!(
+            (first.date.as_str(), first.detail.as_str()),
+            ("2025-06-01", "Les Loups")
+        );
+        assert_eq!(earned(&all, "First Difficile").unwrap().date, "2025-07-01");
+        assert_eq!(
+            earned(&all, "Long haul").unwrap().detail,
+            "21.5km on Le Sommet"
+        );
+        assert_eq!(
+            earned(&all, "Big climb").unwrap().detail,
+            "1200m on Le Sommet"
+        );
+        assert!(earned(&all, "Full range").is_none());
+        assert!(earned(&all, "Regular").is_none());
+    }
+
+    #[test]
+    fn december_counts_toward_the_next_winter() {
+        assert_eq!(
+            season(NaiveDate::from_ymd_opt(2025, 12, 20).unwrap()),
+            ("Winter", 2026)
+        );
+        assert_eq!(
+            season(NaiveDate::from_ymd_opt(2026, 2, 28).unwrap()),
+            ("Winter", 2026)
+        );
+        assert_eq!(
+            season(NaiveDate::from_ymd_opt(2026, 3, 1).unwrap()),
+            ("Spring", 2026)
+        );
+
+        let trails = catalog();
+        let hikes = vec![
+            hike(1, "2025-11-30", &trails[2], 40.0),
+            hike(2, "2025-12-15", &trails[2], 40.0),
+            hike(3, "2026-01-10", &trails[2], 40.0),
+            hike(4, "2026-02-20", &trails[2], 30.0),
+        ];
+        let all = achievements(&hikes, &trails);
+        let century = earned(&all, "Century season").unwrap();
+        assert_eq!(century.detail, "Winter 2026");
+        assert_eq!(century.date, "2026-02-20");
+    }
+
+    #[test]
+    fn park_completed_through_other_trails() {
+        let trails = catalog();
+        let mut track = hike(1, "2025-08-01", &trails[0], 14.0);
+        track.other_trails = vec![TrailRef::of(&trails[1]), TrailRef::of(&trails[3])];
+        let first = vec![track];
+
+        let progress = park_progress(&trails, &first);
+        assert_eq!(progress.len(), 2);
+        assert!(progress[0].is_complete());
+        assert_eq!(progress[0].done_km(), 13.0);
+        assert!(!progress[1].is_complete());
+        assert_eq!(progress[1].remaining()[0].name, "Le Sommet");
+        assert_eq!(
+            earned(&achievements(&first, &trails), "Park complete")
+                .unwrap()
+                .detail,
+            "Parc A"
+        );
+
+        // Finishing Parc B from a hike logged in Parc A
+        let before_hikes = vec![hike(2, "2025-07-01", &trails[3], 3.0)];
+        let before = achievements(&before_hikes, &trails);
+        let mut crossing = hike(3, "2025-08-02", &trails[0], 17.0);
+        crossing.other_trails = vec![TrailRef::of(&trails[2])];
+        let mut all = before_hikes;
+        all.push(crossing);
+        let after = achievements(&all, &trails);
+        assert_eq!(earned(&after, "Park complete").unwrap().detail, "Parc B");
+        assert!(!park_progress(&trails, &all)[0].is_complete());
+
+        let new: Vec<&str> = newly_earned(&before, after)
+            .iter()
+            .map(|a| a.name)
+            .collect();
+        assert_eq!(new, ["Park complete"]);
+    }
+}
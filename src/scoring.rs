@@ -0,0 +1,403 @@
+use crate::config::Config;
+use crate::services::weather::DailyWeather;
+use crate::units;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PROFILE: &str = "summer";
+
+/// Score given to any day with a thunderstorm in the forecast, at most
+const STORM_CAP: f64 = 20.0;
+
+/// Share of ensemble members (%) that must score a day good for it to be recommended
+const LIKELY_GOOD: f64 = 60.0;
+
+/// Factor weights and comfort thresholds for rating a hiking day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringProfile {
+    pub precipitation: f64,
+    pub precipitation_probability: f64,
+    pub wind: f64,
+    pub temperature: f64,
+    pub uv: f64,
+    pub cloud_cover: f64,
+    pub daylight: f64,
+    /// Comfortable daytime high (°C)
+    pub comfort_min: f64,
+    pub comfort_max: f64,
+    /// Precipitation (mm) at which that factor scores zero
+    pub max_precipitation: f64,
+    /// Wind (km/h) at which that factor scores zero
+    pub max_wind: f64,
+    /// Preferred cloud cover (%)
+    pub ideal_cloud_cover: f64,
+    /// Daylight (h) at which that factor scores full marks
+    pub ideal_daylight: f64,
+}
+
+impl Default for ScoringProfile {
+    fn default() -> Self {
+        Self {
+            precipitation: 3.0,
+            precipitation_probability: 2.0,
+            wind: 2.0,
+            temperature: 2.0,
+            uv: 0.5,
+            cloud_cover: 1.0,
+            daylight: 0.5,
+            comfort_min: 12.0,
+            comfort_max: 25.0,
+            max_precipitation: 10.0,
+            max_wind: 40.0,
+            ideal_cloud_cover: 20.0,
+            ideal_daylight: 14.0,
+        }
+    }
+}
+
+impl ScoringProfile {
+    /// Built-in profiles: "summer", "winter-snowshoe" and "photography"
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "summer" => Some(Self::default()),
+            "winter-snowshoe" => Some(Self {
+                precipitation: 2.0,
+                temperature: 3.0,
+                uv: 0.0,
+                daylight: 1.5,
+                comfort_min: -12.0,
+                comfort_max: -2.0,
+                max_precipitation: 15.0,
+                max_wind: 30.0,
+                ideal_daylight: 10.0,
+                ..Self::default()
+            }),
+            "photography" => Some(Self {
+                precipitation_probability: 1.0,
+                temperature: 1.0,
+                cloud_cover: 3.0,
+                comfort_min: 0.0,
+                comfort_max: 28.0,
+                ideal_cloud_cover: 50.0,
+                ..Self::default()
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn builtin_names() -> &'static [&'static str] {
+        &["summer", "winter-snowshoe", "photography"]
+    }
+}
+
+/// Resolve a profile by name: `--profile`, then the config default, then "summer"
+pub fn resolve_profile(name: Option<&str>, config: &Config) -> Result<(String, ScoringProfile)> {
+    let name = name
+        .or(config.scoring_profile.as_deref())
+        .unwrap_or(DEFAULT_PROFILE);
+
+    let profile = config
+        .profiles
+        .get(name)
+        .cloned()
+        .or_else(|| ScoringProfile::builtin(name))
+        .ok_or_else(|| {
+            let mut known: Vec<&str> = ScoringProfile::builtin_names().to_vec();
+            known.extend(config.profiles.keys().map(String::as_str));
+            anyhow::anyhow!(
+                "Unknown scoring profile: '{}'. Available: {}",
+                name,
+                known.join(", ")
+            )
+        })?;
+
+    Ok((name.to_string(), profile))
+}
+
+/// Contribution of one weather factor to a day's score
+#[derive(Debug, Clone)]
+pub struct Factor {
+    pub name: &'static str,
+    pub value: String,
+    /// Points lost out of 100
+    pub penalty: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct DayScore {
+    /// 0-100
+    pub score: u8,
+    pub storm: bool,
+    pub factors: Vec<Factor>,
+}
+
+impl DayScore {
+    pub fn label(&self) -> &'static str {
+        match self.score {
+            80..=100 => "Excellent",
+            60..=79 => "Good",
+            40..=59 => "Okay",
+            _ => "Bad",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        if self.score >= 60 { "[OK]" } else { "[!]" }
+    }
+
+    pub fn is_good(&self) -> bool {
+        self.score >= 60
+    }
+
+    /// The factors that cost the most points, e.g. "rain 8mm -18, wind 35km/h -9"
+    pub fn explanation(&self) -> String {
+        if self.storm {
+            return "thunderstorm".to_string();
+        }
+
+        let mut worst: Vec<&Factor> = self.factors.iter().filter(|f| f.penalty >= 3.0).collect();
+        worst.sort_by(|a, b| b.penalty.total_cmp(&a.penalty));
+
+        if worst.is_empty() {
+            return "ideal conditions".to_string();
+        }
+
+        worst
+            .iter()
+            .take(2)
+            .map(|f| format!("{} {} -{:.0}", f.name, f.value, f.penalty))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Weighted score of a forecast day; factors missing from the forecast are left out
+pub fn score_day(day: &DailyWeather, profile: &ScoringProfile) -> DayScore {
+    let units = units::get();
+    let mut weighted: Vec<(&'static str, String, f64, f64)> = vec![
+        (
+            "rain",
+            units.precipitation(day.precipitation),
+            profile.precipitation,
+            1.0 - day.precipitation / profile.max_precipitation.max(0.1),
+        ),
+        (
+            "wind",
+            units.speed(day.wind_speed),
+            profile.wind,
+            1.0 - day.wind_speed / profile.max_wind.max(1.0),
+        ),
+        (
+            "temp",
+            units.temperature(day.max_temp),
+            profile.temperature,
+            comfort_score(day.max_temp, profile.comfort_min, profile.comfort_max),
+        ),
+        (
+            "daylight",
+            format!("{}h", units.number(day.daylight_hours, 1)),
+            profile.daylight,
+            day.daylight_hours / profile.ideal_daylight.max(1.0),
+        ),
+    ];
+
+    if let Some(probability) = day.precipitation_probability {
+        weighted.push((
+            "rain chance",
+            format!("{:.0}%", probability),
+            profile.precipitation_probability,
+            1.0 - probability / 100.0,
+        ));
+    }
+    if let Some(uv) = day.uv_index {
+        weighted.push((
+            "UV",
+            format!("{:.0}", uv),
+            profile.uv,
+            1.0 - (uv - 3.0) / 8.0,
+        ));
+    }
+    if let Some(cloud) = day.cloud_cover {
+        weighted.push((
+            "clouds",
+            format!("{:.0}%", cloud),
+            profile.cloud_cover,
+            1.0 - (cloud - profile.ideal_cloud_cover).abs() / 100.0,
+        ));
+    }
+
+    let total_weight: f64 = weighted.iter().map(|(_, _, w, _)| w.max(0.0)).sum();
+    let total_weight = total_weight.max(f64::EPSILON);
+
+    let factors: Vec<Factor> = weighted
+        .into_iter()
+        .map(|(name, value, weight, score)| Factor {
+            name,
+            value,
+            penalty: weight.max(0.0) * (1.0 - score.clamp(0.0, 1.0)) / total_weight * 100.0,
+        })
+        .collect();
+
+    let storm = day.weather_code >= 95;
+    let mut score = 100.0 - factors.iter().map(|f| f.penalty).sum::<f64>();
+    if storm {
+        score = score.min(STORM_CAP);
+    }
+
+    DayScore {
+        score: score.round().clamp(0.0, 100.0) as u8,
+        storm,
+        factors,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confidence {
+    High,
+    Medium,
+    Low,
+}
+
+impl Confidence {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Confidence::High => "high",
+            Confidence::Medium => "medium",
+            Confidence::Low => "low",
+        }
+    }
+}
+
+/// How a day scores across the ensemble members
+#[derive(Debug, Clone)]
+pub struct EnsembleScore {
+    /// Share of members (%) scoring the day good
+    pub good_chance: f64,
+    /// 10th and 90th percentile member scores
+    pub low: u8,
+    pub high: u8,
+}
+
+impl EnsembleScore {
+    /// Narrow score band means the members agree
+    pub fn confidence(&self) -> Confidence {
+        match self.high.saturating_sub(self.low) {
+            0..=14 => Confidence::High,
+            15..=29 => Confidence::Medium,
+            _ => Confidence::Low,
+        }
+    }
+
+    pub fn is_likely_good(&self) -> bool {
+        self.good_chance >= LIKELY_GOOD
+    }
+}
+
+/// Score every ensemble member's version of the day, if the forecast has members.
+/// Members only carry temperature, precipitation and wind, so the deterministic
+/// rain chance, storm code, UV and clouds are left out; daylight is the same for all.
+pub fn score_ensemble(day: &DailyWeather, profile: &ScoringProfile) -> Option<EnsembleScore> {
+    let ensemble = day.ensemble.as_ref().filter(|e| !e.members.is_empty())?;
+
+    let mut scores: Vec<u8> = ensemble
+        .members
+        .iter()
+        .map(|member| {
+            let member_day = DailyWeather {
+                max_temp: member.max_temp,
+                min_temp: member.min_temp,
+                precipitation: member.precipitation,
+                wind_speed: member.wind_speed,
+                precipitation_probability: None,
+                weather_code: 0,
+                uv_index: None,
+                cloud_cover: None,
+                ensemble: None,
+                ..day.clone()
+            };
+            score_day(&member_day, profile).score
+        })
+        .collect();
+    scores.sort_unstable();
+
+    let rank = |p: f64| scores[(p * (scores.len() - 1) as f64).round() as usize];
+    let good = scores.iter().filter(|&&s| s >= 60).count();
+
+    Some(EnsembleScore {
+        good_chance: good as f64 / scores.len() as f64 * 100.0,
+        low: rank(0.1),
+        high: rank(0.9),
+    })
+}
+
+/// 1 inside the comfort band, dropping by 0.1 per degree outside it
+fn comfort_score(temp: f64, min: f64, max: f64) -> f64 {
+    let distance = if temp < min {
+        min - temp
+    } else if temp > max {
+        temp - max
+    } else {
+        0.0
+    };
+    1.0 - distance / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::weather::{DailyEnsemble, EnsembleMember};
+    use chrono::NaiveDate;
+
+    fn stormy_day(members: Vec<EnsembleMember>) -> DailyWeather {
+        DailyWeather {
+            date: NaiveDate::from_ymd_opt(2025, 7, 10).unwrap(),
+            max_temp: 20.0,
+            min_temp: 12.0,
+            precipitation: 0.0,
+            precipitation_probability: Some(90.0),
+            wind_speed: 5.0,
+            weather_code: 95,
+            uv_index: Some(10.0),
+            cloud_cover: Some(100.0),
+            daylight_hours: 14.0,
+            ensemble: Some(DailyEnsemble { members }),
+        }
+    }
+
+    fn member(precipitation: f64) -> EnsembleMember {
+        EnsembleMember {
+            max_temp: 20.0,
+            min_temp: 12.0,
+            precipitation,
+            wind_speed: 5.0,
+        }
+    }
+
+    #[test]
+    fn members_ignore_deterministic_only_fields() {
+        let profile = ScoringProfile::default();
+        let day = stormy_day(vec![member(0.0); 5]);
+        assert_eq!(score_day(&day, &profile).score, 20);
+
+        // Calm, dry members score well despite the main run's storm code and rain chance
+        let ensemble = score_ensemble(&day, &profile).unwrap();
+        assert!(ensemble.low >= 90);
+        assert_eq!(ensemble.good_chance, 100.0);
+    }
+
+    #[test]
+    fn members_spread_on_their_own_fields() {
+        let profile = ScoringProfile::default();
+        let members = (0..10).map(|i| member(i as f64 * 2.0)).collect();
+        let ensemble = score_ensemble(&stormy_day(members), &profile).unwrap();
+        assert!(ensemble.low < ensemble.high);
+        assert!(ensemble.good_chance > 0.0 && ensemble.good_chance < 100.0);
+    }
+
+    #[test]
+    fn no_members_no_ensemble() {
+        let day = stormy_day(Vec::new());
+        assert!(score_ensemble(&day, &ScoringProfile::default()).is_none());
+    }
+}
@@ -0,0 +1,233 @@
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+use std::fs;
+
+/// Recorded Open-Meteo air-quality response to read instead of the API
+const AIR_QUALITY_FIXTURE_ENV: &str = "RANDO_AIR_QUALITY_FIXTURE";
+
+/// US AQI above which hiking is discouraged
+pub const AQI_WARNING: f64 = 100.0;
+
+#[derive(Debug, Deserialize)]
+struct AirQualityResponse {
+    current: CurrentAirQuality,
+    hourly: HourlyAirQuality,
+}
+
+#[derive(Debug, Deserialize)]
+struct CurrentAirQuality {
+    pm2_5: Option<f64>,
+    us_aqi: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HourlyAirQuality {
+    time: Vec<String>,
+    pm2_5: Vec<Option<f64>>,
+    us_aqi: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AqiLevel {
+    Good,
+    Moderate,
+    Sensitive,
+    Unhealthy,
+    VeryUnhealthy,
+    Hazardous,
+}
+
+impl AqiLevel {
+    /// US EPA AQI categories
+    pub fn from_aqi(aqi: f64) -> Self {
+        match aqi {
+            a if a <= 50.0 => AqiLevel::Good,
+            a if a <= 100.0 => AqiLevel::Moderate,
+            a if a <= 150.0 => AqiLevel::Sensitive,
+            a if a <= 200.0 => AqiLevel::Unhealthy,
+            a if a <= 300.0 => AqiLevel::VeryUnhealthy,
+            _ => AqiLevel::Hazardous,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AqiLevel::Good => "Good",
+            AqiLevel::Moderate => "Moderate",
+            AqiLevel::Sensitive => "Unhealthy for sensitive groups",
+            AqiLevel::Unhealthy => "Unhealthy",
+            AqiLevel::VeryUnhealthy => "Very unhealthy",
+            AqiLevel::Hazardous => "Hazardous",
+        }
+    }
+}
+
+/// Worst hourly values of one forecast day, None when no hour has one
+#[derive(Debug, Clone)]
+pub struct DailyAirQuality {
+    pub date: NaiveDate,
+    pub max_pm2_5: Option<f64>,
+    pub max_aqi: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AirQuality {
+    /// PM2.5 in µg/m³; None when the station or model has no current value
+    pub pm2_5: Option<f64>,
+    pub aqi: Option<f64>,
+    pub forecast: Vec<DailyAirQuality>,
+}
+
+impl AirQuality {
+    pub fn level(&self) -> Option<AqiLevel> {
+        self.aqi.map(AqiLevel::from_aqi)
+    }
+
+    /// Forecast days where smoke or pollution pushes the AQI over the warning threshold
+    pub fn smoky_days(&self) -> Vec<&DailyAirQuality> {
+        self.forecast
+            .iter()
+            .filter(|d| d.max_aqi.is_some_and(|aqi| aqi > AQI_WARNING))
+            .collect()
+    }
+
+    /// Whether now or any forecast day is over the warning threshold
+    pub fn needs_warning(&self) -> bool {
+        self.aqi.is_some_and(|aqi| aqi > AQI_WARNING) || !self.smoky_days().is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        let aqi = match (self.aqi, self.level()) {
+            (Some(aqi), Some(level)) => format!("AQI {:.0} ({})", aqi, level.label()),
+            _ => "AQI unknown".to_string(),
+        };
+        let pm2_5 = match self.pm2_5 {
+            Some(pm2_5) => format!("PM2.5 {:.0}µg/m³", pm2_5),
+            None => "PM2.5 unknown".to_string(),
+        };
+        format!("{}, {}", aqi, pm2_5)
+    }
+}
+
+/// Current air quality and the PM2.5/AQI forecast (wildfire smoke included) for a location
+pub fn get_air_quality(lat: f64, lng: f64) -> Result<AirQuality> {
+    let response = match std::env::var(AIR_QUALITY_FIXTURE_ENV) {
+        Ok(path) => read_fixture(&path)?,
+        Err(_) => {
+            let url = format!(
+                "https://air-quality-api.open-meteo.com/v1/air-quality?latitude={}&longitude={}&current=pm2_5,us_aqi&hourly=pm2_5,us_aqi&forecast_days=4&timezone=America/Toronto",
+                lat, lng
+            );
+            reqwest::blocking::get(&url)
+                .context("Failed to fetch air quality data")?
+                .json()
+                .context("Failed to parse air quality response")?
+        }
+    };
+
+    Ok(summarize(&response))
+}
+
+fn read_fixture(path: &str) -> Result<AirQualityResponse> {
+    let data = fs::read_to_string(path).context("Failed to read air quality fixture")?;
+    serde_json::from_str(&data).context("Failed to parse air quality fixture")
+}
+
+fn summarize(response: &AirQualityResponse) -> AirQuality {
+    let hourly = &response.hourly;
+    let mut forecast: Vec<DailyAirQuality> = Vec::new();
+
+    for (i, time) in hourly.time.iter().enumerate() {
+        let Ok(datetime) = NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M") else {
+            continue;
+        };
+        let pm2_5 = hourly.pm2_5.get(i).copied().flatten();
+        let aqi = hourly.us_aqi.get(i).copied().flatten();
+
+        match forecast.last_mut() {
+            Some(day) if day.date == datetime.date() => {
+                day.max_pm2_5 = max_known(day.max_pm2_5, pm2_5);
+                day.max_aqi = max_known(day.max_aqi, aqi);
+            }
+            _ => forecast.push(DailyAirQuality {
+                date: datetime.date(),
+                max_pm2_5: pm2_5,
+                max_aqi: aqi,
+            }),
+        }
+    }
+
+    AirQuality {
+        pm2_5: response.current.pm2_5,
+        aqi: response.current.us_aqi,
+        forecast,
+    }
+}
+
+/// Larger of two values, ignoring missing ones
+fn max_known(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        _ => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> AirQuality {
+        let path = format!(
+            "{}/tests/fixtures/air_quality/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        summarize(&read_fixture(&path).unwrap())
+    }
+
+    #[test]
+    fn summarizes_current_values_and_daily_maxima() {
+        let air = fixture("smoke.json");
+        assert_eq!(air.aqi, Some(132.0));
+        assert_eq!(air.pm2_5, Some(48.3));
+        assert_eq!(air.level(), Some(AqiLevel::Sensitive));
+        assert_eq!(
+            air.summary(),
+            "AQI 132 (Unhealthy for sensitive groups), PM2.5 48µg/m³"
+        );
+
+        assert_eq!(air.forecast.len(), 4);
+        assert_eq!(air.forecast[0].max_aqi, Some(83.0));
+        assert_eq!(air.forecast[1].max_aqi, Some(166.0));
+        assert_eq!(air.forecast[1].max_pm2_5, Some(86.0));
+        // Hours without data are ignored rather than read as 0
+        assert_eq!(air.forecast[2].max_aqi, None);
+        assert_eq!(air.forecast[3].max_aqi, Some(21.0));
+    }
+
+    #[test]
+    fn warns_on_smoky_days() {
+        let air = fixture("smoke.json");
+        let smoky: Vec<NaiveDate> = air.smoky_days().iter().map(|d| d.date).collect();
+        assert_eq!(smoky, vec![NaiveDate::from_ymd_opt(2025, 6, 6).unwrap()]);
+        assert!(air.needs_warning());
+    }
+
+    #[test]
+    fn missing_current_values_are_unknown() {
+        let air = fixture("missing_current.json");
+        assert_eq!(air.aqi, None);
+        assert_eq!(air.level(), None);
+        assert_eq!(air.summary(), "AQI unknown, PM2.5 unknown");
+        assert!(!air.needs_warning());
+    }
+
+    #[test]
+    fn aqi_categories() {
+        assert_eq!(AqiLevel::from_aqi(50.0), AqiLevel::Good);
+        assert_eq!(AqiLevel::from_aqi(100.0), AqiLevel::Moderate);
+        assert_eq!(AqiLevel::from_aqi(101.0), AqiLevel::Sensitive);
+        assert_eq!(AqiLevel::from_aqi(301.0), AqiLevel::Hazardous);
+    }
+}
@@ -0,0 +1,461 @@
+use crate::cache;
+use crate::geo::point_in_polygon;
+use crate::xml::{tag_blocks, tag_text, unescape_xml};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+use std::collections::HashSet;
+use std::fs;
+
+/// Environment Canada MSC Datamart CAP feed
+const CAP_BASE_URL: &str = "https://dd.weather.gc.ca/today/alerts/cap";
+
+/// Forecast office issuing alerts for Quebec
+const CAP_OFFICE: &str = "CWUL";
+
+/// Directory of recorded `.cap` files to read instead of the Datamart
+const CAP_DIR_ENV: &str = "RANDO_CAP_DIR";
+
+/// Hours after its end that an hourly directory may still get files
+const LATE_FILES_HOURS: i64 = 1;
+
+/// Cached messages and listings older than this are outside the fetched window
+const ALERT_CACHE_DAYS: u64 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlertKind {
+    Warning,
+    Watch,
+    Advisory,
+    Statement,
+    Other,
+}
+
+impl AlertKind {
+    fn from_cap(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "warning" => AlertKind::Warning,
+            "watch" => AlertKind::Watch,
+            "advisory" => AlertKind::Advisory,
+            "statement" => AlertKind::Statement,
+            _ => AlertKind::Other,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertKind::Warning => "Warning",
+            AlertKind::Watch => "Watch",
+            AlertKind::Advisory => "Advisory",
+            AlertKind::Statement => "Statement",
+            AlertKind::Other => "Alert",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertArea {
+    pub description: String,
+    /// Polygons as (lat, lng) rings
+    pub polygons: Vec<Vec<(f64, f64)>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WeatherAlert {
+    pub identifier: String,
+    pub sent: Option<DateTime<FixedOffset>>,
+    pub cancelled: bool,
+    /// Identifiers of the earlier messages this one replaces
+    pub references: Vec<String>,
+    pub kind: AlertKind,
+    pub event: String,
+    pub headline: String,
+    pub description: String,
+    pub severity: String,
+    pub effective: Option<DateTime<FixedOffset>>,
+    pub expires: Option<DateTime<FixedOffset>>,
+    pub areas: Vec<AlertArea>,
+}
+
+impl WeatherAlert {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        !self.cancelled && self.expires.is_none_or(|expires| expires > now)
+    }
+
+    /// Whether the alert covers a location, by polygon or by area name
+    pub fn covers(&self, lat: f64, lng: f64, place: &str) -> bool {
+        let place = place.to_lowercase();
+        self.areas.iter().any(|area| {
+            area.polygons
+                .iter()
+                .any(|polygon| point_in_polygon(lat, lng, polygon))
+                || (!place.is_empty() && area.description.to_lowercase().contains(&place))
+        })
+    }
+
+    pub fn area_names(&self) -> String {
+        self.areas
+            .iter()
+            .map(|a| a.description.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Parse a CAP 1.2 document into one alert per English `<info>` block
+pub fn parse_cap(xml: &str) -> Result<Vec<WeatherAlert>> {
+    let identifier = tag_text(xml, "identifier").context("CAP message has no identifier")?;
+    let sent = tag_text(xml, "sent").and_then(|s| parse_cap_time(&s));
+    let cancelled = tag_text(xml, "msgType").is_some_and(|t| t == "Cancel");
+
+    // References are "sender,identifier,sent" triples separated by spaces
+    let references = tag_text(xml, "references")
+        .map(|refs| {
+            refs.split_whitespace()
+                .filter_map(|r| r.split(',').nth(1))
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let alerts = tag_blocks(xml, "info")
+        .into_iter()
+        .filter(|info| {
+            tag_text(info, "language").is_none_or(|lang| lang.to_lowercase().starts_with("en"))
+        })
+        .map(|info| {
+            let kind = tag_blocks(info, "parameter")
+                .into_iter()
+                .find(|p| tag_text(p, "valueName").is_some_and(|n| n.ends_with("Alert_Type")))
+                .and_then(|p| tag_text(p, "value"))
+                .map(|v| AlertKind::from_cap(&v))
+                .unwrap_or(AlertKind::Other);
+
+            let areas = tag_blocks(info, "area")
+                .into_iter()
+                .map(|area| AlertArea {
+                    description: tag_text(area, "areaDesc").unwrap_or_default(),
+                    polygons: tag_blocks(area, "polygon")
+                        .into_iter()
+                        .filter_map(|p| parse_polygon(&unescape_xml(p)))
+                        .collect(),
+                })
+                .collect();
+
+            WeatherAlert {
+                identifier: identifier.clone(),
+                sent,
+                cancelled,
+                references: references.clone(),
+                kind,
+                event: tag_text(info, "event").unwrap_or_default(),
+                headline: tag_text(info, "headline").unwrap_or_default(),
+                description: tag_text(info, "description").unwrap_or_default(),
+                severity: tag_text(info, "severity").unwrap_or_default(),
+                effective: tag_text(info, "effective").and_then(|s| parse_cap_time(&s)),
+                expires: tag_text(info, "expires").and_then(|s| parse_cap_time(&s)),
+                areas,
+            }
+        })
+        .collect();
+
+    Ok(alerts)
+}
+
+/// Keep only alerts still in force: drop cancelled, expired and superseded messages
+pub fn active_alerts(mut alerts: Vec<WeatherAlert>, now: DateTime<Utc>) -> Vec<WeatherAlert> {
+    let superseded: HashSet<String> = alerts
+        .iter()
+        .flat_map(|a| a.references.iter().cloned())
+        .collect();
+
+    alerts.retain(|a| !superseded.contains(&a.identifier) && a.is_active(now));
+    alerts.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| b.sent.cmp(&a.sent)));
+    alerts
+}
+
+/// Fetch and parse every active alert for Quebec
+pub fn get_active_alerts() -> Result<Vec<WeatherAlert>> {
+    let documents = match std::env::var(CAP_DIR_ENV) {
+        Ok(dir) => read_cap_dir(&dir)?,
+        Err(_) => fetch_cap_documents()?,
+    };
+
+    let mut alerts = Vec::new();
+    for xml in &documents {
+        // A malformed message should not hide the others
+        if let Ok(parsed) = parse_cap(xml) {
+            alerts.extend(parsed);
+        }
+    }
+
+    Ok(active_alerts(alerts, Utc::now()))
+}
+
+/// Active alerts covering a trail's location or park
+pub fn get_alerts_for_location(lat: f64, lng: f64, park: &str) -> Result<Vec<WeatherAlert>> {
+    Ok(get_active_alerts()?
+        .into_iter()
+        .filter(|a| a.covers(lat, lng, park))
+        .collect())
+}
+
+fn read_cap_dir(dir: &str) -> Result<Vec<String>> {
+    let mut documents = Vec::new();
+    for entry in fs::read_dir(dir).context("Failed to read CAP directory")? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|ext| ext == "cap" || ext == "xml")
+        {
+            documents.push(fs::read_to_string(&path).context("Failed to read CAP file")?);
+        }
+    }
+    Ok(documents)
+}
+
+/// Download the last two days of CAP messages, reusing files already cached.
+/// Hours that are over keep their listing in the cache, so only the current
+/// ones are listed again; an hour or file that fails is skipped
+fn fetch_cap_documents() -> Result<Vec<String>> {
+    let now = Utc::now();
+    let today = now.date_naive();
+    let mut documents = Vec::new();
+    let mut listed_any = false;
+    let mut listing_error = None;
+
+    cache::prune_alerts(std::time::Duration::from_secs(ALERT_CACHE_DAYS * 24 * 3600));
+
+    for date in [today - Duration::days(1), today] {
+        let day = date.format("%Y%m%d").to_string();
+        let day_url = format!("{}/{}/{}/", CAP_BASE_URL, day, CAP_OFFICE);
+        // Yesterday's directory may already have been purged
+        let hours = match list_directory(&day_url) {
+            Ok(hours) => hours,
+            Err(e) => {
+                listing_error = Some(e);
+                continue;
+            }
+        };
+        listed_any = true;
+
+        for hour in hours.iter().filter_map(|h| h.strip_suffix('/')) {
+            let hour_url = format!("{}{}/", day_url, hour);
+            let listing_key = format!("{}_{}", day, hour);
+            let files = match cache::get_cached_alert_listing(&listing_key) {
+                Some(files) => files,
+                None => {
+                    let Ok(files) = list_directory(&hour_url) else {
+                        continue;
+                    };
+                    let files: Vec<String> =
+                        files.into_iter().filter(|f| f.ends_with(".cap")).collect();
+                    if hour_is_over(date, hour, now) {
+                        let _ = cache::cache_alert_listing(&listing_key, &files);
+                    }
+                    files
+                }
+            };
+
+            for file in &files {
+                if let Some(cached) = cache::get_cached_alert(file) {
+                    documents.push(cached);
+                    continue;
+                }
+
+                let Ok(xml) = reqwest::blocking::get(format!("{}{}", hour_url, file))
+                    .and_then(|r| r.error_for_status())
+                    .and_then(|r| r.text())
+                else {
+                    continue;
+                };
+                // Ignore cache errors - non-critical
+                let _ = cache::cache_alert(file, &xml);
+                documents.push(xml);
+            }
+        }
+    }
+
+    match listing_error {
+        Some(e) if !listed_any => Err(e),
+        _ => Ok(documents),
+    }
+}
+
+/// Whether an hourly directory ("00" to "23", UTC) can no longer get new files
+fn hour_is_over(date: NaiveDate, hour: &str, now: DateTime<Utc>) -> bool {
+    hour.parse::<u32>()
+        .ok()
+        .and_then(|h| date.and_hms_opt(h, 0, 0))
+        .is_some_and(|start| start.and_utc() + Duration::hours(1 + LATE_FILES_HOURS) <= now)
+}
+
+/// Relative links of a Datamart directory listing
+fn list_directory(url: &str) -> Result<Vec<String>> {
+    let html = reqwest::blocking::get(url)
+        .context("Failed to fetch alert listing")?
+        .error_for_status()
+        .context("Alert listing unavailable")?
+        .text()
+        .context("Failed to read alert listing")?;
+
+    Ok(html
+        .split("href=\"")
+        .skip(1)
+        .filter_map(|s| s.split('"').next())
+        .filter(|link| !link.starts_with(['/', '?', '.']) && !link.contains("://"))
+        .map(str::to_string)
+        .collect())
+}
+
+/// CAP polygons are "lat,lng lat,lng ..." rings
+fn parse_polygon(text: &str) -> Option<Vec<(f64, f64)>> {
+    let points: Vec<(f64, f64)> = text
+        .split_whitespace()
+        .filter_map(|pair| {
+            let (lat, lng) = pair.split_once(',')?;
+            Some((lat.parse().ok()?, lng.parse().ok()?))
+        })
+        .collect();
+
+    (points.len() >= 3).then_some(points)
+}
+
+fn parse_cap_time(s: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(s.trim()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/cap");
+
+    /// Inside the Jacques-Cartier polygon of the fixtures
+    const PARK: (f64, f64) = (47.2, -71.4);
+
+    fn fixture(name: &str) -> String {
+        fs::read_to_string(format!("{}/{}", FIXTURES, name)).unwrap()
+    }
+
+    fn now() -> DateTime<Utc> {
+        "2025-07-10T18:00:00Z".parse().unwrap()
+    }
+
+    fn all_fixtures() -> Vec<WeatherAlert> {
+        read_cap_dir(FIXTURES)
+            .unwrap()
+            .iter()
+            .filter_map(|xml| parse_cap(xml).ok())
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn parses_english_infos_of_a_multi_info_document() {
+        let alerts = parse_cap(&fixture("rainfall_warning.cap")).unwrap();
+
+        // Four <info> blocks: two areas, each in English and French
+        assert_eq!(alerts.len(), 2);
+        for alert in &alerts {
+            assert_eq!(alert.identifier, "urn:oid:2.49.0.1.124.1002003004.2025");
+            assert_eq!(alert.kind, AlertKind::Warning);
+            assert_eq!(alert.headline, "rainfall warning in effect");
+            assert_eq!(alert.severity, "Moderate");
+            assert!(!alert.cancelled);
+            assert!(alert.references.is_empty());
+            assert_eq!(alert.areas.len(), 1);
+            assert_eq!(alert.areas[0].polygons[0].len(), 5);
+        }
+        assert_eq!(
+            alerts[0].expires.unwrap(),
+            "2025-07-11T12:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert!(alerts[0].description.contains("50 to 80 mm"));
+        assert!(alerts[1].description.contains("30 to 50 mm"));
+    }
+
+    #[test]
+    fn parses_references_and_cancellations() {
+        let update = parse_cap(&fixture("wind_statement_update.cap")).unwrap();
+        assert_eq!(
+            update[0].references,
+            vec!["urn:oid:2.49.0.1.124.2000000001.2025"]
+        );
+        assert_eq!(update[0].kind, AlertKind::Statement);
+        // Escaped text is unescaped
+        assert!(update[0].description.contains("90 km/h & higher"));
+
+        let cancel = parse_cap(&fixture("thunderstorm_watch_cancel.cap")).unwrap();
+        assert!(cancel[0].cancelled);
+        assert!(!cancel[0].is_active(now()));
+    }
+
+    #[test]
+    fn rejects_a_document_without_identifier() {
+        assert!(parse_cap(&fixture("malformed.cap")).is_err());
+    }
+
+    #[test]
+    fn covers_by_polygon_or_area_name() {
+        let alerts = parse_cap(&fixture("rainfall_warning.cap")).unwrap();
+        assert!(alerts[0].covers(PARK.0, PARK.1, ""));
+        assert!(!alerts[1].covers(PARK.0, PARK.1, ""));
+        assert!(alerts[0].covers(0.0, 0.0, "Jacques-Cartier"));
+
+        let gaspe = parse_cap(&fixture("snowfall_warning_gaspe.cap")).unwrap();
+        assert!(!gaspe[0].covers(PARK.0, PARK.1, "Parc national de la Jacques-Cartier"));
+    }
+
+    #[test]
+    fn keeps_only_alerts_in_force() {
+        let active = active_alerts(all_fixtures(), now());
+        let identifiers: Vec<&str> = active.iter().map(|a| a.identifier.as_str()).collect();
+
+        // Warnings first, newest first; the expired advisory, the superseded
+        // statement and the cancelled watch are gone
+        assert_eq!(
+            identifiers,
+            vec![
+                "urn:oid:2.49.0.1.124.4000000001.2025",
+                "urn:oid:2.49.0.1.124.1002003004.2025",
+                "urn:oid:2.49.0.1.124.1002003004.2025",
+                "urn:oid:2.49.0.1.124.2000000002.2025",
+            ]
+        );
+    }
+
+    #[test]
+    fn expired_alerts_were_active_before_expiry() {
+        let before: DateTime<Utc> = "2025-07-10T06:00:00Z".parse().unwrap();
+        let frost = parse_cap(&fixture("frost_advisory_expired.cap")).unwrap();
+        assert!(frost[0].is_active(before));
+        assert!(!frost[0].is_active(now()));
+    }
+
+    #[test]
+    fn alerts_for_the_park_leave_out_other_regions() {
+        let covering: Vec<String> = active_alerts(all_fixtures(), now())
+            .into_iter()
+            .filter(|a| a.covers(PARK.0, PARK.1, "Parc national de la Jacques-Cartier"))
+            .map(|a| a.headline)
+            .collect();
+
+        assert_eq!(
+            covering,
+            vec![
+                "rainfall warning in effect",
+                "special weather statement continued"
+            ]
+        );
+    }
+
+    #[test]
+    fn hour_directories_close_after_late_files() {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 10).unwrap();
+        assert!(hour_is_over(date, "15", now()));
+        assert!(hour_is_over(date, "16", now()));
+        assert!(!hour_is_over(date, "17", now()));
+        assert!(!hour_is_over(date, "18", now()));
+        assert!(!hour_is_over(date, "latest", now()));
+    }
+}
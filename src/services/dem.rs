@@ -0,0 +1,511 @@
+use anyhow::{Context, Result};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::tags::Tag;
+
+/// SRTM void marker
+const HGT_VOID: i16 = -32768;
+
+/// GeoKey IDs and values (GeoTIFF 1.0, section 6)
+const GT_MODEL_TYPE_KEY: u16 = 1024;
+const GT_RASTER_TYPE_KEY: u16 = 1025;
+const GEOGRAPHIC_TYPE_KEY: u16 = 2048;
+const PROJECTED_CS_TYPE_KEY: u16 = 3072;
+const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+const RASTER_PIXEL_IS_POINT: u16 = 2;
+const EPSG_WGS84: u16 = 4326;
+
+/// Extensions of the tiles `LocalDem` reads
+pub const TILE_EXTENSIONS: [&str; 3] = ["hgt", "tif", "tiff"];
+
+/// Public mirror of 1° SRTM tiles (gzipped .hgt)
+const SKADI_URL: &str = "https://s3.amazonaws.com/elevation-tiles-prod/skadi";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TileFormat {
+    Hgt,
+    GeoTiff,
+}
+
+/// Bounds of a tile's sample grid, in degrees
+#[derive(Debug, Clone, Copy)]
+struct Bounds {
+    /// Longitude of the first column and latitude of the first row (sample centres)
+    west: f64,
+    north: f64,
+    /// Degrees between samples
+    x_step: f64,
+    y_step: f64,
+    width: usize,
+    height: usize,
+}
+
+impl Bounds {
+    fn contains(&self, lat: f64, lng: f64) -> bool {
+        let east = self.west + self.x_step * (self.width - 1) as f64;
+        let south = self.north - self.y_step * (self.height - 1) as f64;
+        (self.west..=east).contains(&lng) && (south..=self.north).contains(&lat)
+    }
+}
+
+#[derive(Debug)]
+struct TileEntry {
+    path: PathBuf,
+    format: TileFormat,
+    bounds: Bounds,
+}
+
+/// Elevation samples of one tile, row by row from the north
+struct Tile {
+    bounds: Bounds,
+    data: Vec<f32>,
+    nodata: Option<f32>,
+}
+
+impl Tile {
+    fn value(&self, row: usize, col: usize) -> Option<f64> {
+        let v = *self.data.get(row * self.bounds.width + col)?;
+        if v.is_nan() || self.nodata == Some(v) {
+            None
+        } else {
+            Some(f64::from(v))
+        }
+    }
+
+    /// Bilinear interpolation between the four surrounding samples
+    /// Void samples are left out and the remaining weights renormalized
+    fn sample(&self, lat: f64, lng: f64) -> Option<f64> {
+        let b = &self.bounds;
+        let x = ((lng - b.west) / b.x_step).clamp(0.0, (b.width - 1) as f64);
+        let y = ((b.north - lat) / b.y_step).clamp(0.0, (b.height - 1) as f64);
+
+        let col = (x.floor() as usize).min(b.width.saturating_sub(2));
+        let row = (y.floor() as usize).min(b.height.saturating_sub(2));
+        let fx = x - col as f64;
+        let fy = y - row as f64;
+
+        let corners = [
+            (row, col, (1.0 - fx) * (1.0 - fy)),
+            (row, col + 1, fx * (1.0 - fy)),
+            (row + 1, col, (1.0 - fx) * fy),
+            (row + 1, col + 1, fx * fy),
+        ];
+
+        let (sum, weight) = corners
+            .iter()
+            .filter_map(|&(r, c, w)| self.value(r, c).map(|v| (v * w, w)))
+            .fold((0.0, 0.0), |(s, tw), (v, w)| (s + v, tw + w));
+
+        (weight > 0.0).then(|| sum / weight)
+    }
+}
+
+/// Reads SRTM `.hgt` and GeoTIFF (EPSG:4326) tiles from a local directory
+pub struct LocalDem {
+    entries: Vec<TileEntry>,
+    loaded: RefCell<HashMap<usize, Tile>>,
+}
+
+impl LocalDem {
+    /// Index the tiles in `dir`; tile data is only read when first sampled
+    /// GeoTIFFs that aren't in WGS84 lat/lng are an error rather than skipped
+    pub fn open(dir: &Path) -> Result<Self> {
+        let mut entries = Vec::new();
+        let files = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read DEM directory {}", dir.display()))?;
+
+        for path in files.flatten().map(|e| e.path()) {
+            let extension = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(str::to_lowercase);
+            let entry = match extension.as_deref() {
+                Some("hgt") => hgt_bounds(&path).map(|bounds| TileEntry {
+                    path: path.clone(),
+                    format: TileFormat::Hgt,
+                    bounds,
+                }),
+                Some("tif") | Some("tiff") => Some(TileEntry {
+                    path: path.clone(),
+                    format: TileFormat::GeoTiff,
+                    bounds: geotiff_bounds(&path)?,
+                }),
+                _ => None,
+            };
+            entries.extend(entry);
+        }
+
+        Ok(Self {
+            entries,
+            loaded: RefCell::new(HashMap::new()),
+        })
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn covers(&self, lat: f64, lng: f64) -> bool {
+        self.entries.iter().any(|e| e.bounds.contains(lat, lng))
+    }
+
+    /// Elevation in metres, or None when no tile covers the point or it is void
+    pub fn elevation(&self, lat: f64, lng: f64) -> Result<Option<f64>> {
+        let Some(index) = self
+            .entries
+            .iter()
+            .position(|e| e.bounds.contains(lat, lng))
+        else {
+            return Ok(None);
+        };
+
+        let mut loaded = self.loaded.borrow_mut();
+        let tile = match loaded.entry(index) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(slot) => {
+                let entry = &self.entries[index];
+                slot.insert(match entry.format {
+                    TileFormat::Hgt => read_hgt(&entry.path, entry.bounds)?,
+                    TileFormat::GeoTiff => read_geotiff(&entry.path, entry.bounds)?,
+                })
+            }
+        };
+
+        Ok(tile.sample(lat, lng))
+    }
+}
+
+/// SRTM tile name of the 1° cell containing a point, e.g. "N46W072"
+pub fn tile_name(lat: f64, lng: f64) -> String {
+    let lat = lat.floor() as i32;
+    let lng = lng.floor() as i32;
+    format!(
+        "{}{:02}{}{:03}",
+        if lat >= 0 { 'N' } else { 'S' },
+        lat.abs(),
+        if lng >= 0 { 'E' } else { 'W' },
+        lng.abs()
+    )
+}
+
+/// Whether `dir` holds the tile, as `.hgt` or GeoTIFF
+pub fn has_tile(dir: &Path, name: &str) -> bool {
+    TILE_EXTENSIONS
+        .iter()
+        .any(|extension| dir.join(format!("{}.{}", name, extension)).exists())
+}
+
+/// Download URL of a gzipped SRTM tile
+pub fn tile_url(name: &str) -> String {
+    format!("{}/{}/{}.hgt.gz", SKADI_URL, &name[..3], name)
+}
+
+/// Parse the south-west corner from a name like "N46W072.hgt"
+fn parse_tile_name(stem: &str) -> Option<(f64, f64)> {
+    let stem = stem.get(..7)?.to_uppercase();
+    let lat: f64 = stem.get(1..3)?.parse().ok()?;
+    let lng: f64 = stem.get(4..7)?.parse().ok()?;
+    let lat = match &stem[..1] {
+        "N" => lat,
+        "S" => -lat,
+        _ => return None,
+    };
+    let lng = match &stem[3..4] {
+        "E" => lng,
+        "W" => -lng,
+        _ => return None,
+    };
+    Some((lat, lng))
+}
+
+fn hgt_bounds(path: &Path) -> Option<Bounds> {
+    let (south, west) = parse_tile_name(path.file_stem()?.to_str()?)?;
+    let samples = fs::metadata(path).ok()?.len() / 2;
+    // 1201x1201 (3") or 3601x3601 (1") samples, overlapping neighbours by one
+    let size = (samples as f64).sqrt().round() as usize;
+    if size * size != samples as usize || size < 2 {
+        return None;
+    }
+    let step = 1.0 / (size - 1) as f64;
+
+    Some(Bounds {
+        west,
+        north: south + 1.0,
+        x_step: step,
+        y_step: step,
+        width: size,
+        height: size,
+    })
+}
+
+fn read_hgt(path: &Path, bounds: Bounds) -> Result<Tile> {
+    let bytes =
+        fs::read(path).with_context(|| format!("Failed to read DEM tile {}", path.display()))?;
+    let data = bytes
+        .chunks_exact(2)
+        .map(|b| i16::from_be_bytes([b[0], b[1]]) as f32)
+        .collect();
+
+    Ok(Tile {
+        bounds,
+        data,
+        nodata: Some(f32::from(HGT_VOID)),
+    })
+}
+
+fn open_tiff(path: &Path) -> Result<Decoder<BufReader<File>>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open DEM tile {}", path.display()))?;
+    Decoder::new(BufReader::new(file))
+        .with_context(|| format!("Failed to read GeoTIFF {}", path.display()))
+}
+
+/// Short values of a GeoKey directory, by key ID
+/// Keys stored in other tags (doubles, ASCII) are left out
+fn geo_keys(directory: &[u16]) -> HashMap<u16, u16> {
+    directory
+        .get(4..)
+        .unwrap_or_default()
+        .chunks_exact(4)
+        .filter(|key| key[1] == 0 && key[2] == 1)
+        .map(|key| (key[0], key[3]))
+        .collect()
+}
+
+/// Sampling only maps degrees onto the grid, so other coordinate systems
+/// would give wrong elevations rather than none
+fn check_wgs84(keys: &HashMap<u16, u16>, path: &Path) -> Result<()> {
+    let reproject =
+        "only WGS84 lat/lng (EPSG:4326) is supported; reproject it with gdalwarp -t_srs EPSG:4326";
+    if let Some(code) = keys.get(&PROJECTED_CS_TYPE_KEY) {
+        anyhow::bail!(
+            "GeoTIFF {} is projected (EPSG:{}): {}",
+            path.display(),
+            code,
+            reproject
+        );
+    }
+    match (keys.get(&GT_MODEL_TYPE_KEY), keys.get(&GEOGRAPHIC_TYPE_KEY)) {
+        (Some(&MODEL_TYPE_GEOGRAPHIC), Some(&EPSG_WGS84)) => Ok(()),
+        (_, Some(code)) => anyhow::bail!(
+            "GeoTIFF {} uses EPSG:{}: {}",
+            path.display(),
+            code,
+            reproject
+        ),
+        _ => anyhow::bail!(
+            "GeoTIFF {} doesn't declare its coordinate system: {}",
+            path.display(),
+            reproject
+        ),
+    }
+}
+
+/// Grid from the ModelTiepoint and ModelPixelScale tags, after checking the GeoKeys
+fn geotiff_bounds(path: &Path) -> Result<Bounds> {
+    let mut decoder = open_tiff(path)?;
+    let keys = geo_keys(
+        &decoder
+            .get_tag_u16_vec(Tag::GeoKeyDirectoryTag)
+            .unwrap_or_default(),
+    );
+    check_wgs84(&keys, path)?;
+
+    let (width, height) = decoder.dimensions()?;
+    let scale = decoder.get_tag_f64_vec(Tag::ModelPixelScaleTag)?;
+    let tiepoint = decoder.get_tag_f64_vec(Tag::ModelTiepointTag)?;
+    if scale.len() < 2 || tiepoint.len() < 6 || width < 2 || height < 2 {
+        anyhow::bail!("Unsupported GeoTIFF georeferencing in {}", path.display());
+    }
+
+    // The tie point maps raster (i, j) to the corner of that pixel, unless the
+    // raster is PixelIsPoint; use pixel centres
+    let (x_step, y_step) = (scale[0], scale[1]);
+    let half = if keys.get(&GT_RASTER_TYPE_KEY) == Some(&RASTER_PIXEL_IS_POINT) {
+        0.0
+    } else {
+        0.5
+    };
+    let west = tiepoint[3] - (tiepoint[0] - half) * x_step;
+    let north = tiepoint[4] + (tiepoint[1] - half) * y_step;
+
+    Ok(Bounds {
+        west,
+        north,
+        x_step,
+        y_step,
+        width: width as usize,
+        height: height as usize,
+    })
+}
+
+fn read_geotiff(path: &Path, bounds: Bounds) -> Result<Tile> {
+    let mut decoder = open_tiff(path)?;
+    let nodata = decoder
+        .get_tag_ascii_string(Tag::GdalNodata)
+        .ok()
+        .and_then(|s| s.trim_matches(char::from(0)).trim().parse::<f32>().ok());
+
+    let data: Vec<f32> = match decoder.read_image()? {
+        DecodingResult::I16(v) => v.into_iter().map(f32::from).collect(),
+        DecodingResult::U16(v) => v.into_iter().map(f32::from).collect(),
+        DecodingResult::I32(v) => v.into_iter().map(|x| x as f32).collect(),
+        DecodingResult::F32(v) => v,
+        DecodingResult::F64(v) => v.into_iter().map(|x| x as f32).collect(),
+        _ => anyhow::bail!("Unsupported GeoTIFF sample type in {}", path.display()),
+    };
+
+    Ok(Tile {
+        bounds,
+        data,
+        nodata,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tiff::encoder::{TiffEncoder, colortype};
+
+    /// Empty scratch directory for one test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rando-dem-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 3x3 samples, half a degree apart, north row first; the south-east corner is void
+    const SAMPLES: [i16; 9] = [100, 200, 300, 400, 500, 600, 700, 800, -32768];
+
+    fn write_hgt(dir: &Path, name: &str, samples: &[i16]) -> PathBuf {
+        let path = dir.join(name);
+        let bytes: Vec<u8> = samples.iter().flat_map(|v| v.to_be_bytes()).collect();
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    /// Same grid as `SAMPLES` as a GeoTIFF, with -9999 for the void
+    fn write_geotiff(dir: &Path, geo_keys: &[u16]) -> PathBuf {
+        let path = dir.join("N46W072.tif");
+        let mut samples = SAMPLES;
+        samples[8] = -9999;
+        let file = File::create(&path).unwrap();
+        let mut encoder = TiffEncoder::new(file).unwrap();
+        let mut image = encoder.new_image::<colortype::GrayI16>(3, 3).unwrap();
+        let tags = image.encoder();
+        tags.write_tag(Tag::ModelPixelScaleTag, &[0.5, 0.5, 0.0][..])
+            .unwrap();
+        // Corner of the first pixel, so its centre falls on 47°N 72°W
+        tags.write_tag(
+            Tag::ModelTiepointTag,
+            &[0.0, 0.0, 0.0, -72.25, 47.25, 0.0][..],
+        )
+        .unwrap();
+        tags.write_tag(Tag::GeoKeyDirectoryTag, geo_keys).unwrap();
+        tags.write_tag(Tag::GdalNodata, "-9999").unwrap();
+        image.write_data(&samples).unwrap();
+        path
+    }
+
+    fn assert_samples(dem: &LocalDem) {
+        let at = |lat, lng| dem.elevation(lat, lng).unwrap();
+        // Corners
+        assert_eq!(at(47.0, -72.0), Some(100.0));
+        assert_eq!(at(46.0, -72.0), Some(700.0));
+        assert_eq!(at(47.0, -71.0), Some(300.0));
+        // Centre sample, then between four samples and along an edge
+        assert_eq!(at(46.5, -71.5), Some(500.0));
+        assert_eq!(at(46.75, -71.75), Some(300.0));
+        assert_eq!(at(47.0, -71.25), Some(250.0));
+        assert_eq!(at(46.0, -71.75), Some(750.0));
+        // The void sample is left out of the weights
+        let near_void = at(46.25, -71.25).unwrap();
+        assert!((near_void - 1900.0 / 3.0).abs() < 1e-9);
+        assert_eq!(at(46.0, -71.0), None);
+        // Outside the tile
+        assert_eq!(at(45.9, -71.5), None);
+        assert!(dem.covers(46.2, -71.2));
+        assert!(!dem.covers(46.2, -70.9));
+    }
+
+    #[test]
+    fn tiles_are_named_by_south_west_corner() {
+        assert_eq!(tile_name(46.8, -71.2), "N46W072");
+        assert_eq!(tile_name(46.0, -72.0), "N46W072");
+        assert_eq!(tile_name(-0.5, 0.5), "S01E000");
+        assert_eq!(parse_tile_name("N46W072"), Some((46.0, -72.0)));
+        assert_eq!(parse_tile_name("s01e000"), Some((-1.0, 0.0)));
+        assert_eq!(parse_tile_name("X46W072"), None);
+        assert_eq!(parse_tile_name("N46"), None);
+        assert_eq!(
+            tile_url("N46W072"),
+            format!("{}/N46/N46W072.hgt.gz", SKADI_URL)
+        );
+    }
+
+    #[test]
+    fn hgt_size_sets_the_resolution() {
+        let dir = scratch_dir("hgt-size");
+        let bounds = hgt_bounds(&write_hgt(&dir, "N46W072.hgt", &SAMPLES)).unwrap();
+        assert_eq!((bounds.width, bounds.height), (3, 3));
+        assert_eq!(
+            (bounds.west, bounds.north, bounds.x_step),
+            (-72.0, 47.0, 0.5)
+        );
+
+        // 3" SRTM: 1201 x 1201 samples
+        let srtm3 = write_hgt(&dir, "N45W073.hgt", &vec![0; 1201 * 1201]);
+        assert_eq!(hgt_bounds(&srtm3).unwrap().x_step, 1.0 / 1200.0);
+
+        assert!(hgt_bounds(&write_hgt(&dir, "N44W072.hgt", &[0; 5])).is_none());
+        assert!(hgt_bounds(&write_hgt(&dir, "tile.hgt", &SAMPLES)).is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn hgt_samples_interpolate_bilinearly() {
+        let dir = scratch_dir("hgt-samples");
+        write_hgt(&dir, "N46W072.hgt", &SAMPLES);
+        let dem = LocalDem::open(&dir).unwrap();
+        assert_eq!(dem.tile_count(), 1);
+        assert_samples(&dem);
+        assert!(has_tile(&dir, "N46W072"));
+        assert!(!has_tile(&dir, "N45W072"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn geotiff_in_wgs84_reads_like_hgt() {
+        let dir = scratch_dir("geotiff");
+        write_geotiff(&dir, &[1, 1, 0, 2, 1024, 0, 1, 2, 2048, 0, 1, 4326]);
+        let dem = LocalDem::open(&dir).unwrap();
+        assert_samples(&dem);
+        assert!(has_tile(&dir, "N46W072"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn geotiff_in_another_crs_is_rejected() {
+        let dir = scratch_dir("geotiff-utm");
+        // UTM zone 18N
+        write_geotiff(&dir, &[1, 1, 0, 2, 1024, 0, 1, 1, 3072, 0, 1, 32618]);
+        let error = LocalDem::open(&dir).err().unwrap().to_string();
+        assert!(error.contains("EPSG:32618"), "{}", error);
+
+        // NAD27 lat/lng
+        write_geotiff(&dir, &[1, 1, 0, 2, 1024, 0, 1, 2, 2048, 0, 1, 4267]);
+        let error = LocalDem::open(&dir).err().unwrap().to_string();
+        assert!(error.contains("EPSG:4267"), "{}", error);
+
+        write_geotiff(&dir, &[1, 1, 0, 0]);
+        let error = LocalDem::open(&dir).err().unwrap().to_string();
+        assert!(error.contains("doesn't declare"), "{}", error);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,17 +1,98 @@
+use super::dem::{LocalDem, tile_name};
+use crate::cache;
+use crate::config::load_config;
+use crate::geo::cumulative_distances;
+use crate::trails::Trail;
 use anyhow::{Context, Result};
 use serde::Deserialize;
+use std::path::Path;
 
 /// Maximum points allowed per Open-Meteo elevation API request
 const ELEVATION_BATCH_SIZE: usize = 100;
 
-/// Default number of sample points for elevation profiles
-pub const DEFAULT_SAMPLE_POINTS: usize = 100;
+/// Distance (m) between elevation samples along a trail
+const SAMPLE_SPACING_M: f64 = 75.0;
+
+/// Most samples taken along one trail; longer trails are sampled more sparsely
+const MAX_SAMPLE_POINTS: usize = 1000;
+
+/// Distance (m) covered by the moving-average window
+const SMOOTHING_WINDOW_M: f64 = 150.0;
 
 #[derive(Deserialize)]
 struct ElevationResponse {
     elevation: Vec<f64>,
 }
 
+/// Source of elevations for (lat, lng) coordinates, in metres
+pub trait ElevationProvider {
+    fn elevations(&self, coordinates: &[(f64, f64)]) -> Result<Vec<f64>>;
+}
+
+/// Open-Meteo elevation API (Copernicus 90m DEM)
+pub struct OpenMeteoElevation;
+
+impl ElevationProvider for OpenMeteoElevation {
+    fn elevations(&self, coordinates: &[(f64, f64)]) -> Result<Vec<f64>> {
+        fetch_elevation(coordinates)
+    }
+}
+
+/// SRTM/GeoTIFF tiles in a local directory, no network needed
+pub struct LocalDemElevation {
+    dem: LocalDem,
+}
+
+impl LocalDemElevation {
+    pub fn open(dir: &Path) -> Result<Self> {
+        let dem = LocalDem::open(dir)?;
+        if dem.tile_count() == 0 {
+            anyhow::bail!("No .hgt or .tif DEM tiles found in {}", dir.display());
+        }
+        Ok(Self { dem })
+    }
+}
+
+impl ElevationProvider for LocalDemElevation {
+    fn elevations(&self, coordinates: &[(f64, f64)]) -> Result<Vec<f64>> {
+        let mut elevations = Vec::with_capacity(coordinates.len());
+        let mut last = None;
+
+        for &(lat, lng) in coordinates {
+            let elevation = match self.dem.elevation(lat, lng)? {
+                Some(elevation) => elevation,
+                // Void sample inside a tile: carry the previous point over
+                None if self.dem.covers(lat, lng) => {
+                    last.with_context(|| format!("DEM has no data at {:.5}, {:.5}", lat, lng))?
+                }
+                None => anyhow::bail!(
+                    "No DEM tile covers {:.5}, {:.5} ({} missing, see rando dem fetch-list)",
+                    lat,
+                    lng,
+                    tile_name(lat, lng)
+                ),
+            };
+            last = Some(elevation);
+            elevations.push(elevation);
+        }
+
+        Ok(elevations)
+    }
+}
+
+/// Whether elevations come from the network rather than local tiles
+pub fn uses_remote_elevation() -> Result<bool> {
+    Ok(load_config()?.dem_dir.is_none())
+}
+
+/// Local DEM tiles when `dem_dir` is set in the config, otherwise Open-Meteo
+pub fn elevation_provider() -> Result<Box<dyn ElevationProvider>> {
+    match load_config()?.dem_dir {
+        Some(dir) => Ok(Box::new(LocalDemElevation::open(&dir)?)),
+        None => Ok(Box::new(OpenMeteoElevation)),
+    }
+}
+
 /// Fetch elevation for a list of coordinates from Open-Meteo API
 /// Coordinates are (lat, lng) pairs
 /// Returns elevation in meters for each point
@@ -33,6 +114,8 @@ pub fn fetch_elevation(coordinates: &[(f64, f64)]) -> Result<Vec<f64>> {
 
         let response: ElevationResponse = reqwest::blocking::get(&url)
             .context("Failed to fetch elevation data")?
+            .error_for_status()
+            .context("Elevation API returned an error")?
             .json()
             .context("Failed to parse elevation response")?;
 
@@ -42,71 +125,287 @@ pub fn fetch_elevation(coordinates: &[(f64, f64)]) -> Result<Vec<f64>> {
     Ok(all_elevations)
 }
 
-/// Sample coordinates to reduce API calls
-/// If trail has 500 points, sample ~50 evenly spaced points
-pub fn sample_coordinates(coordinates: &[(f64, f64)], max_points: usize) -> Vec<(f64, f64)> {
-    if coordinates.len() <= max_points {
-        return coordinates.to_vec();
+/// Elevation profile of a trail, from the cache or fetched and cached
+/// Profiles cached with another sampling are fetched again
+pub fn get_trail_elevation(trail: &Trail) -> Result<Vec<f64>> {
+    if let Some(cached) = cache::get_cached_elevation(&trail.name, &trail.park)
+        && is_current_sampling(trail, &cached)
+    {
+        return Ok(cached);
     }
 
-    let step = coordinates.len() as f64 / max_points as f64;
-    let mut sampled: Vec<_> = (0..max_points)
-        .filter_map(|i| {
-            let idx = (i as f64 * step) as usize;
-            coordinates.get(idx).copied()
-        })
+    let elevations = fetch_trail_elevation(trail, elevation_provider()?.as_ref())?;
+
+    // Ignore cache errors - non-critical
+    let _ = cache::cache_elevation(&trail.name, &trail.park, &elevations);
+
+    Ok(elevations)
+}
+
+/// Whether cached elevations line up with the trail's sample points
+pub fn is_current_sampling(trail: &Trail, elevations: &[f64]) -> bool {
+    sample_along(&trail.coordinates_wgs84).len() == elevations.len()
+}
+
+/// Elevations at the trail's sample points, bypassing the cache
+pub fn fetch_trail_elevation(trail: &Trail, provider: &dyn ElevationProvider) -> Result<Vec<f64>> {
+    let sampled: Vec<(f64, f64)> = sample_along(&trail.coordinates_wgs84)
+        .into_iter()
+        .map(|(_, point)| point)
         .collect();
+    provider.elevations(&sampled)
+}
 
-    // Always include last point if not already included
-    if let Some(&last) = coordinates.last()
-        && sampled.last() != Some(&last)
-    {
-        sampled.push(last);
+/// Points every `SAMPLE_SPACING_M` along the line, interpolated between vertices,
+/// with their distance (km) from the start
+/// Both ends are always included; at most `MAX_SAMPLE_POINTS` are taken
+pub fn sample_along(coordinates: &[(f64, f64)]) -> Vec<(f64, (f64, f64))> {
+    let along = cumulative_distances(coordinates);
+    let (Some(&first), Some(&total)) = (coordinates.first(), along.last()) else {
+        return Vec::new();
+    };
+    if total <= 0.0 {
+        return vec![(0.0, first)];
     }
 
-    sampled
+    let intervals =
+        ((total * 1000.0 / SAMPLE_SPACING_M).ceil() as usize).clamp(1, MAX_SAMPLE_POINTS - 1);
+    let step = total / intervals as f64;
+
+    let mut segment = 0;
+    (0..=intervals)
+        .map(|i| {
+            let distance = if i == intervals {
+                total
+            } else {
+                i as f64 * step
+            };
+            while segment + 2 < along.len() && along[segment + 1] < distance {
+                segment += 1;
+            }
+            let (from, to) = (coordinates[segment], coordinates[segment + 1]);
+            let span = along[segment + 1] - along[segment];
+            let t = if span > 0.0 {
+                ((distance - along[segment]) / span).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let point = (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+            (distance, point)
+        })
+        .collect()
 }
 
 /// Calculate elevation statistics
+#[derive(Debug, Default)]
 pub struct ElevationStats {
     pub min: f64,
     pub max: f64,
+    /// Gain/loss after smoothing and hysteresis
     pub total_gain: f64,
     pub total_loss: f64,
-    pub elevations: Vec<f64>,
+    /// Sum of every difference between samples
+    pub raw_gain: f64,
+    pub raw_loss: f64,
 }
 
-pub fn calculate_elevation_stats(elevations: &[f64]) -> ElevationStats {
+/// Elevation statistics for samples spread evenly over `length_km`
+pub fn calculate_elevation_stats(elevations: &[f64], length_km: f64) -> ElevationStats {
     if elevations.is_empty() {
-        return ElevationStats {
-            min: 0.0,
-            max: 0.0,
-            total_gain: 0.0,
-            total_loss: 0.0,
-            elevations: Vec::new(),
-        };
+        return ElevationStats::default();
     }
 
     let min = elevations.iter().copied().reduce(f64::min).unwrap_or(0.0);
     let max = elevations.iter().copied().reduce(f64::max).unwrap_or(0.0);
 
-    let (total_gain, total_loss) =
-        elevations
-            .windows(2)
-            .map(|w| w[1] - w[0])
-            .fold((0.0, 0.0), |(gain, loss), diff| {
-                if diff > 0.0 {
-                    (gain + diff, loss)
-                } else {
-                    (gain, loss + diff.abs())
-                }
-            });
+    let spacing = sample_spacing(elevations.len(), length_km);
+    let (raw_gain, raw_loss) = gain_loss(elevations);
+    let smoothed = smooth_elevations(elevations, spacing);
+    let (total_gain, total_loss) = hysteresis_gain_loss(&smoothed, hysteresis_threshold(spacing));
 
     ElevationStats {
         min,
         max,
         total_gain,
         total_loss,
-        elevations: elevations.to_vec(),
+        raw_gain,
+        raw_loss,
+    }
+}
+
+/// Metres between consecutive samples
+pub fn sample_spacing(samples: usize, length_km: f64) -> f64 {
+    if samples < 2 {
+        return 0.0;
+    }
+    length_km * 1000.0 / (samples - 1) as f64
+}
+
+/// Sum of positive and negative differences between samples
+pub fn gain_loss(elevations: &[f64]) -> (f64, f64) {
+    elevations
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .fold((0.0, 0.0), |(gain, loss), diff| {
+            if diff > 0.0 {
+                (gain + diff, loss)
+            } else {
+                (gain, loss + diff.abs())
+            }
+        })
+}
+
+/// Centered moving average over about `SMOOTHING_WINDOW_M` of trail
+/// Samples further apart than the window are left as they are, since averaging
+/// them would flatten real summits and valleys
+pub fn smooth_elevations(elevations: &[f64], spacing_m: f64) -> Vec<f64> {
+    if spacing_m <= 0.0 || spacing_m > SMOOTHING_WINDOW_M {
+        return elevations.to_vec();
+    }
+    let half_window = ((SMOOTHING_WINDOW_M / spacing_m / 2.0).round() as usize).max(1);
+
+    (0..elevations.len())
+        .map(|i| {
+            let start = i.saturating_sub(half_window);
+            let end = (i + half_window + 1).min(elevations.len());
+            let window = &elevations[start..end];
+            window.iter().sum::<f64>() / window.len() as f64
+        })
+        .collect()
+}
+
+/// Minimum change (m) counted as a real climb or descent
+/// Dense samples pick up more DEM noise; sparse ones already skip over small bumps
+pub fn hysteresis_threshold(spacing_m: f64) -> f64 {
+    (5.0 - spacing_m / 50.0).clamp(2.0, 5.0)
+}
+
+/// Gain and loss counting only moves of at least `threshold` from the last turning point
+pub fn hysteresis_gain_loss(elevations: &[f64], threshold: f64) -> (f64, f64) {
+    let Some(&first) = elevations.first() else {
+        return (0.0, 0.0);
+    };
+
+    let mut anchor = first;
+    let (mut gain, mut loss) = (0.0, 0.0);
+    for &elevation in &elevations[1..] {
+        let diff = elevation - anchor;
+        if diff >= threshold {
+            gain += diff;
+            anchor = elevation;
+        } else if diff <= -threshold {
+            loss -= diff;
+            anchor = elevation;
+        }
+    }
+
+    (gain, loss)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `base` plus alternating noise of `noise` metres
+    fn noisy(base: impl Fn(usize) -> f64, samples: usize, noise: f64) -> Vec<f64> {
+        (0..samples)
+            .map(|i| base(i) + if i % 2 == 0 { noise } else { -noise })
+            .collect()
+    }
+
+    #[test]
+    fn sparse_peak_keeps_its_gain() {
+        // 20 km up to a 500 m summit and back, one sample every 2 km
+        let peak = [
+            300.0, 400.0, 500.0, 600.0, 700.0, 800.0, 700.0, 600.0, 500.0, 400.0, 300.0,
+        ];
+        let spacing = sample_spacing(peak.len(), 20.0);
+        assert!(spacing > SMOOTHING_WINDOW_M);
+        assert_eq!(smooth_elevations(&peak, spacing), peak);
+
+        let stats = calculate_elevation_stats(&peak, 20.0);
+        assert_eq!(stats.total_gain, 500.0);
+        assert_eq!(stats.total_loss, 500.0);
+        assert_eq!(stats.max, 800.0);
+    }
+
+    #[test]
+    fn trails_are_sampled_by_distance() {
+        // About 1.11 km due north with one vertex in the middle
+        let line = [(46.0, -72.0), (46.002, -72.0), (46.01, -72.0)];
+        let samples = sample_along(&line);
+        assert_eq!(samples.len(), 16);
+        assert_eq!(samples[0], (0.0, (46.0, -72.0)));
+        let (end_km, end) = samples[15];
+        assert!((end_km - 1.112).abs() < 0.01, "length {}", end_km);
+        assert_eq!(end, (46.01, -72.0));
+
+        for pair in samples.windows(2) {
+            let step_m = (pair[1].0 - pair[0].0) * 1000.0;
+            assert!(step_m <= SAMPLE_SPACING_M, "step {}", step_m);
+            assert!(pair[1].1.0 > pair[0].1.0);
+        }
+
+        assert!(sample_along(&[]).is_empty());
+        assert_eq!(sample_along(&[(46.0, -72.0)]), vec![(0.0, (46.0, -72.0))]);
+    }
+
+    #[test]
+    fn long_trails_cap_the_sample_count() {
+        // About 222 km
+        let line = [(46.0, -72.0), (48.0, -72.0)];
+        assert_eq!(sample_along(&line).len(), MAX_SAMPLE_POINTS);
+    }
+
+    #[test]
+    fn noisy_climb_keeps_its_real_gain() {
+        // 1 km climbing 100 m with ±3 m of DEM noise on every sample
+        let climb = noisy(|i| i as f64 * 100.0 / 99.0, 100, 3.0);
+        let stats = calculate_elevation_stats(&climb, 1.0);
+        assert!(stats.raw_gain > 300.0);
+        assert!(
+            (85.0..=105.0).contains(&stats.total_gain),
+            "gain {}",
+            stats.total_gain
+        );
+        assert!(stats.total_loss < 5.0, "loss {}", stats.total_loss);
+    }
+
+    #[test]
+    fn stepped_profile_counts_every_step() {
+        // Four 20 m steps up, then back down in one step, over 10 km
+        let stepped: Vec<f64> = (0..100)
+            .map(|i| match i {
+                0..80 => (i / 20) as f64 * 20.0,
+                80..90 => 80.0,
+                _ => 0.0,
+            })
+            .collect();
+        let stats = calculate_elevation_stats(&stepped, 10.0);
+        assert!(
+            (stats.total_gain - 80.0).abs() < 0.01,
+            "gain {}",
+            stats.total_gain
+        );
+        assert!(
+            (stats.total_loss - 80.0).abs() < 0.01,
+            "loss {}",
+            stats.total_loss
+        );
+    }
+
+    #[test]
+    fn hysteresis_ignores_small_wiggles() {
+        let wiggles = [
+            100.0, 101.5, 100.0, 101.5, 100.0, 110.0, 108.5, 110.0, 100.0,
+        ];
+        assert_eq!(hysteresis_gain_loss(&wiggles, 2.0), (10.0, 10.0));
+        assert_eq!(hysteresis_gain_loss(&[], 2.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn sparse_profiles_get_tighter_threshold() {
+        assert_eq!(hysteresis_threshold(10.0), 4.8);
+        assert_eq!(hysteresis_threshold(300.0), 2.0);
     }
 }
@@ -1,2 +1,5 @@
+pub mod air_quality;
+pub mod alerts;
+pub mod dem;
 pub mod elevation;
 pub mod weather;
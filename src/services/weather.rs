@@ -1,6 +1,8 @@
+use crate::cache;
 use anyhow::{Context, Result};
-use chrono::NaiveDate;
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime};
 use serde::Deserialize;
+use std::collections::HashMap;
 
 #[derive(Debug, Deserialize)]
 struct WeatherResponse {
@@ -23,9 +25,91 @@ struct ForecastResponse {
 struct DailyForecast {
     time: Vec<String>,
     temperature_2m_max: Vec<f64>,
+    temperature_2m_min: Vec<f64>,
     precipitation_sum: Vec<f64>,
+    precipitation_probability_max: Vec<Option<f64>>,
     windspeed_10m_max: Vec<f64>,
     weathercode: Vec<u32>,
+    uv_index_max: Vec<Option<f64>>,
+    cloud_cover_mean: Vec<Option<f64>>,
+    daylight_duration: Vec<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EnsembleResponse {
+    daily: EnsembleDaily,
+}
+
+/// Every variable comes once per member, e.g. `precipitation_sum_member07`
+#[derive(Debug, Deserialize)]
+struct EnsembleDaily {
+    time: Vec<String>,
+    #[serde(flatten)]
+    series: HashMap<String, Vec<Option<f64>>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HourlyForecastResponse {
+    hourly: HourlyForecast,
+}
+
+#[derive(Debug, Deserialize)]
+struct HourlyForecast {
+    time: Vec<String>,
+    cape: Vec<Option<f64>>,
+    lifted_index: Vec<Option<f64>>,
+    precipitation_probability: Vec<Option<f64>>,
+    weathercode: Vec<Option<u32>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecentResponse {
+    daily: RecentDaily,
+    hourly: ArchiveHourly,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecentDaily {
+    time: Vec<String>,
+    temperature_2m_max: Vec<Option<f64>>,
+    temperature_2m_min: Vec<Option<f64>>,
+    rain_sum: Vec<Option<f64>>,
+    snowfall_sum: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArchiveHourly {
+    snow_depth: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DayResponse {
+    daily: DayDaily,
+}
+
+#[derive(Debug, Deserialize)]
+struct DayDaily {
+    temperature_2m_max: Vec<Option<f64>>,
+    temperature_2m_min: Vec<Option<f64>>,
+    precipitation_sum: Vec<Option<f64>>,
+    wind_speed_10m_max: Vec<Option<f64>>,
+    weather_code: Vec<Option<u32>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClimateResponse {
+    daily: ClimateDaily,
+    hourly: ArchiveHourly,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClimateDaily {
+    time: Vec<String>,
+    temperature_2m_max: Vec<Option<f64>>,
+    temperature_2m_min: Vec<Option<f64>>,
+    precipitation_sum: Vec<Option<f64>>,
+    wind_speed_10m_max: Vec<Option<f64>>,
+    daylight_duration: Vec<Option<f64>>,
 }
 
 #[derive(Debug, Clone)]
@@ -39,9 +123,109 @@ pub struct Weather {
 pub struct DailyWeather {
     pub date: NaiveDate,
     pub max_temp: f64,
+    pub min_temp: f64,
     pub precipitation: f64,
+    /// Chance of precipitation in %, when the model provides it
+    pub precipitation_probability: Option<f64>,
     pub wind_speed: f64,
     pub weather_code: u32,
+    pub uv_index: Option<f64>,
+    /// Mean cloud cover in %
+    pub cloud_cover: Option<f64>,
+    pub daylight_hours: f64,
+    /// Spread of the ensemble members, when requested and available
+    pub ensemble: Option<DailyEnsemble>,
+}
+
+/// One ensemble member's outcome for a day
+#[derive(Debug, Clone)]
+pub struct EnsembleMember {
+    pub max_temp: f64,
+    pub min_temp: f64,
+    pub precipitation: f64,
+    pub wind_speed: f64,
+}
+
+/// All ensemble members for one forecast day
+#[derive(Debug, Clone)]
+pub struct DailyEnsemble {
+    pub members: Vec<EnsembleMember>,
+}
+
+impl DailyEnsemble {
+    /// Value at percentile `p` (0-100) across members, nearest rank
+    pub fn percentile(&self, value: fn(&EnsembleMember) -> f64, p: f64) -> f64 {
+        let mut values: Vec<f64> = self.members.iter().map(value).collect();
+        if values.is_empty() {
+            return 0.0;
+        }
+        values.sort_by(f64::total_cmp);
+        let rank = (p / 100.0 * (values.len() - 1) as f64).round() as usize;
+        values[rank.min(values.len() - 1)]
+    }
+
+    /// Share of members (in %) with at least 1mm of precipitation
+    pub fn precipitation_chance(&self) -> f64 {
+        if self.members.is_empty() {
+            return 0.0;
+        }
+        let wet = self
+            .members
+            .iter()
+            .filter(|m| m.precipitation >= 1.0)
+            .count();
+        wet as f64 / self.members.len() as f64 * 100.0
+    }
+}
+
+/// Convective parameters for one forecast hour
+#[derive(Debug, Clone)]
+pub struct HourlyConvection {
+    pub time: NaiveDateTime,
+    /// Convective available potential energy in J/kg
+    pub cape: f64,
+    pub lifted_index: Option<f64>,
+    pub precipitation_probability: f64,
+    pub weather_code: u32,
+}
+
+/// One day of recent weather, from the forecast API's past model runs
+#[derive(Debug, Clone)]
+pub struct PastDay {
+    pub max_temp: f64,
+    pub min_temp: f64,
+    /// Rain in mm
+    pub rain: f64,
+    /// Snowfall in cm
+    pub snowfall: f64,
+    /// Deepest snow cover of the day in cm, when the archive has it
+    pub snow_depth: Option<f64>,
+}
+
+/// Weather of a single past day, for the hike log
+#[derive(Debug, Clone)]
+pub struct DayWeather {
+    pub max_temp: f64,
+    pub min_temp: f64,
+    /// Precipitation in mm
+    pub precipitation: f64,
+    /// Strongest wind of the day in km/h
+    pub wind_speed: f64,
+    pub weather_code: u32,
+}
+
+/// One day of the multi-year history used for climate normals
+#[derive(Debug, Clone)]
+pub struct ClimateDay {
+    pub date: NaiveDate,
+    pub max_temp: f64,
+    pub min_temp: f64,
+    /// Precipitation in mm
+    pub precipitation: f64,
+    /// Deepest snow cover of the day in cm, when the archive has it
+    pub snow_depth: Option<f64>,
+    pub wind_speed: f64,
+    pub daylight_hours: f64,
 }
 
 impl Weather {
@@ -66,22 +250,6 @@ impl Weather {
 }
 
 impl DailyWeather {
-    pub fn rating(&self) -> (&'static str, &'static str) {
-        let has_rain = self.precipitation > 0.5;
-        let high_wind = self.wind_speed > 20.0;
-        let storm = self.weather_code >= 95;
-
-        if storm || (has_rain && high_wind) {
-            ("[!]", "Bad")
-        } else if has_rain || high_wind {
-            ("[!]", "Okay")
-        } else if self.weather_code <= 3 && self.wind_speed < 15.0 {
-            ("[OK]", "Excellent")
-        } else {
-            ("[OK]", "Good")
-        }
-    }
-
     pub fn icon(&self) -> &'static str {
         crate::icons::Icons::weather(self.weather_code)
     }
@@ -105,9 +273,10 @@ pub fn get_weather(lat: f64, lng: f64) -> Result<Weather> {
     })
 }
 
+/// 7-day forecast; each day also gets the ensemble spread when it can be fetched
 pub fn get_7day_forecast(lat: f64, lng: f64) -> Result<Vec<DailyWeather>> {
     let url = format!(
-        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&daily=temperature_2m_max,precipitation_sum,windspeed_10m_max,weathercode&timezone=America/Toronto",
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&daily=temperature_2m_max,temperature_2m_min,precipitation_sum,precipitation_probability_max,windspeed_10m_max,weathercode,uv_index_max,cloud_cover_mean,daylight_duration&timezone=America/Toronto",
         lat, lng
     );
 
@@ -117,25 +286,378 @@ pub fn get_7day_forecast(lat: f64, lng: f64) -> Result<Vec<DailyWeather>> {
         .context("Failed to parse forecast response")?;
 
     let daily = &forecast_resp.daily;
-    daily
+    let days = daily
         .time
-        .iter()
-        .zip(&daily.temperature_2m_max)
-        .zip(&daily.precipitation_sum)
-        .zip(&daily.windspeed_10m_max)
-        .zip(&daily.weathercode)
-        .map(
-            |((((time, &max_temp), &precipitation), &wind_speed), &weather_code)| {
-                let date =
-                    NaiveDate::parse_from_str(time, "%Y-%m-%d").context("Failed to parse date")?;
-                Ok(DailyWeather {
-                    date,
-                    max_temp,
-                    precipitation,
-                    wind_speed,
-                    weather_code,
+        .len()
+        .min(daily.temperature_2m_max.len())
+        .min(daily.temperature_2m_min.len())
+        .min(daily.precipitation_sum.len())
+        .min(daily.windspeed_10m_max.len())
+        .min(daily.weathercode.len())
+        .min(daily.daylight_duration.len());
+
+    let ensemble = get_ensemble_forecast(lat, lng).unwrap_or_default();
+
+    (0..days)
+        .map(|i| {
+            let date = NaiveDate::parse_from_str(&daily.time[i], "%Y-%m-%d")
+                .context("Failed to parse date")?;
+            Ok(DailyWeather {
+                date,
+                max_temp: daily.temperature_2m_max[i],
+                min_temp: daily.temperature_2m_min[i],
+                precipitation: daily.precipitation_sum[i],
+                precipitation_probability: daily
+                    .precipitation_probability_max
+                    .get(i)
+                    .copied()
+                    .flatten(),
+                wind_speed: daily.windspeed_10m_max[i],
+                weather_code: daily.weathercode[i],
+                uv_index: daily.uv_index_max.get(i).copied().flatten(),
+                cloud_cover: daily.cloud_cover_mean.get(i).copied().flatten(),
+                // Seconds between sunrise and sunset
+                daylight_hours: daily.daylight_duration[i] / 3600.0,
+                ensemble: ensemble.get(&date).cloned(),
+            })
+        })
+        .collect()
+}
+
+/// Per-member daily outcomes from the GEM global ensemble (21 members)
+pub fn get_ensemble_forecast(lat: f64, lng: f64) -> Result<HashMap<NaiveDate, DailyEnsemble>> {
+    let url = format!(
+        "https://ensemble-api.open-meteo.com/v1/ensemble?latitude={}&longitude={}&daily=temperature_2m_max,temperature_2m_min,precipitation_sum,wind_speed_10m_max&models=gem_global&forecast_days=7&timezone=America/Toronto",
+        lat, lng
+    );
+
+    let ensemble_resp: EnsembleResponse = reqwest::blocking::get(&url)
+        .context("Failed to fetch ensemble forecast")?
+        .json()
+        .context("Failed to parse ensemble forecast response")?;
+
+    ensemble_days(&ensemble_resp.daily)
+}
+
+/// Members of each day, keyed by date
+fn ensemble_days(daily: &EnsembleDaily) -> Result<HashMap<NaiveDate, DailyEnsemble>> {
+    // The control run has no suffix, perturbed members end in _memberNN
+    let mut suffixes: Vec<String> = daily
+        .series
+        .keys()
+        .filter_map(|key| key.strip_prefix("precipitation_sum"))
+        .map(str::to_string)
+        .collect();
+    suffixes.sort();
+
+    let value = |name: &str, suffix: &str, i: usize| {
+        daily
+            .series
+            .get(&format!("{}{}", name, suffix))
+            .and_then(|values| values.get(i).copied().flatten())
+    };
+
+    // Members missing any value are left out rather than scored on a guess,
+    // e.g. no wind as dead calm
+    let mut days = HashMap::new();
+    for (i, time) in daily.time.iter().enumerate() {
+        let date = NaiveDate::parse_from_str(time, "%Y-%m-%d").context("Failed to parse date")?;
+        let members: Vec<EnsembleMember> = suffixes
+            .iter()
+            .filter_map(|suffix| {
+                Some(EnsembleMember {
+                    max_temp: value("temperature_2m_max", suffix, i)?,
+                    min_temp: value("temperature_2m_min", suffix, i)?,
+                    precipitation: value("precipitation_sum", suffix, i)?,
+                    wind_speed: value("wind_speed_10m_max", suffix, i)?,
                 })
-            },
-        )
+            })
+            .collect();
+
+        if !members.is_empty() {
+            days.insert(date, DailyEnsemble { members });
+        }
+    }
+
+    Ok(days)
+}
+
+/// Fetch the weather of the last `days` days before today (oldest first)
+///
+/// The archive lags about `ARCHIVE_DELAY_DAYS` behind, so this asks the
+/// forecast API for its past days instead. Responses are cached per location
+/// for the day; days without data are skipped
+pub fn get_recent_weather(lat: f64, lng: f64, days: u32) -> Result<Vec<PastDay>> {
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let key = format!("{:.2}_{:.2}_{}", lat, lng, days);
+
+    let json = match cache::get_cached_recent(&today, &key) {
+        Some(json) => json,
+        None => {
+            let url = format!(
+                "https://api.open-meteo.com/v1/forecast?latitude={:.2}&longitude={:.2}&past_days={}&forecast_days=1&daily=temperature_2m_max,temperature_2m_min,rain_sum,snowfall_sum&hourly=snow_depth&timezone=America/Toronto",
+                lat,
+                lng,
+                days.max(1)
+            );
+            let json = reqwest::blocking::get(&url)
+                .context("Failed to fetch recent weather")?
+                .error_for_status()
+                .context("Recent weather request failed")?
+                .text()
+                .context("Failed to read recent weather")?;
+            let _ = cache::cache_recent(&today, &key, &json);
+            json
+        }
+    };
+
+    let response: RecentResponse =
+        serde_json::from_str(&json).context("Failed to parse recent weather response")?;
+    let daily = &response.daily;
+    let mut past_days = Vec::with_capacity(daily.time.len());
+
+    for i in 0..daily.time.len() {
+        // Today is only partly over
+        if daily.time[i] >= today {
+            continue;
+        }
+        let (Some(Some(max_temp)), Some(Some(min_temp))) = (
+            daily.temperature_2m_max.get(i),
+            daily.temperature_2m_min.get(i),
+        ) else {
+            continue;
+        };
+
+        // Hourly values are in metres, 24 per day
+        let snow_depth = response
+            .hourly
+            .snow_depth
+            .iter()
+            .skip(i * 24)
+            .take(24)
+            .flatten()
+            .copied()
+            .reduce(f64::max)
+            .map(|depth| depth * 100.0);
+
+        past_days.push(PastDay {
+            max_temp: *max_temp,
+            min_temp: *min_temp,
+            rain: daily.rain_sum.get(i).copied().flatten().unwrap_or(0.0),
+            snowfall: daily.snowfall_sum.get(i).copied().flatten().unwrap_or(0.0),
+            snow_depth,
+        });
+    }
+
+    Ok(past_days)
+}
+
+/// Days it takes the archive to fill in a date; more recent days come from the
+/// forecast API, which keeps its model runs for the past few months
+const ARCHIVE_DELAY_DAYS: i64 = 5;
+
+/// Weather of one day at a location, from the archive or, for the last few
+/// days (including today), the forecast API. Forecast answers are cached for
+/// the day, like recent weather, since they change with each model run
+pub fn get_day_weather(lat: f64, lng: f64, date: NaiveDate) -> Result<DayWeather> {
+    let today = Local::now().date_naive();
+    let from_archive = today - date > Duration::days(ARCHIVE_DELAY_DAYS);
+    let endpoint = if from_archive {
+        "https://archive-api.open-meteo.com/v1/archive"
+    } else {
+        "https://api.open-meteo.com/v1/forecast"
+    };
+    let day = date.format("%Y-%m-%d");
+    let cache_day = today.format("%Y-%m-%d").to_string();
+    let key = format!("day_{:.2}_{:.2}_{}", lat, lng, day);
+
+    let cached = (!from_archive)
+        .then(|| cache::get_cached_recent(&cache_day, &key))
+        .flatten();
+    let json = match cached {
+        Some(json) => json,
+        None => {
+            let url = format!(
+                "{}?latitude={:.2}&longitude={:.2}&start_date={}&end_date={}&daily=temperature_2m_max,temperature_2m_min,precipitation_sum,wind_speed_10m_max,weather_code&timezone=America/Toronto",
+                endpoint, lat, lng, day, day
+            );
+            let json = reqwest::blocking::get(&url)
+                .context("Failed to fetch weather history")?
+                .error_for_status()
+                .context("Weather history request failed")?
+                .text()
+                .context("Failed to read weather history")?;
+            if !from_archive {
+                let _ = cache::cache_recent(&cache_day, &key, &json);
+            }
+            json
+        }
+    };
+
+    let response: DayResponse =
+        serde_json::from_str(&json).context("Failed to parse weather history response")?;
+
+    let daily = &response.daily;
+    let first = |values: &[Option<f64>]| values.first().copied().flatten();
+    let (Some(max_temp), Some(min_temp)) = (
+        first(&daily.temperature_2m_max),
+        first(&daily.temperature_2m_min),
+    ) else {
+        anyhow::bail!("No weather recorded for {}", day);
+    };
+
+    Ok(DayWeather {
+        max_temp,
+        min_temp,
+        precipitation: first(&daily.precipitation_sum).unwrap_or(0.0),
+        wind_speed: first(&daily.wind_speed_10m_max).unwrap_or(0.0),
+        weather_code: daily.weather_code.first().copied().flatten().unwrap_or(0),
+    })
+}
+
+/// Hourly CAPE, lifted index and thunderstorm codes for today and tomorrow
+pub fn get_hourly_convection(lat: f64, lng: f64) -> Result<Vec<HourlyConvection>> {
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=cape,lifted_index,precipitation_probability,weathercode&forecast_days=2&timezone=America/Toronto",
+        lat, lng
+    );
+
+    let hourly_resp: HourlyForecastResponse = reqwest::blocking::get(&url)
+        .context("Failed to fetch hourly forecast")?
+        .json()
+        .context("Failed to parse hourly forecast response")?;
+
+    let hourly = &hourly_resp.hourly;
+    hourly
+        .time
+        .iter()
+        .enumerate()
+        .map(|(i, time)| {
+            let time = NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M")
+                .context("Failed to parse forecast time")?;
+            Ok(HourlyConvection {
+                time,
+                cape: hourly.cape.get(i).copied().flatten().unwrap_or(0.0),
+                lifted_index: hourly.lifted_index.get(i).copied().flatten(),
+                precipitation_probability: hourly
+                    .precipitation_probability
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .unwrap_or(0.0),
+                weather_code: hourly.weathercode.get(i).copied().flatten().unwrap_or(0),
+            })
+        })
         .collect()
 }
+
+/// Daily history of the last `years` full calendar years (cached per location and range)
+pub fn get_climate_history(lat: f64, lng: f64, years: u32) -> Result<Vec<ClimateDay>> {
+    let last_year = Local::now().year() - 1;
+    let first_year = last_year - years.max(1) as i32 + 1;
+    let key = format!("{:.2}_{:.2}_{}_{}", lat, lng, first_year, last_year);
+
+    let json = match cache::get_cached_climate(&key) {
+        Some(json) => json,
+        None => {
+            let url = format!(
+                "https://archive-api.open-meteo.com/v1/archive?latitude={}&longitude={}&start_date={}-01-01&end_date={}-12-31&daily=temperature_2m_max,temperature_2m_min,precipitation_sum,wind_speed_10m_max,daylight_duration&hourly=snow_depth&timezone=America/Toronto",
+                lat, lng, first_year, last_year
+            );
+            let json = reqwest::blocking::get(&url)
+                .context("Failed to fetch climate history")?
+                .error_for_status()
+                .context("Climate history request failed")?
+                .text()
+                .context("Failed to read climate history")?;
+            let _ = cache::cache_climate(&key, &json);
+            json
+        }
+    };
+
+    let response: ClimateResponse =
+        serde_json::from_str(&json).context("Failed to parse climate history response")?;
+    let daily = &response.daily;
+    let mut history = Vec::with_capacity(daily.time.len());
+
+    for (i, time) in daily.time.iter().enumerate() {
+        let (Some(Some(max_temp)), Some(Some(min_temp))) = (
+            daily.temperature_2m_max.get(i),
+            daily.temperature_2m_min.get(i),
+        ) else {
+            continue;
+        };
+        let date = NaiveDate::parse_from_str(time, "%Y-%m-%d").context("Failed to parse date")?;
+
+        // Hourly values are in metres, 24 per day
+        let snow_depth = response
+            .hourly
+            .snow_depth
+            .iter()
+            .skip(i * 24)
+            .take(24)
+            .flatten()
+            .copied()
+            .reduce(f64::max)
+            .map(|depth| depth * 100.0);
+
+        history.push(ClimateDay {
+            date,
+            max_temp: *max_temp,
+            min_temp: *min_temp,
+            precipitation: daily
+                .precipitation_sum
+                .get(i)
+                .copied()
+                .flatten()
+                .unwrap_or(0.0),
+            snow_depth,
+            wind_speed: daily
+                .wind_speed_10m_max
+                .get(i)
+                .copied()
+                .flatten()
+                .unwrap_or(0.0),
+            daylight_hours: daily
+                .daylight_duration
+                .get(i)
+                .copied()
+                .flatten()
+                .unwrap_or(0.0)
+                / 3600.0,
+        });
+    }
+
+    Ok(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ensemble_members_missing_a_value_are_dropped() {
+        let daily: EnsembleDaily = serde_json::from_str(
+            r#"{
+                "time": ["2026-07-14", "2026-07-15"],
+                "temperature_2m_max": [24.0, 20.0],
+                "temperature_2m_min": [12.0, 10.0],
+                "precipitation_sum": [0.0, 5.0],
+                "wind_speed_10m_max": [10.0, 30.0],
+                "temperature_2m_max_member01": [25.0, 21.0],
+                "temperature_2m_min_member01": [13.0, 11.0],
+                "precipitation_sum_member01": [1.0, null],
+                "wind_speed_10m_max_member01": [null, 25.0]
+            }"#,
+        )
+        .unwrap();
+
+        let days = ensemble_days(&daily).unwrap();
+        let first = &days[&NaiveDate::from_ymd_opt(2026, 7, 14).unwrap()];
+        assert_eq!(first.members.len(), 1);
+        assert_eq!(first.members[0].wind_speed, 10.0);
+        let second = &days[&NaiveDate::from_ymd_opt(2026, 7, 15).unwrap()];
+        assert_eq!(second.members.len(), 1);
+        assert_eq!(second.members[0].precipitation, 5.0);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::profile::ElevationProfile;
+use crate::services::weather::HourlyConvection;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+/// Relief (m) below which a trail has no meaningful summit exposure
+const MIN_SUMMIT_RELIEF: f64 = 150.0;
+
+/// Share of the relief, measured down from the high point, counted as exposed
+const EXPOSED_BAND: f64 = 0.25;
+
+/// Time to be off exposed terrain before a storm window opens
+pub const SAFETY_MARGIN_HOURS: i64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StormRisk {
+    Low,
+    Moderate,
+    High,
+}
+
+impl StormRisk {
+    pub fn label(&self) -> &'static str {
+        match self {
+            StormRisk::Low => "Low",
+            StormRisk::Moderate => "Moderate",
+            StormRisk::High => "High",
+        }
+    }
+}
+
+/// Estimated chance of a thunderstorm (0-100) in one forecast hour
+///
+/// Instability (CAPE, lifted index) only turns into storms when there is
+/// moisture to trigger them, so it is scaled by the precipitation probability.
+pub fn thunderstorm_probability(hour: &HourlyConvection) -> f64 {
+    if hour.weather_code >= 95 {
+        return hour.precipitation_probability.max(70.0);
+    }
+
+    let cape_factor: f64 = match hour.cape {
+        c if c < 300.0 => 0.0,
+        c if c < 1000.0 => 0.3,
+        c if c < 2500.0 => 0.6,
+        _ => 0.9,
+    };
+    let li_factor = match hour.lifted_index {
+        Some(li) if li < -6.0 => 0.3,
+        Some(li) if li < -3.0 => 0.2,
+        Some(li) if li < 0.0 => 0.1,
+        _ => 0.0,
+    };
+
+    let instability = (cape_factor + li_factor).min(1.0);
+    (instability * hour.precipitation_probability).round()
+}
+
+pub fn hour_risk(hour: &HourlyConvection) -> StormRisk {
+    match thunderstorm_probability(hour) {
+        p if p >= 50.0 => StormRisk::High,
+        p if p >= 20.0 => StormRisk::Moderate,
+        _ => StormRisk::Low,
+    }
+}
+
+/// Consecutive hours of moderate or high thunderstorm risk
+#[derive(Debug, Clone)]
+pub struct StormWindow {
+    pub start: NaiveDateTime,
+    /// End of the last risky hour
+    pub end: NaiveDateTime,
+    pub risk: StormRisk,
+    pub max_cape: f64,
+    pub min_lifted_index: Option<f64>,
+    pub max_probability: f64,
+}
+
+/// Storm windows on a given day
+pub fn storm_windows(hours: &[HourlyConvection], date: NaiveDate) -> Vec<StormWindow> {
+    let mut windows: Vec<StormWindow> = Vec::new();
+
+    for hour in hours.iter().filter(|h| h.time.date() == date) {
+        let risk = hour_risk(hour);
+        if risk == StormRisk::Low {
+            continue;
+        }
+
+        let probability = thunderstorm_probability(hour);
+        let hour_end = hour.time + Duration::hours(1);
+
+        match windows.last_mut() {
+            Some(window) if window.end == hour.time => {
+                window.end = hour_end;
+                window.risk = window.risk.max(risk);
+                window.max_cape = window.max_cape.max(hour.cape);
+                window.max_probability = window.max_probability.max(probability);
+                window.min_lifted_index = match (window.min_lifted_index, hour.lifted_index) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            _ => windows.push(StormWindow {
+                start: hour.time,
+                end: hour_end,
+                risk,
+                max_cape: hour.cape,
+                min_lifted_index: hour.lifted_index,
+                max_probability: probability,
+            }),
+        }
+    }
+
+    windows
+}
+
+/// Where along a trail it rises above the surrounding terrain
+#[derive(Debug, Clone)]
+pub struct Exposure {
+    /// Elevation (m) above which the trail counts as exposed
+    pub threshold: f64,
+    pub relief: f64,
+    /// Fractions of the trail (0-1) where the exposed section starts and ends
+    pub first: f64,
+    pub last: f64,
+}
+
+/// Exposed section of an elevation profile, if the trail has a real summit
+pub fn summit_exposure(profile: &ElevationProfile) -> Option<Exposure> {
+    let length = profile.length_km();
+    if profile.points.len() < 2 || length <= 0.0 {
+        return None;
+    }
+
+    let elevations = profile.elevations();
+    let min = elevations.iter().copied().reduce(f64::min)?;
+    let max = elevations.iter().copied().reduce(f64::max)?;
+    let relief = max - min;
+    if relief < MIN_SUMMIT_RELIEF {
+        return None;
+    }
+
+    let threshold = max - relief * EXPOSED_BAND;
+    let exposed = || profile.points.iter().filter(|p| p.elevation >= threshold);
+    let first = exposed().next()?.distance_km / length;
+    let last = exposed().next_back()?.distance_km / length;
+
+    Some(Exposure {
+        threshold,
+        relief,
+        first,
+        last,
+    })
+}
+
+/// Timing of a hike's exposed section against the day's storm windows
+#[derive(Debug, Clone)]
+pub struct LightningPlan {
+    pub exposed_from: NaiveDateTime,
+    pub exposed_until: NaiveDateTime,
+    /// First storm window overlapping the exposed section (with safety margin)
+    pub conflict: Option<StormWindow>,
+    /// Latest time to start the exposed section and still be off it before the first storm
+    pub turnaround: Option<NaiveDateTime>,
+    /// Latest trailhead start that keeps the exposed section clear of the first storm
+    pub latest_start: Option<NaiveDateTime>,
+    /// Smallest gap between the exposed section and a storm window, when none overlaps
+    pub clearance: Option<Duration>,
+}
+
+pub fn plan_exposure(
+    exposure: &Exposure,
+    windows: &[StormWindow],
+    start: NaiveDateTime,
+    duration_hours: f64,
+) -> LightningPlan {
+    let at_fraction =
+        |fraction: f64| start + Duration::minutes((fraction * duration_hours * 60.0) as i64);
+    let exposed_from = at_fraction(exposure.first);
+    let exposed_until = at_fraction(exposure.last);
+    let margin = Duration::hours(SAFETY_MARGIN_HOURS);
+
+    let conflict = windows
+        .iter()
+        .find(|w| exposed_from < w.end && exposed_until > w.start - margin)
+        .cloned();
+
+    let exposed_duration = exposed_until - exposed_from;
+    let first_storm = windows.iter().find(|w| w.end > exposed_from);
+    let turnaround = first_storm.map(|w| w.start - margin - exposed_duration);
+    let latest_start = turnaround.map(|t| t - (exposed_from - start));
+
+    let clearance = if conflict.is_some() {
+        None
+    } else {
+        windows
+            .iter()
+            .map(|w| {
+                if w.start >= exposed_until {
+                    w.start - exposed_until
+                } else {
+                    exposed_from - w.end
+                }
+            })
+            .min()
+    };
+
+    LightningPlan {
+        exposed_from,
+        exposed_until,
+        conflict,
+        turnaround,
+        latest_start,
+        clearance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn hour(time: &str, cape: f64, precipitation_probability: f64) -> HourlyConvection {
+        HourlyConvection {
+            time: at(time),
+            cape,
+            lifted_index: None,
+            precipitation_probability,
+            weather_code: 0,
+        }
+    }
+
+    fn window(start: &str, end: &str) -> StormWindow {
+        StormWindow {
+            start: at(start),
+            end: at(end),
+            risk: StormRisk::High,
+            max_cape: 2000.0,
+            min_lifted_index: None,
+            max_probability: 60.0,
+        }
+    }
+
+    /// Exposed from 2h to 4h into an 8 hour hike
+    fn exposure() -> Exposure {
+        Exposure {
+            threshold: 2000.0,
+            relief: 800.0,
+            first: 0.25,
+            last: 0.5,
+        }
+    }
+
+    #[test]
+    fn windows_join_consecutive_risky_hours() {
+        let date = at("2026-07-14 00:00").date();
+        let hours = vec![
+            hour("2026-07-14 11:00", 100.0, 80.0),
+            hour("2026-07-14 12:00", 1500.0, 40.0),
+            hour("2026-07-14 13:00", 3000.0, 80.0),
+            hour("2026-07-14 14:00", 100.0, 80.0),
+            HourlyConvection {
+                weather_code: 95,
+                ..hour("2026-07-14 16:00", 0.0, 10.0)
+            },
+            hour("2026-07-15 13:00", 3000.0, 80.0),
+        ];
+
+        let windows = storm_windows(&hours, date);
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].start, at("2026-07-14 12:00"));
+        assert_eq!(windows[0].end, at("2026-07-14 14:00"));
+        assert_eq!(windows[0].risk, StormRisk::High);
+        assert_eq!(windows[0].max_cape, 3000.0);
+        assert_eq!(windows[0].max_probability, 72.0);
+        // A reported thunderstorm counts regardless of instability
+        assert_eq!(windows[1].start, at("2026-07-14 16:00"));
+        assert_eq!(windows[1].max_probability, 70.0);
+
+        assert!(storm_windows(&hours, at("2026-07-13 00:00").date()).is_empty());
+    }
+
+    #[test]
+    fn no_storm_leaves_the_plan_open() {
+        let plan = plan_exposure(&exposure(), &[], at("2026-07-14 08:00"), 8.0);
+        assert_eq!(plan.exposed_from, at("2026-07-14 10:00"));
+        assert_eq!(plan.exposed_until, at("2026-07-14 12:00"));
+        assert!(plan.conflict.is_none());
+        assert!(plan.turnaround.is_none());
+        assert!(plan.latest_start.is_none());
+        assert!(plan.clearance.is_none());
+    }
+
+    #[test]
+    fn storm_before_the_exposed_section_is_ignored() {
+        let windows = [
+            window("2026-07-14 07:00", "2026-07-14 09:00"),
+            window("2026-07-14 16:00", "2026-07-14 18:00"),
+        ];
+        let plan = plan_exposure(&exposure(), &windows, at("2026-07-14 08:00"), 8.0);
+
+        // The morning storm is still going at the trailhead but clears before the summit
+        assert!(plan.conflict.is_none());
+        assert_eq!(plan.turnaround, Some(at("2026-07-14 13:00")));
+        assert_eq!(plan.latest_start, Some(at("2026-07-14 11:00")));
+        assert_eq!(plan.clearance, Some(Duration::hours(1)));
+    }
+
+    #[test]
+    fn storm_during_the_exposed_section_conflicts() {
+        let windows = [window("2026-07-14 11:00", "2026-07-14 13:00")];
+        let plan = plan_exposure(&exposure(), &windows, at("2026-07-14 08:00"), 8.0);
+
+        assert_eq!(plan.conflict.unwrap().start, at("2026-07-14 11:00"));
+        assert_eq!(plan.turnaround, Some(at("2026-07-14 08:00")));
+        assert_eq!(plan.latest_start, Some(at("2026-07-14 06:00")));
+        assert!(plan.clearance.is_none());
+    }
+
+    #[test]
+    fn storm_within_the_margin_after_the_exposed_section_conflicts() {
+        let windows = [window("2026-07-14 12:30", "2026-07-14 14:00")];
+        let plan = plan_exposure(&exposure(), &windows, at("2026-07-14 08:00"), 8.0);
+
+        assert!(plan.conflict.is_some());
+        assert_eq!(plan.latest_start, Some(at("2026-07-14 07:30")));
+    }
+
+    #[test]
+    fn storm_after_the_exposed_section_sets_the_latest_start() {
+        let windows = [window("2026-07-14 15:00", "2026-07-14 17:00")];
+        let plan = plan_exposure(&exposure(), &windows, at("2026-07-14 08:00"), 8.0);
+
+        assert!(plan.conflict.is_none());
+        assert_eq!(plan.turnaround, Some(at("2026-07-14 12:00")));
+        assert_eq!(plan.latest_start, Some(at("2026-07-14 10:00")));
+        assert_eq!(plan.clearance, Some(Duration::hours(3)));
+    }
+}
@@ -0,0 +1,479 @@
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Completed periods the recent pace is averaged over, besides the current one
+const PACE_PERIODS: usize = 4;
+
+/// Furthest a goal forecast looks ahead, in days
+const MAX_FORECAST_DAYS: f64 = 3660.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Cadence {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl FromStr for Cadence {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "daily" | "day" => Ok(Cadence::Daily),
+            "weekly" | "week" => Ok(Cadence::Weekly),
+            "monthly" | "month" => Ok(Cadence::Monthly),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Cadence {
+    pub fn noun(&self) -> &'static str {
+        match self {
+            Cadence::Daily => "day",
+            Cadence::Weekly => "week",
+            Cadence::Monthly => "month",
+        }
+    }
+}
+
+/// A calendar day, ISO week (Monday to Sunday) or month
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Period {
+    pub cadence: Cadence,
+    pub start: NaiveDate,
+}
+
+impl Period {
+    pub fn containing(cadence: Cadence, date: NaiveDate) -> Self {
+        let start = match cadence {
+            Cadence::Daily => date,
+            Cadence::Weekly => {
+                date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+            }
+            Cadence::Monthly => date - Duration::days(i64::from(date.day0())),
+        };
+        Self { cadence, start }
+    }
+
+    /// Stepping by dates rather than week numbers keeps 53-week ISO years right
+    pub fn next(&self) -> Self {
+        let start = match self.cadence {
+            Cadence::Daily => self.start + Duration::days(1),
+            Cadence::Weekly => self.start + Duration::days(7),
+            Cadence::Monthly => self.start + Months::new(1),
+        };
+        Self { start, ..*self }
+    }
+
+    pub fn prev(&self) -> Self {
+        let start = match self.cadence {
+            Cadence::Daily => self.start - Duration::days(1),
+            Cadence::Weekly => self.start - Duration::days(7),
+            Cadence::Monthly => self.start - Months::new(1),
+        };
+        Self { start, ..*self }
+    }
+
+    /// Last day of the period
+    pub fn end(&self) -> NaiveDate {
+        self.next().start - Duration::days(1)
+    }
+
+    /// e.g. "2025-06-02", "2026-W01" or "June 2025"
+    pub fn label(&self) -> String {
+        match self.cadence {
+            Cadence::Daily => self.start.format("%Y-%m-%d").to_string(),
+            Cadence::Weekly => {
+                let week = self.start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Cadence::Monthly => self.start.format("%B %Y").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Streak {
+    /// Consecutive periods with a hike, up to the current one
+    pub current: u32,
+    pub longest: u32,
+    /// Last period of the longest streak
+    pub longest_end: Option<Period>,
+    /// No hike yet in the current period; the streak ends if it passes without one
+    pub pending: bool,
+}
+
+/// Streaks of periods with at least one hike. A streak stays alive through
+/// the current period until it ends, so it doesn't drop to 0 every Monday
+pub fn streak(dates: &[NaiveDate], cadence: Cadence, today: NaiveDate) -> Streak {
+    let periods: BTreeSet<Period> = dates
+        .iter()
+        .filter(|&&d| d <= today)
+        .map(|&d| Period::containing(cadence, d))
+        .collect();
+
+    let this = Period::containing(cadence, today);
+    let pending = !periods.contains(&this);
+    let mut check = if pending { this.prev() } else { this };
+    let mut current = 0;
+    while periods.contains(&check) {
+        current += 1;
+        check = check.prev();
+    }
+
+    let mut longest = 0;
+    let mut longest_end = None;
+    let mut run = 0;
+    let mut last: Option<Period> = None;
+    for &period in &periods {
+        run = if last.is_some_and(|l| l.next() == period) {
+            run + 1
+        } else {
+            1
+        };
+        if run > longest {
+            longest = run;
+            longest_end = Some(period);
+        }
+        last = Some(period);
+    }
+
+    Streak {
+        current,
+        longest,
+        longest_end,
+        pending,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoalMetric {
+    Hikes,
+    /// Kilometres
+    Distance,
+    /// Metres of elevation gain
+    Gain,
+    Hours,
+}
+
+impl GoalMetric {
+    pub fn unit(&self) -> &'static str {
+        match self {
+            GoalMetric::Hikes => "hikes",
+            GoalMetric::Distance => "km",
+            GoalMetric::Gain => "m",
+            GoalMetric::Hours => "h",
+        }
+    }
+}
+
+/// A target per period, e.g. "2 hikes/week" or "40 km/month"
+#[derive(Debug, Clone, Copy)]
+pub struct Goal {
+    pub target: f64,
+    pub metric: GoalMetric,
+    pub cadence: Cadence,
+}
+
+impl FromStr for Goal {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (amount, cadence) = s.split_once('/').ok_or(())?;
+        let amount = amount.trim();
+        let split = amount
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+            .unwrap_or(amount.len());
+        let target: f64 = amount[..split].replace(',', ".").parse().map_err(|_| ())?;
+        let metric = match amount[split..].trim().to_lowercase().as_str() {
+            "hike" | "hikes" => GoalMetric::Hikes,
+            "km" => GoalMetric::Distance,
+            "m" => GoalMetric::Gain,
+            "h" | "hour" | "hours" => GoalMetric::Hours,
+            _ => return Err(()),
+        };
+        if !(target > 0.0 && target.is_finite()) {
+            return Err(());
+        }
+        Ok(Self {
+            target,
+            metric,
+            cadence: cadence.parse()?,
+        })
+    }
+}
+
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.metric {
+            GoalMetric::Hikes if self.target == 1.0 => "hike",
+            metric => metric.unit(),
+        };
+        write!(
+            f,
+            "{} {}/{}",
+            format_amount(self.target),
+            unit,
+            self.cadence.noun()
+        )
+    }
+}
+
+/// Whole numbers without decimals, others with one
+pub fn format_amount(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Forecast {
+    /// Reached on this date
+    Met(NaiveDate),
+    /// Reached on this date at the recent pace, before the period ends
+    OnTrack(NaiveDate),
+    /// Reached only on this date at the recent pace, after the period ends
+    Behind(NaiveDate),
+    /// Nothing logged recently to project from
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GoalProgress {
+    pub period: Period,
+    pub done: f64,
+    /// Consecutive periods the goal was met, up to the current one if it's met already
+    pub met_in_a_row: u32,
+    pub forecast: Forecast,
+}
+
+/// Progress toward a goal in the period containing `today`, from per-hike
+/// `(date, amount)` entries in the goal's unit
+pub fn goal_progress(goal: &Goal, entries: &[(NaiveDate, f64)], today: NaiveDate) -> GoalProgress {
+    let period = Period::containing(goal.cadence, today);
+    let total = |from: NaiveDate, to: NaiveDate| -> f64 {
+        entries
+            .iter()
+            .filter(|(d, _)| (from..=to).contains(d))
+            .fold(0.0, |sum, (_, amount)| sum + amount)
+    };
+    let done = total(period.start, today);
+
+    let forecast = if done >= goal.target {
+        let mut in_period: Vec<&(NaiveDate, f64)> = entries
+            .iter()
+            .filter(|(d, _)| (period.start..=today).contains(d))
+            .collect();
+        in_period.sort_by_key(|(d, _)| *d);
+        let mut sum = 0.0;
+        let reached = in_period.iter().find(|(_, amount)| {
+            sum += amount;
+            sum >= goal.target
+        });
+        Forecast::Met(reached.map_or(today, |(d, _)| *d))
+    } else {
+        let window_start = (0..PACE_PERIODS).fold(period, |p, _| p.prev()).start;
+        let days = (today - window_start).num_days() + 1;
+        let rate = total(window_start, today) / days as f64;
+        let days_needed = ((goal.target - done) / rate).ceil();
+        if rate > 0.0 && days_needed <= MAX_FORECAST_DAYS {
+            let eta = today + Duration::days(days_needed as i64);
+            if eta <= period.end() {
+                Forecast::OnTrack(eta)
+            } else {
+                Forecast::Behind(eta)
+            }
+        } else {
+            Forecast::Unknown
+        }
+    };
+
+    let mut met_in_a_row = u32::from(done >= goal.target);
+    let mut previous = period.prev();
+    while total(previous.start, previous.end()) >= goal.target {
+        met_in_a_row += 1;
+        previous = previous.prev();
+    }
+
+    GoalProgress {
+        period,
+        done,
+        met_in_a_row,
+        forecast,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn days(texts: &[&str]) -> Vec<NaiveDate> {
+        texts.iter().map(|t| day(t)).collect()
+    }
+
+    #[test]
+    fn weeks_cross_53_week_years() {
+        // 2020 and 2026 have 53 ISO weeks; Jan 1-3 belong to the old year's W53
+        let week = Period::containing(Cadence::Weekly, day("2021-01-01"));
+        assert_eq!(week.label(), "2020-W53");
+        assert_eq!(week.start, day("2020-12-28"));
+        assert_eq!(week.next().label(), "2021-W01");
+        assert_eq!(week.next().start, day("2021-01-04"));
+        assert_eq!(week.prev().label(), "2020-W52");
+
+        let week = Period::containing(Cadence::Weekly, day("2027-01-03"));
+        assert_eq!(week.label(), "2026-W53");
+        assert_eq!(week.end(), day("2027-01-03"));
+        assert_eq!(week.next().label(), "2027-W01");
+        assert_eq!(week.next().prev(), week);
+
+        // In a 52-week year, Dec 30 can already be next year's W01
+        assert_eq!(
+            Period::containing(Cadence::Weekly, day("2024-12-30")).label(),
+            "2025-W01"
+        );
+    }
+
+    #[test]
+    fn months_step_from_the_first() {
+        let january = Period::containing(Cadence::Monthly, day("2025-01-31"));
+        assert_eq!(january.start, day("2025-01-01"));
+        assert_eq!(january.next().start, day("2025-02-01"));
+        assert_eq!(january.end(), day("2025-01-31"));
+        assert_eq!(january.prev().start, day("2024-12-01"));
+        assert_eq!(january.next().end(), day("2025-02-28"));
+
+        let february = Period::containing(Cadence::Monthly, day("2024-02-29"));
+        assert_eq!(february.end(), day("2024-02-29"));
+        assert_eq!(february.label(), "February 2024");
+    }
+
+    #[test]
+    fn streaks_run_across_new_year() {
+        let weekly = streak(
+            &days(&["2026-12-22", "2026-12-30", "2027-01-05"]),
+            Cadence::Weekly,
+            day("2027-01-06"),
+        );
+        assert_eq!(
+            (weekly.current, weekly.longest, weekly.pending),
+            (3, 3, false)
+        );
+        assert_eq!(weekly.longest_end.unwrap().label(), "2027-W01");
+
+        let weekly = streak(
+            &days(&["2020-12-31", "2021-01-04"]),
+            Cadence::Weekly,
+            day("2021-01-10"),
+        );
+        assert_eq!((weekly.current, weekly.longest), (2, 2));
+
+        let daily = streak(
+            &days(&["2024-12-30", "2024-12-31", "2025-01-01"]),
+            Cadence::Daily,
+            day("2025-01-01"),
+        );
+        assert_eq!((daily.current, daily.pending), (3, false));
+
+        let monthly = streak(
+            &days(&["2024-11-15", "2024-12-31", "2025-01-02"]),
+            Cadence::Monthly,
+            day("2025-01-20"),
+        );
+        assert_eq!(monthly.current, 3);
+    }
+
+    #[test]
+    fn streak_is_pending_on_the_first_day_of_a_period() {
+        // Monday of 2027-W01, last hike in 2026-W53
+        let weekly = streak(&days(&["2026-12-30"]), Cadence::Weekly, day("2027-01-04"));
+        assert_eq!((weekly.current, weekly.pending), (1, true));
+
+        let daily = streak(&days(&["2024-12-31"]), Cadence::Daily, day("2025-01-01"));
+        assert_eq!((daily.current, daily.pending), (1, true));
+
+        let monthly = streak(
+            &days(&["2024-11-03", "2024-12-20"]),
+            Cadence::Monthly,
+            day("2025-01-01"),
+        );
+        assert_eq!((monthly.current, monthly.pending), (2, true));
+
+        // A skipped period ends it
+        let weekly = streak(&days(&["2026-12-20"]), Cadence::Weekly, day("2027-01-04"));
+        assert_eq!((weekly.current, weekly.longest), (0, 1));
+    }
+
+    #[test]
+    fn goals_parse_and_display() {
+        let goal: Goal = "2 hikes/week".parse().unwrap();
+        assert_eq!(goal.to_string(), "2 hikes/week");
+        assert_eq!(
+            "1 hike/day".parse::<Goal>().unwrap().to_string(),
+            "1 hike/day"
+        );
+        assert_eq!(
+            "40 km/month".parse::<Goal>().unwrap().metric,
+            GoalMetric::Distance
+        );
+        for invalid in ["2 hikes", "0 km/month", "3 miles/week"] {
+            assert!(invalid.parse::<Goal>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn goal_met_this_period() {
+        let goal: Goal = "2 hikes/week".parse().unwrap();
+        let entries: Vec<(NaiveDate, f64)> =
+            days(&["2026-12-28", "2026-12-29", "2026-12-22", "2026-12-23"])
+                .into_iter()
+                .map(|d| (d, 1.0))
+                .collect();
+        let progress = goal_progress(&goal, &entries, day("2026-12-30"));
+        assert_eq!(progress.done, 2.0);
+        assert_eq!(progress.forecast, Forecast::Met(day("2026-12-29")));
+        assert_eq!(progress.met_in_a_row, 2);
+    }
+
+    #[test]
+    fn goal_forecast_at_recent_pace() {
+        let goal: Goal = "40 km/month".parse().unwrap();
+        let today = day("2026-12-10");
+
+        // 310 km since Aug 1 (132 days): 30 km to go takes 13 days
+        let busy = [
+            (day("2026-09-15"), 100.0),
+            (day("2026-10-05"), 100.0),
+            (day("2026-11-10"), 100.0),
+            (day("2026-12-02"), 10.0),
+        ];
+        let progress = goal_progress(&goal, &busy, today);
+        assert_eq!(progress.done, 10.0);
+        assert_eq!(progress.forecast, Forecast::OnTrack(day("2026-12-23")));
+        // September to November
+        assert_eq!(progress.met_in_a_row, 3);
+
+        // 40 km in 132 days: 99 days to go
+        let slow = [(day("2026-11-10"), 30.0), (day("2026-12-02"), 10.0)];
+        let progress = goal_progress(&goal, &slow, today);
+        assert_eq!(progress.forecast, Forecast::Behind(day("2027-03-19")));
+        assert_eq!(progress.met_in_a_row, 0);
+
+        // Nothing in the last four months to project from
+        let stale = [(day("2026-01-15"), 50.0)];
+        assert_eq!(
+            goal_progress(&goal, &stale, today).forecast,
+            Forecast::Unknown
+        );
+        assert_eq!(goal_progress(&goal, &[], today).forecast, Forecast::Unknown);
+    }
+}
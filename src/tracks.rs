@@ -0,0 +1,518 @@
+use crate::fit::parse_fit;
+use crate::geo::distance_km;
+use crate::services::elevation::calculate_elevation_stats;
+use crate::trails::Trail;
+use crate::xml::{attribute, elements, tag_text};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use std::fs;
+use std::path::Path;
+
+/// Slower than this (km/h) between two points counts as stopped
+const MOVING_SPEED_KMH: f64 = 1.0;
+
+/// Trail points further than this (m) from the track weren't hiked
+const MATCH_TOLERANCE_M: f64 = 60.0;
+
+/// Share of a trail's points near the track for it to count as hiked
+const MIN_COVERAGE: f64 = 0.8;
+
+/// Points compared per line when matching; keeps matching fast on long recordings
+const MATCH_SAMPLE_POINTS: usize = 300;
+
+/// Margin (degrees) around the track's bounding box when picking candidate trails
+const BBOX_MARGIN_DEG: f64 = 0.005;
+
+#[derive(Debug, Clone)]
+pub struct TrackPoint {
+    pub lat: f64,
+    pub lng: f64,
+    pub elevation: Option<f64>,
+    pub time: Option<DateTime<Utc>>,
+    pub heart_rate: Option<u8>,
+}
+
+/// Totals the device computed for the activity (FIT session message)
+#[derive(Debug, Clone, Default)]
+pub struct SessionSummary {
+    pub start_time: Option<DateTime<Utc>>,
+    pub distance_km: Option<f64>,
+    /// Barometric ascent on most watches, better than GPS altitude
+    pub ascent: Option<f64>,
+    pub calories: Option<u32>,
+    pub avg_heart_rate: Option<u8>,
+    pub max_heart_rate: Option<u8>,
+}
+
+/// A recorded GPS track
+#[derive(Debug, Clone, Default)]
+pub struct Track {
+    pub name: Option<String>,
+    pub points: Vec<TrackPoint>,
+    pub session: Option<SessionSummary>,
+}
+
+impl Track {
+    pub fn coordinates(&self) -> Vec<(f64, f64)> {
+        self.points.iter().map(|p| (p.lat, p.lng)).collect()
+    }
+
+    /// Device total when the file has one, otherwise summed between points
+    pub fn distance_km(&self) -> f64 {
+        if let Some(distance) = self.session.as_ref().and_then(|s| s.distance_km) {
+            return distance;
+        }
+        self.points
+            .windows(2)
+            .map(|w| distance_km(w[0].lat, w[0].lng, w[1].lat, w[1].lng))
+            .sum()
+    }
+
+    pub fn start_time(&self) -> Option<DateTime<Utc>> {
+        self.session
+            .as_ref()
+            .and_then(|s| s.start_time)
+            .or_else(|| self.points.iter().find_map(|p| p.time))
+    }
+
+    /// Local date the recording started
+    pub fn date(&self) -> Option<NaiveDate> {
+        self.start_time()
+            .map(|t| t.with_timezone(&Local).date_naive())
+    }
+
+    /// Time spent moving, leaving out breaks; None without timestamps or movement
+    pub fn moving_minutes(&self) -> Option<u32> {
+        let timed: Vec<&TrackPoint> = self.points.iter().filter(|p| p.time.is_some()).collect();
+        if timed.len() < 2 {
+            return None;
+        }
+
+        let seconds: i64 = timed
+            .windows(2)
+            .filter_map(|w| {
+                let elapsed = (w[1].time? - w[0].time?).num_seconds();
+                let km = distance_km(w[0].lat, w[0].lng, w[1].lat, w[1].lng);
+                (elapsed > 0 && km / (elapsed as f64 / 3600.0) >= MOVING_SPEED_KMH)
+                    .then_some(elapsed)
+            })
+            .sum();
+        let minutes = (seconds as f64 / 60.0).round() as u32;
+        (minutes > 0).then_some(minutes)
+    }
+
+    /// Average and maximum heart rate (bpm) from the session or the recorded samples
+    pub fn heart_rate(&self) -> Option<(u8, u8)> {
+        if let Some(session) = &self.session
+            && let (Some(avg), Some(max)) = (session.avg_heart_rate, session.max_heart_rate)
+        {
+            return Some((avg, max));
+        }
+        let samples: Vec<u32> = self
+            .points
+            .iter()
+            .filter_map(|p| p.heart_rate)
+            .map(u32::from)
+            .collect();
+        let max = *samples.iter().max()?;
+        let avg = samples.iter().sum::<u32>() / samples.len() as u32;
+        Some((avg as u8, max as u8))
+    }
+
+    pub fn calories(&self) -> Option<u32> {
+        self.session.as_ref().and_then(|s| s.calories)
+    }
+
+    /// Device ascent when recorded, otherwise the smoothed gain of the
+    /// recorded elevations; None when the track has neither
+    pub fn elevation_gain(&self) -> Option<f64> {
+        if let Some(ascent) = self.session.as_ref().and_then(|s| s.ascent) {
+            return Some(ascent);
+        }
+        let elevations: Vec<f64> = self.points.iter().filter_map(|p| p.elevation).collect();
+        if elevations.len() < 2 {
+            return None;
+        }
+        Some(calculate_elevation_stats(&elevations, self.distance_km()).total_gain)
+    }
+}
+
+/// Read a GPX or FIT file, by extension
+pub fn read_track(path: &Path) -> Result<Track> {
+    let is_fit = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("fit"));
+    if is_fit {
+        let bytes =
+            fs::read(path).with_context(|| format!("Failed to read track {}", path.display()))?;
+        return parse_fit(&bytes)
+            .with_context(|| format!("Failed to parse FIT {}", path.display()));
+    }
+
+    let xml = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read track {}", path.display()))?;
+    parse_gpx(&xml).with_context(|| format!("Failed to parse GPX {}", path.display()))
+}
+
+/// Track points of every `<trk>` (or the route points when there are none)
+pub fn parse_gpx(xml: &str) -> Result<Track> {
+    let (mut points, mut container) = (parse_points(xml, "trkpt"), "trk");
+    if points.is_empty() {
+        (points, container) = (parse_points(xml, "rtept"), "rte");
+    }
+    if points.len() < 2 {
+        anyhow::bail!("GPX has fewer than 2 track points");
+    }
+
+    let name = elements(xml, container)
+        .first()
+        .and_then(|(_, content)| tag_text(content, "name"))
+        .filter(|n| !n.is_empty());
+
+    Ok(Track {
+        name,
+        points,
+        session: None,
+    })
+}
+
+fn parse_points(xml: &str, tag: &str) -> Vec<TrackPoint> {
+    elements(xml, tag)
+        .into_iter()
+        .filter_map(|(attributes, content)| {
+            Some(TrackPoint {
+                lat: attribute(attributes, "lat")?.parse().ok()?,
+                lng: attribute(attributes, "lon")?.parse().ok()?,
+                elevation: tag_text(content, "ele").and_then(|e| e.parse().ok()),
+                time: tag_text(content, "time")
+                    .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+                    .map(|t| t.with_timezone(&Utc)),
+                // Garmin's TrackPointExtension, <gpxtpx:hr>
+                heart_rate: tag_text(content, "hr").and_then(|hr| hr.parse().ok()),
+            })
+        })
+        .collect()
+}
+
+/// A catalog trail the track follows
+#[derive(Debug, Clone)]
+pub struct TrailMatch<'a> {
+    pub trail: &'a Trail,
+    /// Share of the trail's points within `MATCH_TOLERANCE_M` of the track
+    pub coverage: f64,
+    /// Directed Hausdorff distance from the trail to the track, in metres
+    pub max_deviation_m: f64,
+    /// Symmetric Hausdorff distance between trail and track, in metres
+    pub hausdorff_m: f64,
+}
+
+/// Trails the track covers, best fit to the whole track first.
+///
+/// A trail is kept when most of it lies near the track (directed from the trail),
+/// so a track linking several trails matches each of them; the symmetric
+/// Hausdorff distance would reject them all. Ranking uses the symmetric distance,
+/// so the trail closest to the whole recording comes first.
+pub fn match_trails<'a>(track: &Track, trails: &'a [Trail]) -> Vec<TrailMatch<'a>> {
+    let coordinates = track.coordinates();
+    let Some(bbox) = BoundingBox::around(&coordinates, BBOX_MARGIN_DEG) else {
+        return Vec::new();
+    };
+    let track_line = LocalLine::new(&sample(&coordinates, MATCH_SAMPLE_POINTS * 4));
+    let track_points = sample(&coordinates, MATCH_SAMPLE_POINTS);
+
+    let mut matches: Vec<TrailMatch> = trails
+        .iter()
+        .filter(|t| {
+            t.coordinates_wgs84
+                .iter()
+                .any(|&(lat, lng)| bbox.contains(lat, lng))
+        })
+        .filter_map(|trail| {
+            let points = sample(&trail.coordinates_wgs84, MATCH_SAMPLE_POINTS);
+            let distances: Vec<f64> = points
+                .iter()
+                .map(|&(lat, lng)| track_line.distance_m(lat, lng))
+                .collect();
+            let near = distances
+                .iter()
+                .filter(|&&d| d <= MATCH_TOLERANCE_M)
+                .count();
+            let coverage = near as f64 / distances.len().max(1) as f64;
+            if coverage < MIN_COVERAGE {
+                return None;
+            }
+
+            let max_deviation_m = distances.iter().copied().fold(0.0, f64::max);
+            let trail_line = LocalLine::new(&points);
+            let track_deviation_m = track_points
+                .iter()
+                .map(|&(lat, lng)| trail_line.distance_m(lat, lng))
+                .fold(0.0, f64::max);
+            Some(TrailMatch {
+                trail,
+                coverage,
+                max_deviation_m,
+                hausdorff_m: max_deviation_m.max(track_deviation_m),
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        a.hausdorff_m
+            .total_cmp(&b.hausdorff_m)
+            .then(b.trail.length_km.total_cmp(&a.trail.length_km))
+    });
+    matches
+}
+
+/// Evenly spaced subset of at most `max` points, keeping the last one
+fn sample(coordinates: &[(f64, f64)], max: usize) -> Vec<(f64, f64)> {
+    if coordinates.len() <= max {
+        return coordinates.to_vec();
+    }
+    let step = coordinates.len() as f64 / max as f64;
+    let mut sampled: Vec<(f64, f64)> = (0..max)
+        .map(|i| coordinates[(i as f64 * step) as usize])
+        .collect();
+    sampled.extend(coordinates.last());
+    sampled
+}
+
+struct BoundingBox {
+    south: f64,
+    north: f64,
+    west: f64,
+    east: f64,
+}
+
+impl BoundingBox {
+    fn around(coordinates: &[(f64, f64)], margin: f64) -> Option<Self> {
+        let first = coordinates.first()?;
+        let mut bbox = Self {
+            south: first.0,
+            north: first.0,
+            west: first.1,
+            east: first.1,
+        };
+        for &(lat, lng) in coordinates {
+            bbox.south = bbox.south.min(lat);
+            bbox.north = bbox.north.max(lat);
+            bbox.west = bbox.west.min(lng);
+            bbox.east = bbox.east.max(lng);
+        }
+        bbox.south -= margin;
+        bbox.north += margin;
+        bbox.west -= margin;
+        bbox.east += margin;
+        Some(bbox)
+    }
+
+    fn contains(&self, lat: f64, lng: f64) -> bool {
+        (self.south..=self.north).contains(&lat) && (self.west..=self.east).contains(&lng)
+    }
+}
+
+/// Polyline projected to metres around its first point, accurate over a few km
+struct LocalLine {
+    origin: (f64, f64),
+    metres_per_lng: f64,
+    points: Vec<(f64, f64)>,
+}
+
+impl LocalLine {
+    const METRES_PER_LAT: f64 = 111_132.0;
+
+    fn new(coordinates: &[(f64, f64)]) -> Self {
+        let origin = coordinates.first().copied().unwrap_or_default();
+        let metres_per_lng = Self::METRES_PER_LAT * origin.0.to_radians().cos();
+        let mut line = Self {
+            origin,
+            metres_per_lng,
+            points: Vec::new(),
+        };
+        line.points = coordinates
+            .iter()
+            .map(|&(lat, lng)| line.project(lat, lng))
+            .collect();
+        line
+    }
+
+    fn project(&self, lat: f64, lng: f64) -> (f64, f64) {
+        (
+            (lng - self.origin.1) * self.metres_per_lng,
+            (lat - self.origin.0) * Self::METRES_PER_LAT,
+        )
+    }
+
+    /// Shortest distance from a point to any segment of the line
+    fn distance_m(&self, lat: f64, lng: f64) -> f64 {
+        let p = self.project(lat, lng);
+        match self.points.as_slice() {
+            [] => f64::INFINITY,
+            [only] => (p.0 - only.0).hypot(p.1 - only.1),
+            points => points
+                .windows(2)
+                .map(|w| segment_distance(p, w[0], w[1]))
+                .fold(f64::INFINITY, f64::min),
+        }
+    }
+}
+
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (x, y) = (a.0 + t * dx, a.1 + t * dy);
+    (p.0 - x).hypot(p.1 - y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// `steps` + 1 points heading east from 47°N 71°W, about 100 m apart
+    fn line(start_step: usize, steps: usize, lat_offset: f64) -> Vec<(f64, f64)> {
+        (start_step..=start_step + steps)
+            .map(|i| (47.0 + lat_offset, -71.0 + i as f64 * 0.0013))
+            .collect()
+    }
+
+    fn track(coordinates: &[(f64, f64)], seconds_apart: i64) -> Track {
+        let start = Utc.with_ymd_and_hms(2025, 7, 10, 13, 0, 0).unwrap();
+        Track {
+            name: None,
+            points: coordinates
+                .iter()
+                .enumerate()
+                .map(|(i, &(lat, lng))| TrackPoint {
+                    lat,
+                    lng,
+                    elevation: None,
+                    time: Some(start + chrono::Duration::seconds(i as i64 * seconds_apart)),
+                    heart_rate: None,
+                })
+                .collect(),
+            session: None,
+        }
+    }
+
+    fn trail(name: &str, park: &str, coordinates: Vec<(f64, f64)>) -> Trail {
+        Trail {
+            name: name.to_string(),
+            park: park.to_string(),
+            park_code: String::new(),
+            difficulty: None,
+            length_km: coordinates.len() as f64 * 0.1,
+            lat: coordinates[0].0,
+            lng: coordinates[0].1,
+            distance_from_mtl: 0.0,
+            coordinates_wgs84: coordinates,
+            elevation: None,
+        }
+    }
+
+    #[test]
+    fn best_fit_to_whole_track_comes_first() {
+        let recorded = track(&line(0, 30, 0.0), 60);
+        let trails = vec![
+            trail("First half", "A", line(0, 15, 0.0)),
+            trail("Whole way", "A", line(0, 30, 0.0)),
+            trail("Second half", "A", line(15, 15, 0.0)),
+            // Runs on past the end of the track
+            trail("Too long", "A", line(0, 60, 0.0)),
+            // Parallel, 500 m north
+            trail("Next valley", "A", line(0, 30, 0.0045)),
+        ];
+
+        let matches = match_trails(&recorded, &trails);
+        let names: Vec<&str> = matches.iter().map(|m| m.trail.name.as_str()).collect();
+        assert_eq!(names[0], "Whole way");
+        assert_eq!(names.len(), 3);
+        assert!(names.contains(&"First half") && names.contains(&"Second half"));
+
+        assert!(matches[0].hausdorff_m < 1.0);
+        assert!(matches[1].hausdorff_m > 1000.0);
+        assert!(matches.iter().all(|m| m.max_deviation_m < 1.0));
+    }
+
+    #[test]
+    fn gpx_points_in_any_attribute_style() {
+        let gpx = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test" xmlns:gpxtpx="http://www.garmin.com/xmlschemas/TrackPointExtension/v1">
+  <metadata><name>Not the track</name></metadata>
+  <trk>
+    <name>Les Loups &amp; La Croche</name>
+    <trkseg>
+      <trkpt lat="46.8" lon="-71.4"><ele>310.5</ele><time>2025-07-10T13:00:00Z</time>
+        <extensions><gpxtpx:TrackPointExtension><gpxtpx:hr>121</gpxtpx:hr></gpxtpx:TrackPointExtension></extensions>
+      </trkpt>
+      <trkpt lon='-71.41' lat='46.81'/>
+      <trkpt
+          lon = "-71.42"
+          lat = "46.82" >
+        <time>2025-07-10T09:05:00-04:00</time>
+      </trkpt>
+      <trkpt lon="-71.43"/>
+    </trkseg>
+  </trk>
+</gpx>"#;
+
+        let track = parse_gpx(gpx).unwrap();
+        assert_eq!(track.name.as_deref(), Some("Les Loups & La Croche"));
+        // The point without a latitude is dropped
+        assert_eq!(track.points.len(), 3);
+
+        let first = &track.points[0];
+        assert_eq!((first.lat, first.lng), (46.8, -71.4));
+        assert_eq!(first.elevation, Some(310.5));
+        assert_eq!(first.heart_rate, Some(121));
+
+        let second = &track.points[1];
+        assert_eq!((second.lat, second.lng), (46.81, -71.41));
+        assert_eq!(
+            (second.elevation, second.time, second.heart_rate),
+            (None, None, None)
+        );
+
+        let third = &track.points[2];
+        assert_eq!((third.lat, third.lng), (46.82, -71.42));
+        assert_eq!(
+            third.time,
+            Some(Utc.with_ymd_and_hms(2025, 7, 10, 13, 5, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn gpx_routes_stand_in_for_tracks() {
+        let gpx = r#"<gpx xmlns="http://www.topografix.com/GPX/1/1">
+  <rte>
+    <name>Planned loop</name>
+    <rtept lat="47.0" lon="-71.0"><name>Start</name></rtept>
+    <rtept lat="47.01" lon="-71.0"/>
+  </rte>
+</gpx>"#;
+        let track = parse_gpx(gpx).unwrap();
+        assert_eq!(track.name.as_deref(), Some("Planned loop"));
+        assert_eq!(track.points.len(), 2);
+        assert_eq!(track.points[1].lat, 47.01);
+
+        let one_point = r#"<gpx><trk><trkseg><trkpt lat="47" lon="-71"/></trkseg></trk></gpx>"#;
+        assert!(parse_gpx(one_point).is_err());
+    }
+
+    #[test]
+    fn moving_time_leaves_out_stops() {
+        // 100 m a minute is 6 km/h
+        assert_eq!(track(&line(0, 30, 0.0), 60).moving_minutes(), Some(30));
+
+        let standing = vec![(47.0, -71.0); 10];
+        assert_eq!(track(&standing, 60).moving_minutes(), None);
+        assert_eq!(track(&line(0, 5, 0.0), 0).moving_minutes(), None);
+    }
+}
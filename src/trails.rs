@@ -2,8 +2,11 @@ use crate::cache;
 use crate::geo::{
     MONTREAL_LAT, MONTREAL_LNG, distance_km, extract_all_coordinates, lambert_to_wgs84,
 };
+use crate::services::elevation::calculate_elevation_stats;
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeSet;
 use std::collections::hash_map::Entry;
 use std::fmt;
 use std::str::FromStr;
@@ -19,9 +22,44 @@ pub struct Trail {
     pub lng: f64,
     pub distance_from_mtl: f64,
     pub coordinates_wgs84: Vec<(f64, f64)>,
+    /// Known once the profile is in the elevation cache (see `rando update --elevation`)
+    pub elevation: Option<TrailElevation>,
 }
 
+/// Climb summary of a trail's cached profile, in metres
+#[derive(Debug, Clone, Copy)]
+pub struct TrailElevation {
+    pub gain: f64,
+    pub loss: f64,
+    pub max: f64,
+}
+
+/// Orders for `rando list --sort`
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailSort {
+    /// Most gain first
+    Climb,
+    /// Longest first
+    Length,
+    /// Closest to Montreal first
+    Distance,
+}
+
+impl FromStr for TrailSort {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "climb" | "gain" => Ok(TrailSort::Climb),
+            "length" => Ok(TrailSort::Length),
+            "distance" => Ok(TrailSort::Distance),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Difficulty {
     Facile,
     Intermediaire,
@@ -137,6 +175,7 @@ pub fn load_trails() -> Result<Vec<Trail>> {
             lng,
             distance_from_mtl,
             coordinates_wgs84,
+            elevation: None,
         };
 
         let key = (name, park);
@@ -154,9 +193,37 @@ pub fn load_trails() -> Result<Vec<Trail>> {
     let mut trails: Vec<Trail> = trail_map.into_values().collect();
     trails.sort_by(|a, b| a.park.cmp(&b.park).then_with(|| a.name.cmp(&b.name)));
 
+    let elevations = cache::get_all_cached_elevations();
+    for trail in &mut trails {
+        if let Some(profile) = elevations.get(&cache::elevation_key(&trail.name, &trail.park))
+            && !profile.is_empty()
+        {
+            let stats = calculate_elevation_stats(profile, trail.length_km);
+            trail.elevation = Some(TrailElevation {
+                gain: stats.total_gain,
+                loss: stats.total_loss,
+                max: stats.max,
+            });
+        }
+    }
+
     Ok(trails)
 }
 
+/// Sort in place; trails without the needed data go last
+pub fn sort_trails(trails: &mut [Trail], sort: TrailSort) {
+    match sort {
+        TrailSort::Climb => trails.sort_by(|a, b| {
+            let gain = |t: &Trail| t.elevation.map(|e| e.gain).unwrap_or(f64::NEG_INFINITY);
+            gain(b).total_cmp(&gain(a))
+        }),
+        TrailSort::Length => trails.sort_by(|a, b| b.length_km.total_cmp(&a.length_km)),
+        TrailSort::Distance => {
+            trails.sort_by(|a, b| a.distance_from_mtl.total_cmp(&b.distance_from_mtl))
+        }
+    }
+}
+
 pub fn filter_trails(
     trails: &[Trail],
     difficulty: Option<Difficulty>,
@@ -210,9 +277,72 @@ pub fn find_trail_by_name<'a>(trails: &'a [Trail], name: &str) -> Option<&'a Tra
         .find(|t| t.name.to_lowercase().contains(&name.to_lowercase()))
 }
 
-pub fn get_trails_by_park<'a>(trails: &'a [Trail], park_code: &str) -> Vec<&'a Trail> {
+/// Names of the parks a `--park` argument picks out of (name, code) pairs:
+/// those with that code, or else those whose name contains it (so "Oka" works too)
+pub fn match_parks<'a>(
+    query: &str,
+    parks: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> BTreeSet<&'a str> {
+    let parks: Vec<(&str, &str)> = parks.into_iter().collect();
+    let by_code: BTreeSet<&str> = parks
+        .iter()
+        .filter(|(_, code)| code.eq_ignore_ascii_case(query.trim()))
+        .map(|(name, _)| *name)
+        .collect();
+    if !by_code.is_empty() {
+        return by_code;
+    }
+
+    let query = query.trim().to_lowercase();
+    parks
+        .iter()
+        .filter(|(name, _)| name.to_lowercase().contains(&query))
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// Trails of the parks matching a code or part of a name, as in `match_parks`
+pub fn find_trails_by_park<'a>(trails: &'a [Trail], park: &str) -> Vec<&'a Trail> {
+    let parks = match_parks(
+        park,
+        trails
+            .iter()
+            .map(|t| (t.park.as_str(), t.park_code.as_str())),
+    );
     trails
         .iter()
-        .filter(|t| t.park_code.eq_ignore_ascii_case(park_code))
+        .filter(|t| parks.contains(t.park.as_str()))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARKS: [(&str, &str); 3] = [
+        ("Parc national de la Jacques-Cartier", "PJC"),
+        ("Parc national d'Oka", "PNO"),
+        ("Parc national du Mont-Orford", "MOR"),
+    ];
+
+    fn matches(query: &str) -> Vec<&'static str> {
+        match_parks(query, PARKS).into_iter().collect()
+    }
+
+    #[test]
+    fn park_codes_come_first() {
+        assert_eq!(matches("pjc"), vec!["Parc national de la Jacques-Cartier"]);
+        assert_eq!(matches(" MOR "), vec!["Parc national du Mont-Orford"]);
+    }
+
+    #[test]
+    fn short_names_fall_back_to_name_match() {
+        assert_eq!(matches("Oka"), vec!["Parc national d'Oka"]);
+        assert_eq!(
+            matches("jacques"),
+            vec!["Parc national de la Jacques-Cartier"]
+        );
+        assert_eq!(matches("national").len(), 3);
+        assert!(matches("Bic").is_empty());
+    }
+}
@@ -1,5 +1,11 @@
+use crate::data::logs::HikeLog;
+use crate::profile::{Climb, ElevationProfile, Segment};
 use crate::services::elevation::calculate_elevation_stats;
-use crate::trails::Trail;
+use crate::trails::{Difficulty, Trail};
+use crate::units;
+use chrono::NaiveDate;
+use ratatui::widgets::TableState;
+use std::collections::HashMap;
 
 pub struct CompareApp {
     pub trail1_name: String,
@@ -10,6 +16,7 @@ pub struct CompareApp {
     pub trail2_length: f64,
     pub trail1_difficulty: String,
     pub trail2_difficulty: String,
+    /// Chart points (distance, elevation) in display units
     pub trail1_elevation: Vec<(f64, f64)>,
     pub trail2_elevation: Vec<(f64, f64)>,
     pub trail1_gain: f64,
@@ -18,18 +25,28 @@ pub struct CompareApp {
     pub trail2_max: f64,
     pub trail1_min: f64,
     pub trail2_min: f64,
+    /// Steepest 500m uphill
+    pub trail1_steepest: Option<Segment>,
+    pub trail2_steepest: Option<Segment>,
+    pub trail1_climbs: Vec<Climb>,
+    pub trail2_climbs: Vec<Climb>,
 }
 
 impl CompareApp {
-    pub fn new(trail1: &Trail, trail2: &Trail, elev1: &[f64], elev2: &[f64]) -> Self {
+    pub fn new(
+        trail1: &Trail,
+        trail2: &Trail,
+        profile1: &ElevationProfile,
+        profile2: &ElevationProfile,
+    ) -> Self {
         let len1 = trail1.length_km;
         let len2 = trail2.length_km;
 
-        let trail1_elevation = normalize_elevation(elev1, len1);
-        let trail2_elevation = normalize_elevation(elev2, len2);
+        let trail1_elevation = chart_points(profile1);
+        let trail2_elevation = chart_points(profile2);
 
-        let stats1 = calculate_elevation_stats(elev1);
-        let stats2 = calculate_elevation_stats(elev2);
+        let stats1 = calculate_elevation_stats(&profile1.elevations(), profile1.length_km());
+        let stats2 = calculate_elevation_stats(&profile2.elevations(), profile2.length_km());
 
         Self {
             trail1_name: trail1.name.clone(),
@@ -54,25 +71,224 @@ impl CompareApp {
             trail2_min: stats2.min,
             trail1_elevation,
             trail2_elevation,
+            trail1_steepest: profile1.steepest(0.5, true),
+            trail2_steepest: profile2.steepest(0.5, true),
+            trail1_climbs: profile1.climbs(),
+            trail2_climbs: profile2.climbs(),
         }
     }
 }
 
-fn normalize_elevation(elevations: &[f64], total_distance: f64) -> Vec<(f64, f64)> {
-    if elevations.is_empty() || total_distance <= 0.0 {
-        return vec![];
+/// Profile points converted to display units
+fn chart_points(profile: &ElevationProfile) -> Vec<(f64, f64)> {
+    let units = units::get();
+    profile
+        .points
+        .iter()
+        .map(|p| {
+            (
+                units.distance_value(p.distance_km),
+                units.elevation_value(p.elevation),
+            )
+        })
+        .collect()
+}
+
+/// Columns of the hike table, also the sort keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HikeColumn {
+    Date,
+    Trail,
+    Park,
+    Distance,
+    Time,
+    Gain,
+}
+
+impl HikeColumn {
+    pub const ALL: [HikeColumn; 6] = [
+        HikeColumn::Date,
+        HikeColumn::Trail,
+        HikeColumn::Park,
+        HikeColumn::Distance,
+        HikeColumn::Time,
+        HikeColumn::Gain,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HikeColumn::Date => "Date",
+            HikeColumn::Trail => "Trail",
+            HikeColumn::Park => "Park",
+            HikeColumn::Distance => "Distance",
+            HikeColumn::Time => "Time",
+            HikeColumn::Gain => "Gain",
+        }
     }
 
-    elevations
+    pub fn next(&self) -> Self {
+        let idx = Self::ALL.iter().position(|c| c == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(&self) -> Self {
+        let idx = Self::ALL.iter().position(|c| c == self).unwrap_or(0);
+        Self::ALL[(idx + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+pub struct HikeRow {
+    pub date: String,
+    pub trail: String,
+    pub park: String,
+    pub km: f64,
+    pub minutes: Option<u32>,
+    pub gain: Option<f64>,
+}
+
+pub struct StatsApp {
+    /// Scope of the stats, e.g. " (2025)"
+    pub title: String,
+    pub first_date: NaiveDate,
+    pub last_date: NaiveDate,
+    /// Chart points (days since the first hike, total distance) in display units
+    pub cumulative: Vec<(f64, f64)>,
+    /// Distance hiked each day, in km
+    pub days: HashMap<NaiveDate, f64>,
+    /// Park code (or name) and distance in km, most hiked first
+    pub parks: Vec<(String, f64)>,
+    /// Hikes per catalog difficulty, then "Unknown" if any
+    pub difficulties: Vec<(String, u64)>,
+    pub rows: Vec<HikeRow>,
+    pub sort: HikeColumn,
+    pub descending: bool,
+    pub table: TableState,
+}
+
+impl StatsApp {
+    /// `hikes` must be sorted by date, as `LogsData::between` returns them
+    pub fn new(title: &str, hikes: &[&HikeLog], trails: &[Trail]) -> Self {
+        let units = units::get();
+        let dated: Vec<(NaiveDate, &HikeLog)> = hikes
+            .iter()
+            .filter_map(|h| {
+                NaiveDate::parse_from_str(&h.date, "%Y-%m-%d")
+                    .ok()
+                    .map(|d| (d, *h))
+            })
+            .collect();
+        let today = chrono::Local::now().date_naive();
+        let first_date = dated.first().map_or(today, |(d, _)| *d);
+        let last_date = dated.last().map_or(today, |(d, _)| *d);
+
+        let mut cumulative = vec![(0.0, 0.0)];
+        let mut total = 0.0;
+        let mut days: HashMap<NaiveDate, f64> = HashMap::new();
+        let mut parks: HashMap<String, f64> = HashMap::new();
+        let mut by_difficulty: HashMap<Difficulty, u64> = HashMap::new();
+        let mut unknown = 0;
+        for (date, hike) in &dated {
+            total += hike.hiked_km();
+            cumulative.push((
+                (*date - first_date).num_days() as f64,
+                units.distance_value(total),
+            ));
+            *days.entry(*date).or_insert(0.0) += hike.hiked_km();
+
+            let trail = hike.trail(trails);
+            let park = trail.map_or_else(|| hike.park.clone(), |t| t.park_code.to_uppercase());
+            *parks.entry(park).or_insert(0.0) += hike.hiked_km();
+            match trail.and_then(|t| t.difficulty) {
+                Some(difficulty) => *by_difficulty.entry(difficulty).or_insert(0) += 1,
+                None => unknown += 1,
+            }
+        }
+
+        let mut parks: Vec<(String, f64)> = parks.into_iter().collect();
+        parks.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let mut difficulties: Vec<(String, u64)> = [
+            Difficulty::Facile,
+            Difficulty::Intermediaire,
+            Difficulty::Difficile,
+        ]
         .iter()
-        .enumerate()
-        .map(|(i, &elev)| {
-            let distance = if elevations.len() > 1 {
-                (i as f64 / (elevations.len() - 1) as f64) * total_distance
-            } else {
-                0.0
+        .map(|d| (d.to_string(), by_difficulty.get(d).copied().unwrap_or(0)))
+        .collect();
+        if unknown > 0 {
+            difficulties.push(("Unknown".to_string(), unknown));
+        }
+
+        let rows = hikes
+            .iter()
+            .map(|h| HikeRow {
+                date: h.date.clone(),
+                trail: h.trail_name.clone(),
+                park: h.park.clone(),
+                km: h.hiked_km(),
+                minutes: h.duration_minutes,
+                gain: h.gain(trails),
+            })
+            .collect();
+
+        let mut app = Self {
+            title: title.to_string(),
+            first_date,
+            last_date,
+            cumulative,
+            days,
+            parks,
+            difficulties,
+            rows,
+            sort: HikeColumn::Date,
+            descending: true,
+            table: TableState::default().with_selected(Some(0)),
+        };
+        app.sort_rows();
+        app
+    }
+
+    pub fn sort_by(&mut self, column: HikeColumn) {
+        self.sort = column;
+        // Text sorts A to Z, numbers and dates largest first
+        self.descending = !matches!(column, HikeColumn::Trail | HikeColumn::Park);
+        self.sort_rows();
+    }
+
+    pub fn reverse(&mut self) {
+        self.descending = !self.descending;
+        self.sort_rows();
+    }
+
+    fn sort_rows(&mut self) {
+        let column = self.sort;
+        self.rows.sort_by(|a, b| {
+            let order = match column {
+                HikeColumn::Date => a.date.cmp(&b.date),
+                HikeColumn::Trail => a.trail.cmp(&b.trail),
+                HikeColumn::Park => a.park.cmp(&b.park),
+                HikeColumn::Distance => a.km.total_cmp(&b.km),
+                HikeColumn::Time => a.minutes.cmp(&b.minutes),
+                HikeColumn::Gain => a.gain.unwrap_or(-1.0).total_cmp(&b.gain.unwrap_or(-1.0)),
             };
-            (distance, elev)
-        })
-        .collect()
+            // Ties keep date order
+            order.then(a.date.cmp(&b.date))
+        });
+        if self.descending {
+            self.rows.reverse();
+        }
+        self.table.select(Some(0));
+    }
+
+    /// Move the selected row, clamped to the table
+    pub fn scroll(&mut self, rows: isize) {
+        let last = self.rows.len().saturating_sub(1);
+        let selected = self.table.selected().unwrap_or(0);
+        let selected = if rows < 0 {
+            selected.saturating_sub(rows.unsigned_abs())
+        } else {
+            selected.saturating_add(rows as usize).min(last)
+        };
+        self.table.select(Some(selected));
+    }
 }
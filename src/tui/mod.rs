@@ -1,13 +1,15 @@
 pub mod app;
 pub mod ui;
 
+use crate::data::logs::HikeLog;
+use crate::profile::ElevationProfile;
 use crate::trails::Trail;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use ratatui::{Terminal, backend::CrosstermBackend};
+use ratatui::{Frame, Terminal, backend::CrosstermBackend};
 use std::io::{self, Stdout};
 
 /// RAII guard for terminal state cleanup
@@ -27,11 +29,11 @@ impl Drop for TerminalGuard {
     }
 }
 
-pub fn run_compare_tui(
-    trail1: &Trail,
-    trail2: &Trail,
-    elev1: &[f64],
-    elev2: &[f64],
+/// Draw the app and pass it key presses until `on_key` returns true
+fn run<A>(
+    app: &mut A,
+    draw: impl Fn(&mut Frame, &mut A),
+    mut on_key: impl FnMut(&mut A, KeyCode) -> bool,
 ) -> io::Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -40,20 +42,51 @@ pub fn run_compare_tui(
     let terminal = Terminal::new(backend)?;
 
     let mut guard = TerminalGuard { terminal };
-    let app = app::CompareApp::new(trail1, trail2, elev1, elev2);
 
     loop {
-        guard.terminal.draw(|frame| ui::draw_compare(frame, &app))?;
+        guard.terminal.draw(|frame| draw(frame, app))?;
 
         if let Event::Key(key) = event::read()?
             && key.kind == KeyEventKind::Press
+            && on_key(app, key.code)
         {
-            match key.code {
-                KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => break,
-                _ => {}
-            }
+            break;
         }
     }
 
     Ok(())
 }
+
+pub fn run_compare_tui(
+    trail1: &Trail,
+    trail2: &Trail,
+    profile1: &ElevationProfile,
+    profile2: &ElevationProfile,
+) -> io::Result<()> {
+    let mut app = app::CompareApp::new(trail1, trail2, profile1, profile2);
+    run(
+        &mut app,
+        |frame, app| ui::draw_compare(frame, app),
+        |_, code| matches!(code, KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter),
+    )
+}
+
+pub fn run_stats_tui(title: &str, hikes: &[&HikeLog], trails: &[Trail]) -> io::Result<()> {
+    let mut app = app::StatsApp::new(title, hikes, trails);
+    run(&mut app, ui::draw_stats, |app, code| {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Down | KeyCode::Char('j') => app.scroll(1),
+            KeyCode::Up | KeyCode::Char('k') => app.scroll(-1),
+            KeyCode::PageDown => app.scroll(10),
+            KeyCode::PageUp => app.scroll(-10),
+            KeyCode::Home => app.scroll(isize::MIN),
+            KeyCode::End => app.scroll(isize::MAX),
+            KeyCode::Char('s') | KeyCode::Right => app.sort_by(app.sort.next()),
+            KeyCode::Left => app.sort_by(app.sort.prev()),
+            KeyCode::Char('r') => app.reverse(),
+            _ => {}
+        }
+        false
+    })
+}
@@ -4,16 +4,22 @@ use ratatui::{
     style::{Color, Modifier, Style},
     symbols,
     text::{Line, Span},
-    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph},
+    widgets::{
+        Axis, Bar, BarChart, BarGroup, Block, Borders, Chart, Dataset, GraphType, Paragraph, Row,
+        Table,
+    },
 };
 
-use super::app::CompareApp;
+use super::app::{CompareApp, HikeColumn, StatsApp};
+use crate::profile::{Climb, Segment};
+use crate::units;
+use chrono::{Datelike, Duration, NaiveDate};
 
 pub fn draw_compare(frame: &mut Frame, app: &CompareApp) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(10),
+            Constraint::Length(11),
             Constraint::Min(15),
             Constraint::Length(3),
         ])
@@ -25,6 +31,7 @@ pub fn draw_compare(frame: &mut Frame, app: &CompareApp) {
 }
 
 fn draw_stats_comparison(frame: &mut Frame, app: &CompareApp, area: Rect) {
+    let units = units::get();
     let columns = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
@@ -42,12 +49,23 @@ fn draw_stats_comparison(frame: &mut Frame, app: &CompareApp, area: Rect) {
 
     let trail1_text = vec![
         Line::from(format!("Park: {}", app.trail1_park)),
-        Line::from(format!("Length: {:.1} km", app.trail1_length)),
+        Line::from(format!("Length: {}", units.distance(app.trail1_length, 1))),
         Line::from(format!("Difficulty: {}", app.trail1_difficulty)),
         Line::from(""),
-        Line::from(format!("Elevation gain: {:.0} m", app.trail1_gain)),
-        Line::from(format!("Max elevation: {:.0} m", app.trail1_max)),
-        Line::from(format!("Min elevation: {:.0} m", app.trail1_min)),
+        Line::from(format!(
+            "Elevation gain: {}",
+            units.elevation(app.trail1_gain)
+        )),
+        Line::from(format!(
+            "Max elevation: {}",
+            units.elevation(app.trail1_max)
+        )),
+        Line::from(format!(
+            "Min elevation: {}",
+            units.elevation(app.trail1_min)
+        )),
+        Line::from(steepest_text(app.trail1_steepest.as_ref())),
+        Line::from(climbs_text(&app.trail1_climbs)),
     ];
 
     let trail1_para = Paragraph::new(trail1_text)
@@ -68,12 +86,23 @@ fn draw_stats_comparison(frame: &mut Frame, app: &CompareApp, area: Rect) {
 
     let trail2_text = vec![
         Line::from(format!("Park: {}", app.trail2_park)),
-        Line::from(format!("Length: {:.1} km", app.trail2_length)),
+        Line::from(format!("Length: {}", units.distance(app.trail2_length, 1))),
         Line::from(format!("Difficulty: {}", app.trail2_difficulty)),
         Line::from(""),
-        Line::from(format!("Elevation gain: {:.0} m", app.trail2_gain)),
-        Line::from(format!("Max elevation: {:.0} m", app.trail2_max)),
-        Line::from(format!("Min elevation: {:.0} m", app.trail2_min)),
+        Line::from(format!(
+            "Elevation gain: {}",
+            units.elevation(app.trail2_gain)
+        )),
+        Line::from(format!(
+            "Max elevation: {}",
+            units.elevation(app.trail2_max)
+        )),
+        Line::from(format!(
+            "Min elevation: {}",
+            units.elevation(app.trail2_min)
+        )),
+        Line::from(steepest_text(app.trail2_steepest.as_ref())),
+        Line::from(climbs_text(&app.trail2_climbs)),
     ];
 
     let trail2_para = Paragraph::new(trail2_text)
@@ -83,6 +112,26 @@ fn draw_stats_comparison(frame: &mut Frame, app: &CompareApp, area: Rect) {
     frame.render_widget(trail2_para, columns[1]);
 }
 
+fn steepest_text(steepest: Option<&Segment>) -> String {
+    let units = units::get();
+    match steepest {
+        Some(s) => format!(
+            "Steepest 500m: {}% at {}",
+            units.number(s.grade(), 0),
+            units.distance(s.start_km, 1)
+        ),
+        None => "Steepest 500m: -".to_string(),
+    }
+}
+
+fn climbs_text(climbs: &[Climb]) -> String {
+    let hardest = climbs.iter().filter_map(|c| c.category).max();
+    match hardest {
+        Some(category) => format!("Climbs: {} (hardest {})", climbs.len(), category.label()),
+        None => format!("Climbs: {}", climbs.len()),
+    }
+}
+
 fn draw_elevation_chart(frame: &mut Frame, app: &CompareApp, area: Rect) {
     let mut datasets = Vec::new();
 
@@ -119,9 +168,12 @@ fn draw_elevation_chart(frame: &mut Frame, app: &CompareApp, area: Rect) {
         return;
     }
 
-    let max_distance = app.trail1_length.max(app.trail2_length).max(1.0);
-    let min_elev = app.trail1_min.min(app.trail2_min) - 20.0;
-    let max_elev = app.trail1_max.max(app.trail2_max) + 20.0;
+    let units = units::get();
+    let max_distance = units
+        .distance_value(app.trail1_length.max(app.trail2_length))
+        .max(1.0);
+    let min_elev = units.elevation_value(app.trail1_min.min(app.trail2_min) - 20.0);
+    let max_elev = units.elevation_value(app.trail1_max.max(app.trail2_max) + 20.0);
     let elev_range = (max_elev - min_elev).max(1.0);
     let min_el = min_elev;
     let max_el = min_elev + elev_range;
@@ -134,18 +186,18 @@ fn draw_elevation_chart(frame: &mut Frame, app: &CompareApp, area: Rect) {
         )
         .x_axis(
             Axis::default()
-                .title("Distance (km)")
+                .title(format!("Distance ({})", units.distance_unit()))
                 .style(Style::default().fg(Color::Gray))
                 .bounds([0.0, max_distance])
                 .labels(vec![
                     Span::raw("0"),
-                    Span::raw(format!("{:.0}", max_distance / 2.0)),
-                    Span::raw(format!("{:.0}", max_distance)),
+                    Span::raw(units.number(max_distance / 2.0, 0)),
+                    Span::raw(units.number(max_distance, 0)),
                 ]),
         )
         .y_axis(
             Axis::default()
-                .title("Elevation (m)")
+                .title(format!("Elevation ({})", units.elevation_unit()))
                 .style(Style::default().fg(Color::Gray))
                 .bounds([min_el, max_el])
                 .labels(vec![
@@ -174,3 +226,283 @@ fn draw_help_bar(frame: &mut Frame, area: Rect) {
 
     frame.render_widget(help, area);
 }
+
+pub fn draw_stats(frame: &mut Frame, app: &mut StatsApp) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Min(6),
+            Constraint::Length(3),
+        ])
+        .split(frame.size());
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(45),
+            Constraint::Percentage(30),
+            Constraint::Percentage(25),
+        ])
+        .split(chunks[0]);
+
+    draw_cumulative_chart(frame, app, top[0]);
+    draw_park_bars(frame, app, top[1]);
+    draw_difficulty_bars(frame, app, top[2]);
+    draw_heatmap(frame, app, chunks[1]);
+    draw_hike_table(frame, app, chunks[2]);
+    draw_stats_help_bar(frame, chunks[3]);
+}
+
+fn draw_cumulative_chart(frame: &mut Frame, app: &StatsApp, area: Rect) {
+    let units = units::get();
+    let span = app
+        .cumulative
+        .last()
+        .map_or(0.0, |(days, _)| *days)
+        .max(1.0);
+    let total = app
+        .cumulative
+        .last()
+        .map_or(0.0, |(_, distance)| *distance)
+        .max(1.0);
+    let middle = app.first_date + Duration::days((span / 2.0) as i64);
+
+    let dataset = Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(Color::Cyan))
+        .data(&app.cumulative);
+
+    let chart = Chart::new(vec![dataset])
+        .block(
+            Block::default()
+                .title(format!(" Cumulative distance{} ", app.title))
+                .borders(Borders::ALL),
+        )
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .bounds([0.0, span])
+                .labels(vec![
+                    Span::raw(app.first_date.format("%b %Y").to_string()),
+                    Span::raw(middle.format("%b %Y").to_string()),
+                    Span::raw(app.last_date.format("%b %Y").to_string()),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .title(format!("Distance ({})", units.distance_unit()))
+                .style(Style::default().fg(Color::Gray))
+                .bounds([0.0, total])
+                .labels(vec![
+                    Span::raw("0"),
+                    Span::raw(units.number(total / 2.0, 0)),
+                    Span::raw(units.number(total, 0)),
+                ]),
+        );
+
+    frame.render_widget(chart, area);
+}
+
+fn draw_park_bars(frame: &mut Frame, app: &StatsApp, area: Rect) {
+    let units = units::get();
+    let bars: Vec<Bar> = app
+        .parks
+        .iter()
+        .map(|(park, km)| {
+            Bar::default()
+                .value(units.distance_value(*km).round() as u64)
+                .label(Line::from(park.as_str()))
+                .text_value(units.distance(*km, 0))
+        })
+        .collect();
+
+    let chart = BarChart::default()
+        .block(
+            Block::default()
+                .title(" Distance by park ")
+                .borders(Borders::ALL),
+        )
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(0)
+        .bar_style(Style::default().fg(Color::Cyan))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Cyan))
+        .data(BarGroup::default().bars(&bars));
+
+    frame.render_widget(chart, area);
+}
+
+fn draw_difficulty_bars(frame: &mut Frame, app: &StatsApp, area: Rect) {
+    let bars: Vec<Bar> = app
+        .difficulties
+        .iter()
+        .map(|(difficulty, count)| {
+            let color = match difficulty.as_str() {
+                "Facile" => Color::Green,
+                "Intermédiaire" => Color::Yellow,
+                "Difficile" => Color::Red,
+                _ => Color::Gray,
+            };
+            Bar::default()
+                .value(*count)
+                .label(Line::from(difficulty.as_str()))
+                .style(Style::default().fg(color))
+                .value_style(Style::default().fg(Color::Black).bg(color))
+        })
+        .collect();
+
+    let chart = BarChart::default()
+        .block(Block::default().title(" Difficulty ").borders(Borders::ALL))
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(1)
+        .data(BarGroup::default().bars(&bars));
+
+    frame.render_widget(chart, area);
+}
+
+/// One column per week up to the last hike, one row per weekday, shaded by distance
+fn draw_heatmap(frame: &mut Frame, app: &StatsApp, area: Rect) {
+    const DAY_LABEL_WIDTH: u16 = 4;
+    let weeks = (area.width.saturating_sub(2 + DAY_LABEL_WIDTH) / 2).clamp(1, 53) as i64;
+    let last_monday =
+        app.last_date - Duration::days(i64::from(app.last_date.weekday().num_days_from_monday()));
+    let first_monday = last_monday - Duration::weeks(weeks - 1);
+
+    // Month names above the week they start in
+    let mut header = vec![' '; (DAY_LABEL_WIDTH as usize) + weeks as usize * 2 + 3];
+    let mut previous_month = None;
+    for week in 0..weeks {
+        let monday = first_monday + Duration::weeks(week);
+        let month = (monday + Duration::days(6)).month();
+        if previous_month.is_some_and(|m| m != month) || week == 0 {
+            let name = (monday + Duration::days(6)).format("%b").to_string();
+            let start = DAY_LABEL_WIDTH as usize + week as usize * 2;
+            for (i, c) in name.chars().enumerate() {
+                header[start + i] = c;
+            }
+        }
+        previous_month = Some(month);
+    }
+    let mut lines = vec![Line::from(header.into_iter().collect::<String>())];
+
+    for (row, day) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+        let mut spans = vec![Span::raw(format!("{:<4}", day))];
+        for week in 0..weeks {
+            let date = first_monday + Duration::days(week * 7 + row as i64);
+            spans.push(heatmap_cell(app, date));
+            spans.push(Span::raw(" "));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let heatmap = Paragraph::new(lines).block(
+        Block::default()
+            .title(format!(
+                " Hiking days, weeks to {} ",
+                app.last_date.format("%b %-d, %Y")
+            ))
+            .borders(Borders::ALL),
+    );
+
+    frame.render_widget(heatmap, area);
+}
+
+fn heatmap_cell(app: &StatsApp, date: NaiveDate) -> Span<'static> {
+    if date > app.last_date {
+        return Span::raw(" ");
+    }
+    match app.days.get(&date) {
+        None => Span::styled("·", Style::default().fg(Color::DarkGray)),
+        Some(&km) => {
+            let color = if km < 5.0 {
+                Color::Rgb(14, 110, 50)
+            } else if km < 10.0 {
+                Color::Rgb(38, 166, 65)
+            } else if km < 20.0 {
+                Color::Rgb(57, 211, 83)
+            } else {
+                Color::Rgb(150, 255, 150)
+            };
+            Span::styled("■", Style::default().fg(color))
+        }
+    }
+}
+
+fn draw_hike_table(frame: &mut Frame, app: &mut StatsApp, area: Rect) {
+    let units = units::get();
+    let header = Row::new(HikeColumn::ALL.iter().map(|column| {
+        if *column == app.sort {
+            let arrow = if app.descending { "▼" } else { "▲" };
+            Span::styled(
+                format!("{} {}", column.label(), arrow),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Span::styled(
+                column.label(),
+                Style::default().add_modifier(Modifier::BOLD),
+            )
+        }
+    }));
+
+    let rows = app.rows.iter().map(|hike| {
+        Row::new(vec![
+            hike.date.clone(),
+            hike.trail.clone(),
+            hike.park.clone(),
+            units.distance(hike.km, 1),
+            hike.minutes
+                .map(|m| format!("{}h{:02}", m / 60, m % 60))
+                .unwrap_or_else(|| "-".into()),
+            hike.gain
+                .map(|g| units.elevation(g))
+                .unwrap_or_else(|| "-".into()),
+        ])
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Min(20),
+            Constraint::Min(20),
+            Constraint::Length(10),
+            Constraint::Length(7),
+            Constraint::Length(8),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .title(format!(" Hikes ({}) ", app.rows.len()))
+            .borders(Borders::ALL),
+    )
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    frame.render_stateful_widget(table, area, &mut app.table);
+}
+
+fn draw_stats_help_bar(frame: &mut Frame, area: Rect) {
+    let key = Style::default().bg(Color::DarkGray).fg(Color::White);
+    let help_text = Line::from(vec![
+        Span::styled(" q ", key),
+        Span::raw(" Quit  "),
+        Span::styled(" ↑↓ ", key),
+        Span::raw(" Scroll  "),
+        Span::styled(" s ←→ ", key),
+        Span::raw(" Sort column  "),
+        Span::styled(" r ", key),
+        Span::raw(" Reverse "),
+    ]);
+
+    let help = Paragraph::new(help_text)
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default());
+
+    frame.render_widget(help, area);
+}
@@ -0,0 +1,209 @@
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+const KM_PER_MILE: f64 = 1.609_344;
+const M_PER_FOOT: f64 = 0.3048;
+const MM_PER_INCH: f64 = 25.4;
+
+static UNITS: OnceLock<Units> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystem {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+impl FromStr for UnitSystem {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "metric" => Ok(UnitSystem::Metric),
+            "imperial" => Ok(UnitSystem::Imperial),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Clock {
+    #[default]
+    #[serde(rename = "24h")]
+    H24,
+    #[serde(rename = "12h")]
+    H12,
+}
+
+impl FromStr for Clock {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "24h" | "24" => Ok(Clock::H24),
+            "12h" | "12" => Ok(Clock::H12),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Display preferences applied to every number printed by the CLI and TUI
+#[derive(Debug, Clone, Copy)]
+pub struct Units {
+    pub system: UnitSystem,
+    pub clock: Clock,
+    pub decimal_separator: char,
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self {
+            system: UnitSystem::Metric,
+            clock: Clock::H24,
+            decimal_separator: '.',
+        }
+    }
+}
+
+/// Set the display units for the rest of the run (first call wins)
+pub fn init(units: Units) {
+    let _ = UNITS.set(units);
+}
+
+/// Display units chosen at startup, metric/24h until `init` is called
+pub fn get() -> &'static Units {
+    UNITS.get_or_init(Units::default)
+}
+
+impl Units {
+    fn imperial(&self) -> bool {
+        self.system == UnitSystem::Imperial
+    }
+
+    /// Format a number with the configured decimal separator
+    pub fn number(&self, value: f64, decimals: usize) -> String {
+        let text = format!("{:.*}", decimals, value);
+        if self.decimal_separator == '.' {
+            text
+        } else {
+            text.replace('.', &self.decimal_separator.to_string())
+        }
+    }
+
+    pub fn distance_unit(&self) -> &'static str {
+        if self.imperial() { "mi" } else { "km" }
+    }
+
+    pub fn elevation_unit(&self) -> &'static str {
+        if self.imperial() { "ft" } else { "m" }
+    }
+
+    /// Kilometres converted to the display unit
+    pub fn distance_value(&self, km: f64) -> f64 {
+        if self.imperial() {
+            km / KM_PER_MILE
+        } else {
+            km
+        }
+    }
+
+    /// Metres converted to the display unit
+    pub fn elevation_value(&self, m: f64) -> f64 {
+        if self.imperial() { m / M_PER_FOOT } else { m }
+    }
+
+    pub fn distance(&self, km: f64, decimals: usize) -> String {
+        format!(
+            "{}{}",
+            self.number(self.distance_value(km), decimals),
+            self.distance_unit()
+        )
+    }
+
+    pub fn elevation(&self, m: f64) -> String {
+        format!(
+            "{}{}",
+            self.number(self.elevation_value(m), 0),
+            self.elevation_unit()
+        )
+    }
+
+    pub fn temperature(&self, celsius: f64) -> String {
+        if self.imperial() {
+            format!("{}°F", self.number(celsius * 9.0 / 5.0 + 32.0, 0))
+        } else {
+            format!("{}°C", self.number(celsius, 0))
+        }
+    }
+
+    pub fn speed(&self, kmh: f64) -> String {
+        if self.imperial() {
+            format!("{}mph", self.number(kmh / KM_PER_MILE, 0))
+        } else {
+            format!("{}km/h", self.number(kmh, 0))
+        }
+    }
+
+    pub fn precipitation(&self, mm: f64) -> String {
+        if self.imperial() {
+            format!("{}in", self.number(mm / MM_PER_INCH, 2))
+        } else {
+            format!("{}mm", self.number(mm, 1))
+        }
+    }
+
+    /// Snow depth or snowfall given in cm
+    pub fn snow(&self, cm: f64) -> String {
+        if self.imperial() {
+            format!("{}in", self.number(cm * 10.0 / MM_PER_INCH, 0))
+        } else {
+            format!("{}cm", self.number(cm, 0))
+        }
+    }
+
+    pub fn volume(&self, liters: f64) -> String {
+        if self.imperial() {
+            format!("{}qt", self.number(liters * 1.056_688, 0))
+        } else {
+            format!("{}L", self.number(liters, 0))
+        }
+    }
+
+    /// Time of day from fractional hours (e.g. 18.5 -> "18:30" or "6:30 PM")
+    pub fn time_of_day(&self, hour: f64) -> String {
+        let total_minutes = (hour * 60.0).round().rem_euclid(24.0 * 60.0) as u32;
+        self.clock_time(total_minutes / 60, total_minutes % 60)
+    }
+
+    pub fn clock_time(&self, hour: u32, minute: u32) -> String {
+        match self.clock {
+            Clock::H24 => format!("{:02}:{:02}", hour, minute),
+            Clock::H12 => {
+                let suffix = if hour < 12 { "AM" } else { "PM" };
+                let h = match hour % 12 {
+                    0 => 12,
+                    h => h,
+                };
+                format!("{}:{:02} {}", h, minute, suffix)
+            }
+        }
+    }
+
+    /// chrono format string for a time of day
+    pub fn time_format(&self) -> &'static str {
+        match self.clock {
+            Clock::H24 => "%H:%M",
+            Clock::H12 => "%-I:%M %p",
+        }
+    }
+
+    /// Local weekday, day of month and time, e.g. "Mon 19 14:00"
+    pub fn day_and_time<Tz: TimeZone>(&self, dt: &DateTime<Tz>) -> String {
+        dt.with_timezone(&Local)
+            .format(&format!("%a %d {}", self.time_format()))
+            .to_string()
+    }
+}
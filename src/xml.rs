@@ -0,0 +1,191 @@
+/// Contents of every `<tag>...</tag>` element, ignoring namespace prefixes
+pub fn tag_blocks<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let mut blocks = Vec::new();
+    let mut rest = xml;
+
+    while let Some(content_start) = find_open_tag(rest, tag) {
+        let content = &rest[content_start..];
+        let Some(end) = find_close_tag(content, tag) else {
+            break;
+        };
+        blocks.push(&content[..end]);
+        rest = &content[end..];
+    }
+
+    blocks
+}
+
+/// Unescaped text of the first `<tag>` element
+pub fn tag_text(xml: &str, tag: &str) -> Option<String> {
+    tag_blocks(xml, tag)
+        .first()
+        .map(|text| unescape_xml(text.trim()))
+}
+
+/// Start of the content following `<tag ...>` (or `<ns:tag ...>`)
+fn find_open_tag(xml: &str, tag: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(pos) = xml[offset..].find('<') {
+        let start = offset + pos;
+        let rest = &xml[start + 1..];
+        let name_end = rest.find(|c: char| c == '>' || c == '/' || c.is_whitespace())?;
+        let name = &rest[..name_end];
+        let local = name.rsplit(':').next().unwrap_or(name);
+
+        if local == tag && !name.starts_with(['/', '?', '!']) {
+            let close = rest.find('>')?;
+            // Self-closing elements have no content
+            if rest[..close].ends_with('/') {
+                offset = start + 1;
+                continue;
+            }
+            return Some(start + 1 + close + 1);
+        }
+        offset = start + 1;
+    }
+    None
+}
+
+fn find_close_tag(xml: &str, tag: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(pos) = xml[offset..].find("</") {
+        let start = offset + pos;
+        let rest = &xml[start + 2..];
+        let name_end = rest.find('>')?;
+        let name = rest[..name_end].trim();
+        if name.rsplit(':').next() == Some(tag) {
+            return Some(start);
+        }
+        offset = start + 2;
+    }
+    None
+}
+
+pub fn unescape_xml(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Attributes and content of every `<tag>` element, self-closing ones included
+pub fn elements<'a>(xml: &'a str, tag: &str) -> Vec<(&'a str, &'a str)> {
+    let mut elements = Vec::new();
+    let mut offset = 0;
+
+    while let Some(pos) = xml[offset..].find('<') {
+        let start = offset + pos + 1;
+        offset = start;
+        let rest = &xml[start..];
+        let Some(name_end) = rest.find(|c: char| c == '>' || c == '/' || c.is_whitespace()) else {
+            break;
+        };
+        let name = &rest[..name_end];
+        if name.rsplit(':').next() != Some(tag) || name.starts_with(['/', '?', '!']) {
+            continue;
+        }
+        let Some(close) = rest.find('>') else {
+            break;
+        };
+
+        let attributes = &rest[name_end..close];
+        if let Some(attributes) = attributes.strip_suffix('/') {
+            elements.push((attributes, ""));
+            offset = start + close + 1;
+            continue;
+        }
+
+        let content = &rest[close + 1..];
+        let Some(end) = find_close_tag(content, tag) else {
+            break;
+        };
+        elements.push((attributes, &content[..end]));
+        offset = start + close + 1 + end;
+    }
+
+    elements
+}
+
+/// Unescaped value of `name="..."` (or single-quoted) in an element's attributes
+pub fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+    while let Some(pos) = rest.find(name) {
+        let before = rest[..pos].chars().next_back();
+        let after = rest[pos + name.len()..].trim_start();
+        rest = &rest[pos + name.len()..];
+        if before.is_some_and(|c| !c.is_whitespace()) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=').map(str::trim_start) else {
+            continue;
+        };
+        let quote = value.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            continue;
+        }
+        let end = value[1..].find(quote)?;
+        return Some(unescape_xml(&value[1..1 + end]));
+    }
+    None
+}
+
+pub fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elements_include_self_closing_and_namespaced_ones() {
+        let xml = r#"<root><ns:pt a="1"/><pt a="2">two</pt><point a="3"/><pt/><ns:pt a='5'>five</ns:pt></root>"#;
+        let found = elements(xml, "pt");
+        assert_eq!(
+            found,
+            vec![
+                (r#" a="1""#, ""),
+                (r#" a="2""#, "two"),
+                ("", ""),
+                (" a='5'", "five"),
+            ]
+        );
+        assert!(elements(xml, "missing").is_empty());
+        // Unclosed elements end the scan
+        assert!(elements("<pt a=\"1\">text", "pt").is_empty());
+    }
+
+    #[test]
+    fn attributes_by_whole_name_in_either_quotes() {
+        let attributes = r#" lon='-71.5' xlat="1" lat = "46.8" name="Tom &amp; Jerry""#;
+        assert_eq!(attribute(attributes, "lat").as_deref(), Some("46.8"));
+        assert_eq!(attribute(attributes, "lon").as_deref(), Some("-71.5"));
+        assert_eq!(
+            attribute(attributes, "name").as_deref(),
+            Some("Tom & Jerry")
+        );
+        assert_eq!(attribute(attributes, "ele"), None);
+        assert_eq!(attribute(r#" lat=46.8"#, "lat"), None);
+    }
+
+    #[test]
+    fn tag_text_skips_prefixes_and_self_closing_tags() {
+        let xml = "<a><ele/><gpxtpx:hr> 121 </gpxtpx:hr><ele>3 &lt; 4</ele></a>";
+        assert_eq!(tag_text(xml, "hr").as_deref(), Some("121"));
+        assert_eq!(tag_text(xml, "ele").as_deref(), Some("3 < 4"));
+        assert_eq!(tag_blocks(xml, "ele"), vec!["3 &lt; 4"]);
+        assert_eq!(tag_text(xml, "time"), None);
+    }
+
+    #[test]
+    fn escaping_round_trips() {
+        let text = r#"<Les "Loups" & l'Érablière>"#;
+        assert_eq!(unescape_xml(&escape_xml(text)), text);
+        assert_eq!(unescape_xml("&amp;lt;"), "&lt;");
+    }
+}